@@ -1,8 +1,9 @@
 use clap::ArgMatches;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-use crate::cli::{CallParams, Logger, System};
+use crate::cli::{CallParams, Logger, System, Trace};
 use crate::commands::{BCommand, CmdHandler};
 use crate::error::BError;
 
@@ -13,6 +14,10 @@ pub struct Cli {
     logger: Box<dyn Logger>,
     system: Box<dyn System>,
     verbose: bool,
+    log_file: RefCell<Option<PathBuf>>,
+    trace: Trace,
+    hermetic: Cell<bool>,
+    quiet: Cell<bool>,
 }
 
 impl Cli {
@@ -58,6 +63,10 @@ impl Cli {
             logger,
             system,
             verbose,
+            log_file: RefCell::new(None),
+            trace: Trace::new(),
+            hermetic: Cell::new(false),
+            quiet: Cell::new(false),
         }
     }
 
@@ -74,15 +83,79 @@ impl Cli {
         });
         self.debug(format!("{}", cmd.as_str().trim_end()));
         //println!("{}", cmd.as_str().trim_end());
-        self.system.check_call(&CallParams {
+        let params: CallParams = CallParams {
             cmd_line: cmd_line.to_owned(),
             env: env.to_owned(),
             shell,
-        })?;
+        };
+        match self.log_file.borrow().as_ref() {
+            Some(log) => {
+                self.system.check_call_log(&params, log)?;
+            }
+            None => {
+                self.system.check_call(&params)?;
+            }
+        }
         //self.system.test(String::from(cmd.as_str().trim_end()))?;
         Ok(())
     }
 
+    /*
+     * Execute a command and return what it has written to stdout. Used
+     * when bakery needs to parse the output of a tool
+     */
+    pub fn check_output(
+        &self,
+        cmd_line: &Vec<String>,
+        env: &HashMap<String, String>,
+        shell: bool,
+    ) -> Result<String, BError> {
+        let mut cmd: String = String::new();
+        cmd_line.iter().for_each(|c| {
+            cmd.push_str(c);
+            cmd.push(' ');
+        });
+        self.debug(format!("{}", cmd.as_str().trim_end()));
+        let params: CallParams = CallParams {
+            cmd_line: cmd_line.to_owned(),
+            env: env.to_owned(),
+            shell,
+        };
+        self.system.check_output(&params)
+    }
+
+    /*
+     * When a log file is set all the output from the commands executed
+     * using check_call is written to the log file as well as the terminal
+     */
+    pub fn set_log_file(&self, log: Option<PathBuf>) {
+        *self.log_file.borrow_mut() = log;
+    }
+
+    pub fn trace(&self) -> &Trace {
+        &self.trace
+    }
+
+    /*
+     * When running a hermetic build all the containers started by bakery
+     * for executing a task are started without any network access
+     */
+    pub fn set_hermetic(&self, hermetic: bool) {
+        self.hermetic.set(hermetic);
+    }
+
+    pub fn hermetic(&self) -> bool {
+        self.hermetic.get()
+    }
+
+    /*
+     * Commands printing json on stdout are setting the cli in quiet mode
+     * so the output is not mixed with info and debug messages
+     */
+    pub fn set_quiet(&self, quiet: bool) {
+        self.quiet.set(quiet);
+    }
+
     pub fn rmdir_all(&self, path: &PathBuf) -> Result<(), BError> {
         self.system.rmdir_all(path)?;
         Ok(())
@@ -155,11 +228,13 @@ impl Cli {
     }
 
     pub fn info(&self, message: String) {
-        (*self.logger).info(message);
+        if !self.quiet.get() {
+            (*self.logger).info(message);
+        }
     }
 
     pub fn debug(&self, message: String) {
-        if self.verbose {
+        if self.verbose && !self.quiet.get() {
             (*self.logger).debug(message);
         }
     }
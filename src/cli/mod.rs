@@ -2,6 +2,7 @@ pub mod bakery;
 pub mod cli;
 pub mod logger;
 pub mod system;
+pub mod trace;
 
 pub use bakery::Bakery;
 pub use cli::Cli;
@@ -10,3 +11,4 @@ pub use logger::MockLogger;
 pub use logger::{BLogger, Logger};
 pub use system::MockSystem;
 pub use system::{BSystem, CallParams, System};
+pub use trace::{Trace, TraceSpan};
@@ -7,6 +7,7 @@ use std::collections::HashMap;
 use std::fmt;
 use std::fs;
 use std::fs::ReadDir;
+use std::io::{Read, Write};
 use std::path::PathBuf;
 use std::str;
 
@@ -41,6 +42,8 @@ impl fmt::Display for CallParams {
 #[automock]
 pub trait System {
     fn check_call(&self, params: &CallParams) -> Result<(), BError>;
+    fn check_call_log(&self, params: &CallParams, log: &PathBuf) -> Result<(), BError>;
+    fn check_output(&self, params: &CallParams) -> Result<String, BError>;
     fn init_env_file(
         &self,
         init_file: &PathBuf,
@@ -109,6 +112,94 @@ impl System for BSystem {
         Ok(())
     }
 
+    fn check_call_log(&self, params: &CallParams, log: &PathBuf) -> Result<(), BError> {
+        let mut cmd: String = String::new();
+        params.cmd_line.iter().for_each(|c| {
+            cmd.push_str(c);
+            cmd.push(' ');
+        });
+
+        let mut log_file: fs::File = fs::OpenOptions::new().create(true).append(true).open(log)?;
+        writeln!(log_file, "$ {}", cmd.as_str().trim_end())?;
+
+        if TestMode::is_test_mode() {
+            println!("{}", cmd.as_str().trim_end());
+            return Ok(());
+        }
+
+        /*
+         * Both stdout and stderr of the child is redirected to the same pipe
+         * so the order of the output is kept when it is written to the terminal
+         * and the log file. The command is dropped as soon as the child has been
+         * spawned so that the only write end left open is the one owned by the
+         * child otherwise reading from the pipe would never end.
+         */
+        let (mut reader, writer) = os_pipe::pipe()?;
+        let mut child: std::process::Child = {
+            let mut command: std::process::Command = std::process::Command::new("/bin/bash");
+            command
+                .arg("-c")
+                .arg(cmd.as_str().trim_end())
+                .stdout(writer.try_clone()?)
+                .stderr(writer)
+                .env_clear()
+                .envs(&params.env);
+            command.spawn()?
+        };
+
+        let mut stdout: std::io::Stdout = std::io::stdout();
+        let mut buffer: [u8; 8192] = [0; 8192];
+        loop {
+            let size: usize = reader.read(&mut buffer)?;
+            if size == 0 {
+                break;
+            }
+            stdout.write_all(&buffer[..size])?;
+            stdout.flush()?;
+            log_file.write_all(&buffer[..size])?;
+        }
+
+        // Wait for the command to finish
+        let status: std::process::ExitStatus = child.wait()?;
+        if !status.success() {
+            return Err(BError::CliError(format!("{}", status)));
+        }
+
+        Ok(())
+    }
+
+    fn check_output(&self, params: &CallParams) -> Result<String, BError> {
+        let mut cmd: String = String::new();
+        params.cmd_line.iter().for_each(|c| {
+            cmd.push_str(c);
+            cmd.push(' ');
+        });
+
+        if TestMode::is_test_mode() {
+            println!("{}", cmd.as_str().trim_end());
+            return Ok(String::new());
+        }
+
+        /*
+         * Only stdout is captured and returned to the caller stderr is
+         * still written to the terminal so any errors can be seen
+         */
+        let output: std::process::Output = std::process::Command::new("/bin/bash")
+            .arg("-c")
+            .arg(cmd.as_str().trim_end())
+            .stderr(os_pipe::dup_stderr()?)
+            .env_clear()
+            .envs(&params.env)
+            .output()?;
+
+        let status: std::process::ExitStatus = output.status;
+        if !status.success() {
+            return Err(BError::CliError(format!("{}", status)));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
     fn init_env_file(
         &self,
         init_file: &PathBuf,
@@ -239,6 +330,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_system_check_call_log() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let log: PathBuf = temp_dir.path().join("task.log");
+        let system: BSystem = BSystem::new();
+        let params: CallParams = CallParams {
+            cmd_line: vec![
+                "echo".to_string(),
+                "stdout".to_string(),
+                "&&".to_string(),
+                "echo".to_string(),
+                "stderr".to_string(),
+                "1>&2".to_string(),
+            ],
+            env: HashMap::new(),
+            shell: true,
+        };
+        system
+            .check_call_log(&params, &log)
+            .expect("Failed to execute command");
+        let content: String = std::fs::read_to_string(&log).expect("Failed to read log");
+        assert!(content.starts_with("$ echo stdout && echo stderr 1>&2\n"));
+        assert!(content.ends_with("stdout\nstderr\n"));
+    }
+
+    #[test]
+    fn test_system_check_output() {
+        let system: BSystem = BSystem::new();
+        let params: CallParams = CallParams {
+            cmd_line: vec![
+                "echo".to_string(),
+                "stdout".to_string(),
+                "&&".to_string(),
+                "echo".to_string(),
+                "stderr".to_string(),
+                "1>&2".to_string(),
+            ],
+            env: HashMap::new(),
+            shell: true,
+        };
+        let output: String = system
+            .check_output(&params)
+            .expect("Failed to execute command");
+        assert!(output.ends_with("stdout\n"));
+        assert!(!output.contains("stderr"));
+    }
+
     /*
     TODO: we need to fix this test not sure why it does not fail
     #[test]
@@ -0,0 +1,331 @@
+use serde_json::{json, Value};
+use std::cell::{Cell, RefCell};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::BError;
+
+/*
+ * The trace is using the Chrome trace event format which can be loaded
+ * into Perfetto (https://ui.perfetto.dev) or chrome://tracing. All the
+ * timestamps are in micro seconds since epoch so that the bakery spans
+ * and the bitbake buildstats can be shown in the same timeline.
+ */
+const BAKERY_PID: u32 = 1;
+const BITBAKE_PID: u32 = 2;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceEvent {
+    pub name: String,
+    pub cat: String,
+    pub pid: u32,
+    pub tid: u32,
+    pub ts: u64,
+    pub dur: u64,
+}
+
+impl TraceEvent {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "cat": self.cat,
+            "ph": "X",
+            "pid": self.pid,
+            "tid": self.tid,
+            "ts": self.ts,
+            "dur": self.dur,
+        })
+    }
+}
+
+pub struct TraceSpan {
+    name: String,
+    cat: String,
+    start: u64,
+}
+
+impl TraceSpan {
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+}
+
+pub struct Trace {
+    enabled: Cell<bool>,
+    events: RefCell<Vec<TraceEvent>>,
+}
+
+impl Trace {
+    pub fn now() -> u64 {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_micros() as u64,
+            Err(_err) => 0,
+        }
+    }
+
+    fn parse_buildstats_time(content: &str, key: &str) -> Option<u64> {
+        for line in content.lines() {
+            if let Some(value) = line.strip_prefix(key) {
+                return match value.trim().parse::<f64>() {
+                    Ok(seconds) => Some((seconds * 1_000_000.0) as u64),
+                    Err(_err) => None,
+                };
+            }
+        }
+        None
+    }
+
+    pub fn enable(&self) {
+        self.enabled.set(true);
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled.get()
+    }
+
+    pub fn begin(&self, name: &str, cat: &str) -> TraceSpan {
+        TraceSpan {
+            name: name.to_string(),
+            cat: cat.to_string(),
+            start: Self::now(),
+        }
+    }
+
+    pub fn end(&self, span: TraceSpan) {
+        let end: u64 = Self::now();
+        self.events.borrow_mut().push(TraceEvent {
+            name: span.name,
+            cat: span.cat,
+            pid: BAKERY_PID,
+            tid: 0,
+            ts: span.start,
+            dur: end.saturating_sub(span.start),
+        });
+    }
+
+    #[cfg(test)]
+    pub fn events(&self) -> Vec<TraceEvent> {
+        self.events.borrow().clone()
+    }
+
+    /*
+     * Read the bitbake buildstats and add a span for each recipe task that
+     * was started after 'since'. The buildstats are stored by bitbake in
+     * <tmpdir>/buildstats/<buildname>/<recipe>/<task>. To make it possible to
+     * see how many tasks were executed in parallel the tasks are spread out
+     * on as few lanes (threads) as possible without overlapping each other.
+     */
+    pub fn add_buildstats(&self, buildstats_dir: &PathBuf, since: u64) -> Result<(), BError> {
+        let mut tasks: Vec<TraceEvent> = Vec::new();
+
+        // Reading the buildstats can take some time so only do it when needed
+        if !self.enabled() || !buildstats_dir.is_dir() {
+            return Ok(());
+        }
+
+        for build in std::fs::read_dir(buildstats_dir)? {
+            let build: PathBuf = build?.path();
+            if !build.is_dir() {
+                continue;
+            }
+
+            for recipe in std::fs::read_dir(&build)? {
+                let recipe: PathBuf = recipe?.path();
+                if !recipe.is_dir() {
+                    continue;
+                }
+
+                for task in std::fs::read_dir(&recipe)? {
+                    let task: PathBuf = task?.path();
+                    if !task.is_file() {
+                        continue;
+                    }
+
+                    let content: String = match std::fs::read_to_string(&task) {
+                        Ok(content) => content,
+                        Err(_err) => continue,
+                    };
+                    let started: Option<u64> = Self::parse_buildstats_time(&content, "Started:");
+                    let ended: Option<u64> = Self::parse_buildstats_time(&content, "Ended:");
+                    match (started, ended) {
+                        (Some(started), Some(ended)) if started >= since => {
+                            tasks.push(TraceEvent {
+                                name: format!(
+                                    "{}:{}",
+                                    recipe.file_name().unwrap_or_default().to_string_lossy(),
+                                    task.file_name().unwrap_or_default().to_string_lossy()
+                                ),
+                                cat: String::from("bitbake"),
+                                pid: BITBAKE_PID,
+                                tid: 0,
+                                ts: started,
+                                dur: ended.saturating_sub(started),
+                            });
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        tasks.sort_by(|a, b| a.ts.cmp(&b.ts).then(a.name.cmp(&b.name)));
+        let mut lanes: Vec<u64> = Vec::new();
+        for task in tasks.iter_mut() {
+            let end: u64 = task.ts + task.dur;
+            match lanes.iter().position(|lane_end| *lane_end <= task.ts) {
+                Some(lane) => {
+                    lanes[lane] = end;
+                    task.tid = lane as u32;
+                }
+                None => {
+                    task.tid = lanes.len() as u32;
+                    lanes.push(end);
+                }
+            }
+        }
+
+        self.events.borrow_mut().append(&mut tasks);
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut events: Vec<Value> = vec![
+            json!({"name": "process_name", "ph": "M", "pid": BAKERY_PID, "args": {"name": "bakery"}}),
+            json!({"name": "process_name", "ph": "M", "pid": BITBAKE_PID, "args": {"name": "bitbake"}}),
+        ];
+        events.extend(self.events.borrow().iter().map(|e| e.to_json()));
+        json!({
+            "traceEvents": events,
+            "displayTimeUnit": "ms",
+        })
+    }
+
+    /*
+     * When bakery is bootstrapped into docker the trace file is written by
+     * bakery running inside of docker. The events from the trace file are
+     * then merged with the events recorded outside of docker.
+     */
+    pub fn write(&self, path: &PathBuf, merge: bool) -> Result<(), BError> {
+        let mut trace: Value = self.to_json();
+
+        if merge && path.exists() {
+            let existing: Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+            if let Some(existing_events) = existing["traceEvents"].as_array() {
+                let events: &mut Vec<Value> = trace["traceEvents"].as_array_mut().unwrap();
+                for event in existing_events {
+                    // The process names are already part of the trace
+                    if event["ph"] != "M" {
+                        events.push(event.clone());
+                    }
+                }
+            }
+        }
+
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        std::fs::write(path, serde_json::to_string_pretty(&trace)?)?;
+        Ok(())
+    }
+
+    pub fn new() -> Self {
+        Trace {
+            enabled: Cell::new(false),
+            events: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    use crate::cli::trace::TraceEvent;
+    use crate::cli::{Trace, TraceSpan};
+
+    fn helper_buildstats(dir: &PathBuf, recipe: &str, task: &str, started: f64, ended: f64) {
+        let recipe_dir: PathBuf = dir.join("20240101000000").join(recipe);
+        std::fs::create_dir_all(&recipe_dir).expect("Failed to create buildstats dir");
+        std::fs::write(
+            recipe_dir.join(task),
+            format!(
+                "Event: TaskStarted\nStarted: {:.2}\nEvent: TaskSucceeded\nEnded: {:.2}\nStatus: PASSED\n",
+                started, ended
+            ),
+        )
+        .expect("Failed to write buildstats");
+    }
+
+    #[test]
+    fn test_trace_span() {
+        let trace: Trace = Trace::new();
+        let span: TraceSpan = trace.begin("image", "task");
+        let start: u64 = span.start();
+        trace.end(span);
+        let events: Vec<TraceEvent> = trace.events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name, "image");
+        assert_eq!(events[0].cat, "task");
+        assert_eq!(events[0].ts, start);
+    }
+
+    #[test]
+    fn test_trace_buildstats() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let buildstats: PathBuf = temp_dir.path().join("buildstats");
+        helper_buildstats(&buildstats, "recipe-a-1.0-r0", "do_compile", 100.0, 110.0);
+        helper_buildstats(&buildstats, "recipe-b-1.0-r0", "do_compile", 102.0, 104.0);
+        helper_buildstats(&buildstats, "recipe-b-1.0-r0", "do_install", 105.0, 106.0);
+        // Started before the task and should be ignored
+        helper_buildstats(&buildstats, "recipe-c-1.0-r0", "do_fetch", 10.0, 20.0);
+        std::fs::write(
+            buildstats.join("20240101000000/build_stats"),
+            "Build Started: 0",
+        )
+        .expect("Failed to write build_stats");
+        let trace: Trace = Trace::new();
+        trace.enable();
+        trace
+            .add_buildstats(&buildstats, 50_000_000)
+            .expect("Failed to read buildstats");
+        let events: Vec<TraceEvent> = trace.events();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].name, "recipe-a-1.0-r0:do_compile");
+        assert_eq!(events[0].tid, 0);
+        assert_eq!(events[0].ts, 100_000_000);
+        assert_eq!(events[0].dur, 10_000_000);
+        assert_eq!(events[1].name, "recipe-b-1.0-r0:do_compile");
+        assert_eq!(events[1].tid, 1);
+        assert_eq!(events[2].name, "recipe-b-1.0-r0:do_install");
+        assert_eq!(events[2].tid, 1);
+    }
+
+    #[test]
+    fn test_trace_write_merge() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let path: PathBuf = temp_dir.path().join("trace.json");
+        let inner: Trace = Trace::new();
+        inner.end(inner.begin("image", "task"));
+        inner.write(&path, false).expect("Failed to write trace");
+        let outer: Trace = Trace::new();
+        outer.end(outer.begin("docker bootstrap", "docker"));
+        outer.write(&path, true).expect("Failed to write trace");
+        let content: Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).expect("Failed to read trace"))
+                .expect("Invalid json");
+        let names: Vec<&str> = content["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|e| e["ph"] == "X")
+            .map(|e| e["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["docker bootstrap", "image"]);
+    }
+}
@@ -16,7 +16,12 @@ impl<'a> Collector for ArchiveCollector<'a> {
     fn collect(&self, src: &PathBuf, dest: &PathBuf) -> Result<Vec<Collected>, BError> {
         let archive_name: &str = self.artifact.data().name();
         let archive_path: PathBuf = dest.join(PathBuf::from(archive_name));
-        let temp_dir: TempDir = TempDir::new("bakery-archiver")?;
+        /*
+         * The files are staged in the artifacts dir so that files collected
+         * as hardlinks or reflinks are not copied to another file system
+         */
+        std::fs::create_dir_all(dest)?;
+        let temp_dir: TempDir = TempDir::new_in(dest, ".bakery-archiver")?;
         let archive_tmp_dir: PathBuf = PathBuf::from(temp_dir.path());
         let mut collected: Vec<Collected> = vec![];
 
@@ -27,12 +32,19 @@ impl<'a> Collector for ArchiveCollector<'a> {
 
         for child in self.artifact.children().iter() {
             let collector: Box<dyn Collector> = CollectorFactory::create(child, None)?;
-            let mut c: Vec<Collected> = collector.collect(src, &archive_tmp_dir)?;
+            let mut c: Vec<Collected> =
+                collector.collect_with(src, &archive_tmp_dir, &collected)?;
             collected.append(&mut c);
         }
 
         let files: Vec<PathBuf> = collected.iter().map(|f| f.dest.clone()).collect();
-        let archiver: Archiver = Archiver::new(&archive_path)?;
+        let mut archiver: Archiver = Archiver::new(&archive_path)?;
+        if let Some(level) = self.artifact.data().level()? {
+            archiver.set_level(level)?;
+        }
+        if let Some(source_date_epoch) = self.artifact.data().reproducible()? {
+            archiver.set_reproducible(source_date_epoch);
+        }
         archiver.add_files(&files, &archive_tmp_dir)?;
         self.info(
             self.cli,
@@ -189,4 +201,79 @@ mod tests {
             assert!(c.dest.exists());
         }
     }
+
+    #[test]
+    fn test_archive_collector_level() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let task_build_dir: PathBuf = work_dir.clone().join("task/dir");
+        let files: Vec<PathBuf> = vec![task_build_dir.clone().join("file1.txt")];
+        let json_artifacts_config: &str = r#"
+        {
+            "type": "archive",
+            "name": "archive.tar.zst",
+            "level": "19",
+            "artifacts": [
+                {
+                    "source": "file1.txt"
+                }
+            ]
+        }"#;
+        let build_data: WsBuildData = Helper::setup_build_data(&work_dir, None, None);
+        let artifacts: WsArtifactsHandler = Helper::setup_collector_test_ws(
+            &work_dir,
+            &task_build_dir,
+            &files,
+            &build_data,
+            json_artifacts_config,
+        );
+        let collector: ArchiveCollector = ArchiveCollector::new(&artifacts, None);
+        let artifacts_dir: PathBuf = build_data.settings().artifacts_dir();
+        let collected: Vec<Collected> = collector
+            .collect(&task_build_dir, &artifacts_dir)
+            .expect("Failed to collect artifacts");
+        assert_eq!(
+            collected,
+            vec![Collected {
+                src: PathBuf::from(""),
+                dest: artifacts_dir.clone().join("archive.tar.zst")
+            }]
+        );
+        assert!(artifacts_dir.join("archive.tar.zst").exists());
+    }
+
+    #[test]
+    fn test_archive_collector_invalid_level() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let task_build_dir: PathBuf = work_dir.clone().join("task/dir");
+        let files: Vec<PathBuf> = vec![task_build_dir.clone().join("file1.txt")];
+        let json_artifacts_config: &str = r#"
+        {
+            "type": "archive",
+            "name": "archive.tar.gz",
+            "level": "high",
+            "artifacts": [
+                {
+                    "source": "file1.txt"
+                }
+            ]
+        }"#;
+        let build_data: WsBuildData = Helper::setup_build_data(&work_dir, None, None);
+        let artifacts: WsArtifactsHandler = Helper::setup_collector_test_ws(
+            &work_dir,
+            &task_build_dir,
+            &files,
+            &build_data,
+            json_artifacts_config,
+        );
+        let collector: ArchiveCollector = ArchiveCollector::new(&artifacts, None);
+        let result = collector.collect(&task_build_dir, &build_data.settings().artifacts_dir());
+        assert_eq!(
+            result.expect_err("Expected an error").to_string(),
+            "Invalid compression level 'high'"
+        );
+    }
 }
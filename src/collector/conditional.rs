@@ -12,14 +12,29 @@ pub struct ConditionalCollector<'a> {
 
 impl<'a> Collector for ConditionalCollector<'a> {
     fn collect(&self, src: &PathBuf, dest: &PathBuf) -> Result<Vec<Collected>, BError> {
+        self.collect_with(src, dest, &vec![])
+    }
+
+    fn collect_with(
+        &self,
+        src: &PathBuf,
+        dest: &PathBuf,
+        collected_before: &Vec<Collected>,
+    ) -> Result<Vec<Collected>, BError> {
         let condition: bool = self.artifact.data().condition();
         let mut collected: Vec<Collected> = vec![];
 
         if condition {
+            /*
+             * The children are collected to the same dest as the artifacts
+             * collected before the conditional so they are all passed on
+             */
+            let mut all: Vec<Collected> = collected_before.clone();
             for child in self.artifact.children().iter() {
                 let collector: Box<dyn Collector> = CollectorFactory::create(child, None)?;
-                let mut c: Vec<Collected> = collector.collect(src, dest)?;
-                collected.append(&mut c);
+                let c: Vec<Collected> = collector.collect_with(src, dest, &all)?;
+                all.extend(c.iter().cloned());
+                collected.extend(c);
             }
         } else {
             self.info(self.cli, "Skipping collecting condition false".to_string());
@@ -0,0 +1,289 @@
+use crate::cli::Cli;
+use crate::collector::{Collected, Collector};
+use crate::error::BError;
+use crate::workspace::WsArtifactsHandler;
+
+use indexmap::{indexmap, IndexMap};
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/*
+ * Collect the json reports created by the cve-check class and summarise
+ * the number of unpatched CVEs by severity. The severity is based on the
+ * CVSS v3 score and falls back on the CVSS v2 score if there is no v3 score.
+ */
+pub struct CveCollector<'a> {
+    artifact: &'a WsArtifactsHandler,
+    cli: Option<&'a Cli>,
+}
+
+impl<'a> Collector for CveCollector<'a> {
+    fn collect(&self, src: &PathBuf, dest: &PathBuf) -> Result<Vec<Collected>, BError> {
+        let src_path: PathBuf = src.join(PathBuf::from(self.artifact.data().source()));
+        let dest_path: PathBuf = dest.join(PathBuf::from(self.artifact.data().dest()));
+        let mut collected: Vec<Collected> = vec![];
+        let mut reports: Vec<Value> = vec![];
+
+        for f in glob::glob(&src_path.to_string_lossy())? {
+            let f: PathBuf = f?;
+            if f.is_dir() {
+                continue;
+            }
+
+            /*
+             * The deploy dir can contain other json files like the testdata
+             * so only the files looking like a cve-check report are collected
+             */
+            let report: Value = match serde_json::from_str(&std::fs::read_to_string(&f)?) {
+                Ok(report) => report,
+                Err(_err) => continue,
+            };
+            if !report["package"].is_array() {
+                continue;
+            }
+
+            let dest_file: PathBuf = dest_path.join(f.file_name().unwrap_or_default());
+            self.info(
+                self.cli,
+                format!("Copy CVE report {} => {}", f.display(), dest_file.display()),
+            );
+            std::fs::create_dir_all(&dest_path)?;
+            std::fs::copy(&f, &dest_file)?;
+            collected.push(Collected {
+                src: f.clone(),
+                dest: dest_file,
+            });
+            reports.push(report);
+        }
+
+        if collected.is_empty() {
+            return Err(BError::CollectorError(format!(
+                "No CVE reports found matching '{}'",
+                src_path.display()
+            )));
+        }
+
+        let summary: IndexMap<String, u64> = Self::summary(&reports);
+        self.info(
+            self.cli,
+            format!(
+                "Unpatched CVEs: {}",
+                summary
+                    .iter()
+                    .map(|(severity, count)| format!("{} {}", severity, count))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        );
+
+        /*
+         * The summary is generated and not collected from the build so it is
+         * not part of the collected artifacts
+         */
+        let summary_name: &str = self.artifact.data().summary();
+        if !summary_name.is_empty() {
+            let summary_file: PathBuf = dest_path.join(summary_name);
+            std::fs::create_dir_all(summary_file.parent().unwrap())?;
+            let unpatched: Map<String, Value> = summary
+                .iter()
+                .map(|(severity, count)| (severity.clone(), json!(count)))
+                .collect();
+            std::fs::write(&summary_file, json!({ "unpatched": unpatched }).to_string())?;
+            self.info(
+                self.cli,
+                format!("CVE summary written to {}", summary_file.display()),
+            );
+        }
+
+        Ok(collected)
+    }
+}
+
+impl<'a> CveCollector<'a> {
+    fn score(value: &Value) -> Option<f64> {
+        match value {
+            Value::String(s) => s.trim().parse::<f64>().ok(),
+            Value::Number(n) => n.as_f64(),
+            _ => None,
+        }
+    }
+
+    pub fn severity(issue: &Value) -> &'static str {
+        let score: Option<f64> = Self::score(&issue["scorev3"])
+            .filter(|s| *s > 0.0)
+            .or_else(|| Self::score(&issue["scorev2"]).filter(|s| *s > 0.0));
+        match score {
+            Some(s) if s >= 9.0 => "critical",
+            Some(s) if s >= 7.0 => "high",
+            Some(s) if s >= 4.0 => "medium",
+            Some(_s) => "low",
+            None => "unknown",
+        }
+    }
+
+    /*
+     * The same CVE can be listed in more then one report for example in the
+     * cve-summary and in the image report so each CVE is only counted once
+     * per package.
+     */
+    pub fn summary(reports: &Vec<Value>) -> IndexMap<String, u64> {
+        let mut summary: IndexMap<String, u64> = indexmap! {
+            String::from("critical") => 0,
+            String::from("high") => 0,
+            String::from("medium") => 0,
+            String::from("low") => 0,
+            String::from("unknown") => 0,
+        };
+        let mut counted: HashSet<(String, String)> = HashSet::new();
+
+        for report in reports.iter() {
+            for package in report["package"].as_array().unwrap_or(&vec![]) {
+                for issue in package["issue"].as_array().unwrap_or(&vec![]) {
+                    if issue["status"].as_str().unwrap_or_default() != "Unpatched" {
+                        continue;
+                    }
+
+                    let key: (String, String) = (
+                        package["name"].as_str().unwrap_or_default().to_string(),
+                        issue["id"].as_str().unwrap_or_default().to_string(),
+                    );
+                    if counted.insert(key) {
+                        *summary
+                            .entry(Self::severity(issue).to_string())
+                            .or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        summary
+    }
+
+    pub fn new(artifact: &'a WsArtifactsHandler, cli: Option<&'a Cli>) -> Self {
+        CveCollector { artifact, cli }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::{Collected, Collector, CveCollector};
+    use crate::data::WsBuildData;
+    use crate::helper::Helper;
+    use crate::workspace::WsArtifactsHandler;
+
+    use indexmap::IndexMap;
+    use serde_json::{json, Value};
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    static CVE_REPORT: &str = r#"
+    {
+        "version": "1",
+        "package": [
+            {
+                "name": "busybox",
+                "version": "1.36.1",
+                "issue": [
+                    { "id": "CVE-2022-0001", "status": "Unpatched", "scorev2": "6.8", "scorev3": "9.8" },
+                    { "id": "CVE-2022-0002", "status": "Unpatched", "scorev2": "5.0", "scorev3": "0.0" },
+                    { "id": "CVE-2022-0003", "status": "Patched", "scorev2": "5.0", "scorev3": "7.5" },
+                    { "id": "CVE-2022-0004", "status": "Unpatched", "scorev2": "0.0", "scorev3": "0.0" }
+                ]
+            },
+            {
+                "name": "zlib",
+                "version": "1.3",
+                "issue": [
+                    { "id": "CVE-2023-0001", "status": "Unpatched", "scorev3": "7.5" },
+                    { "id": "CVE-2023-0002", "status": "Ignored", "scorev3": "3.1" }
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_cve_collector_summary() {
+        let report: Value = serde_json::from_str(CVE_REPORT).unwrap();
+        let summary: IndexMap<String, u64> =
+            CveCollector::summary(&vec![report.clone(), report.clone()]);
+        assert_eq!(summary.get("critical").unwrap(), &1);
+        assert_eq!(summary.get("high").unwrap(), &1);
+        assert_eq!(summary.get("medium").unwrap(), &1);
+        assert_eq!(summary.get("low").unwrap(), &0);
+        assert_eq!(summary.get("unknown").unwrap(), &1);
+    }
+
+    #[test]
+    fn test_cve_collector() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let task_build_dir: PathBuf = work_dir.clone().join("task/dir");
+        let deploy_dir: PathBuf = task_build_dir.join("tmp/deploy/images/qemux86-64");
+        std::fs::create_dir_all(&deploy_dir).unwrap();
+        std::fs::write(deploy_dir.join("test-image.rootfs.json"), CVE_REPORT).unwrap();
+        std::fs::write(deploy_dir.join("test-image.testdata.json"), "{}").unwrap();
+        let json_artifacts_config: &str = r#"
+        {
+            "type": "cve",
+            "summary": "cve-summary.json",
+            "source": "tmp/deploy/images/*/*.json",
+            "dest": "cve"
+        }"#;
+        let build_data: WsBuildData = Helper::setup_build_data(&work_dir, None, None);
+        let artifacts: WsArtifactsHandler = Helper::setup_collector_test_ws(
+            &work_dir,
+            &task_build_dir,
+            &vec![],
+            &build_data,
+            json_artifacts_config,
+        );
+        let collector: CveCollector = CveCollector::new(&artifacts, None);
+        assert!(collector.verify_attributes().is_ok());
+        let collected: Vec<Collected> = collector
+            .collect(&task_build_dir, &build_data.settings().artifacts_dir())
+            .expect("Failed to collect artifacts");
+        let cve_dir: PathBuf = build_data.settings().artifacts_dir().join("cve");
+        assert_eq!(
+            collected,
+            vec![Collected {
+                src: deploy_dir.join("test-image.rootfs.json"),
+                dest: cve_dir.join("test-image.rootfs.json"),
+            }]
+        );
+        let summary: Value = serde_json::from_str(
+            &std::fs::read_to_string(cve_dir.join("cve-summary.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            summary,
+            json!({ "unpatched": { "critical": 1, "high": 1, "medium": 1, "low": 0, "unknown": 1 } })
+        );
+    }
+
+    #[test]
+    fn test_cve_collector_no_reports() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let task_build_dir: PathBuf = work_dir.clone().join("task/dir");
+        let json_artifacts_config: &str = r#"
+        {
+            "type": "cve",
+            "source": "tmp/deploy/images/*/*.json"
+        }"#;
+        let build_data: WsBuildData = Helper::setup_build_data(&work_dir, None, None);
+        let artifacts: WsArtifactsHandler = Helper::setup_collector_test_ws(
+            &work_dir,
+            &task_build_dir,
+            &vec![],
+            &build_data,
+            json_artifacts_config,
+        );
+        let collector: CveCollector = CveCollector::new(&artifacts, None);
+        assert!(collector
+            .collect(&task_build_dir, &build_data.settings().artifacts_dir())
+            .is_err());
+    }
+}
@@ -21,7 +21,7 @@ impl<'a> Collector for DirectoryCollector<'a> {
         );
         for child in self.artifact.children().iter() {
             let collector: Box<dyn Collector> = CollectorFactory::create(child, None)?;
-            let mut c: Vec<Collected> = collector.collect(src, &directory_path)?;
+            let mut c: Vec<Collected> = collector.collect_with(src, &directory_path, &collected)?;
             collected.append(&mut c);
         }
         self.info(
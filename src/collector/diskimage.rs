@@ -0,0 +1,250 @@
+use crate::cli::Cli;
+use crate::collector::{Collected, Collector};
+use crate::error::BError;
+use crate::fs::{Archiver, BlockMap, Compressor};
+use crate::workspace::WsArtifactsHandler;
+
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+/*
+ * Collect a disk image like a wic or img file together with a bmap file
+ * that can be used by bmaptool to only write the blocks containing data
+ * when flashing the image. The image is read once and is copied or
+ * compressed while the bmap is created. An uncompressed copy is written
+ * as a sparse file skipping the zero blocks.
+ */
+pub struct DiskImageCollector<'a> {
+    artifact: &'a WsArtifactsHandler,
+    cli: Option<&'a Cli>,
+}
+
+impl<'a> Collector for DiskImageCollector<'a> {
+    fn collect(&self, src: &PathBuf, dest: &PathBuf) -> Result<Vec<Collected>, BError> {
+        let src_path: PathBuf = src.join(PathBuf::from(self.artifact.data().source()));
+        let dest_path: PathBuf = dest.join(PathBuf::from(self.artifact.data().dest()));
+        let compression: &str = self.artifact.data().compression();
+        let level: Option<u32> = self.artifact.data().level()?;
+        let mut collected: Vec<Collected> = vec![];
+
+        if let Some(level) = level {
+            match Archiver::level_range(compression) {
+                Some((min, max)) if level >= min && level <= max => {}
+                Some((min, max)) => {
+                    return Err(BError::CollectorError(format!(
+                        "Invalid compression level {} for '{}' expected {}-{}!",
+                        level, compression, min, max
+                    )));
+                }
+                None => {
+                    return Err(BError::CollectorError(String::from(
+                        "Compression level is not supported for an uncompressed disk image!",
+                    )));
+                }
+            }
+        }
+
+        for image in glob::glob(&src_path.to_string_lossy())? {
+            let image: PathBuf = image?;
+            if image.is_dir() {
+                continue;
+            }
+
+            let image_name: String = image
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            let dest_image: PathBuf = match compression.is_empty() {
+                true => dest_path.join(&image_name),
+                false => dest_path.join(format!("{}.{}", image_name, compression)),
+            };
+            let dest_bmap: PathBuf = dest_path.join(format!("{}.bmap", image_name));
+            self.info(
+                self.cli,
+                format!(
+                    "Copy disk image {} => {}",
+                    image.display(),
+                    dest_image.display()
+                ),
+            );
+
+            std::fs::create_dir_all(&dest_path)?;
+            let bmap: BlockMap;
+            if compression.is_empty() {
+                let mut file: File = File::create(&dest_image)?;
+                bmap = BlockMap::scan(&image, |data: &[u8], zero: bool| {
+                    match zero {
+                        true => {
+                            file.seek(SeekFrom::Current(data.len() as i64))?;
+                        }
+                        false => {
+                            file.write_all(data)?;
+                        }
+                    }
+                    Ok(())
+                })?;
+                /*
+                 * If the image is ending with zero blocks the size has to be
+                 * set since nothing has been written
+                 */
+                file.set_len(bmap.image_size())?;
+            } else {
+                let mut enc: Compressor =
+                    Archiver::compressor(File::create(&dest_image)?, compression, level)?;
+                bmap = BlockMap::scan(&image, |data: &[u8], _zero: bool| {
+                    enc.write_all(data)?;
+                    Ok(())
+                })?;
+                enc.finish()?;
+            }
+            bmap.write(&dest_bmap)?;
+
+            self.info(
+                self.cli,
+                format!(
+                    "Created bmap {} with {} of {} blocks mapped",
+                    dest_bmap.display(),
+                    bmap.mapped_blocks_count(),
+                    bmap.blocks_count()
+                ),
+            );
+            collected.push(Collected {
+                src: image.clone(),
+                dest: dest_image,
+            });
+            collected.push(Collected {
+                src: image.clone(),
+                dest: dest_bmap,
+            });
+        }
+
+        if collected.is_empty() {
+            return Err(BError::CollectorError(format!(
+                "No disk images found matching '{}'",
+                src_path.display()
+            )));
+        }
+
+        Ok(collected)
+    }
+
+    fn verify_attributes(&self) -> Result<(), BError> {
+        match self.artifact.data().compression() {
+            "" | "gz" | "xz" | "zst" => Ok(()),
+            compression => Err(BError::ValueError(format!(
+                "Invalid compression '{}' for disk image, expected gz, xz or zst!",
+                compression
+            ))),
+        }
+    }
+}
+
+impl<'a> DiskImageCollector<'a> {
+    pub fn new(artifact: &'a WsArtifactsHandler, cli: Option<&'a Cli>) -> Self {
+        DiskImageCollector { artifact, cli }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::{Collected, Collector, DiskImageCollector};
+    use crate::data::WsBuildData;
+    use crate::helper::Helper;
+    use crate::workspace::WsArtifactsHandler;
+
+    use std::io::Read;
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    fn helper_test_disk_image_collector(
+        work_dir: &PathBuf,
+        json_artifacts_config: &str,
+    ) -> (Vec<u8>, Vec<Collected>) {
+        let task_build_dir: PathBuf = work_dir.clone().join("task/dir");
+        let deploy_dir: PathBuf = task_build_dir.join("tmp/deploy/images/qemux86-64");
+        let mut image: Vec<u8> = vec![0; 4096 * 16];
+        image[0..512].fill(0xaa);
+        image[4096 * 8..4096 * 9].fill(0x55);
+        std::fs::create_dir_all(&deploy_dir).unwrap();
+        std::fs::write(deploy_dir.join("core-image-minimal.wic"), &image).unwrap();
+        let build_data: WsBuildData = Helper::setup_build_data(work_dir, None, None);
+        let artifacts: WsArtifactsHandler = Helper::setup_collector_test_ws(
+            work_dir,
+            &task_build_dir,
+            &vec![],
+            &build_data,
+            json_artifacts_config,
+        );
+        let collector: DiskImageCollector = DiskImageCollector::new(&artifacts, None);
+        assert!(collector.verify_attributes().is_ok());
+        let collected: Vec<Collected> = collector
+            .collect(&task_build_dir, &build_data.settings().artifacts_dir())
+            .expect("Failed to collect artifacts");
+        (image, collected)
+    }
+
+    #[test]
+    fn test_disk_image_collector() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let (image, collected) = helper_test_disk_image_collector(
+            &work_dir,
+            r#"
+            {
+                "type": "disk-image",
+                "source": "tmp/deploy/images/qemux86-64/*.wic",
+                "dest": "images"
+            }"#,
+        );
+        let images_dir: PathBuf = work_dir.join("artifacts/images");
+        assert_eq!(
+            collected
+                .iter()
+                .map(|c| c.dest.clone())
+                .collect::<Vec<PathBuf>>(),
+            vec![
+                images_dir.join("core-image-minimal.wic"),
+                images_dir.join("core-image-minimal.wic.bmap")
+            ]
+        );
+        assert_eq!(
+            std::fs::read(images_dir.join("core-image-minimal.wic")).unwrap(),
+            image
+        );
+        let bmap: String =
+            std::fs::read_to_string(images_dir.join("core-image-minimal.wic.bmap")).unwrap();
+        assert!(bmap.contains("<BlocksCount> 16 </BlocksCount>"));
+        assert!(bmap.contains("<MappedBlocksCount> 2 </MappedBlocksCount>"));
+    }
+
+    #[test]
+    fn test_disk_image_collector_compressed() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let (image, collected) = helper_test_disk_image_collector(
+            &work_dir,
+            r#"
+            {
+                "type": "disk-image",
+                "source": "tmp/deploy/images/qemux86-64/*.wic",
+                "compression": "gz",
+                "level": "9"
+            }"#,
+        );
+        let compressed: PathBuf = work_dir.join("artifacts/core-image-minimal.wic.gz");
+        assert_eq!(collected[0].dest, compressed);
+        assert_eq!(
+            collected[1].dest,
+            work_dir.join("artifacts/core-image-minimal.wic.bmap")
+        );
+        let mut decompressed: Vec<u8> = vec![];
+        flate2::read::GzDecoder::new(std::fs::File::open(&compressed).unwrap())
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, image);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::cli::Cli;
+use crate::collector::{Collected, Collector};
+use crate::error::BError;
+use crate::fs::Archiver;
+use crate::workspace::WsArtifactsHandler;
+
+use std::path::{Path, PathBuf};
+
+/*
+ * Extract files from an archive in the task build dir like a rootfs
+ * tarball or an SDK archive. The archive can be any of the archives
+ * supported by the Archiver. The include and exclude patterns are matched
+ * against the paths in the archive and the strip components are removed
+ * from the path before the file is written to the dest dir.
+ */
+pub struct ExtractCollector<'a> {
+    artifact: &'a WsArtifactsHandler,
+    cli: Option<&'a Cli>,
+}
+
+impl<'a> Collector for ExtractCollector<'a> {
+    fn collect(&self, src: &PathBuf, dest: &PathBuf) -> Result<Vec<Collected>, BError> {
+        let src_path: PathBuf = src.join(PathBuf::from(self.artifact.data().source()));
+        let dest_path: PathBuf = dest.join(PathBuf::from(self.artifact.data().dest()));
+        let include: Vec<glob::Pattern> = Self::patterns(self.artifact.data().include())?;
+        let exclude: Vec<glob::Pattern> = Self::patterns(self.artifact.data().exclude())?;
+        let strip_components: usize = self.artifact.data().strip_components()?;
+        let mut collected: Vec<Collected> = vec![];
+
+        let filter = |path: &Path| -> Option<PathBuf> {
+            if !include.is_empty() && !include.iter().any(|p| p.matches_path(path)) {
+                return None;
+            }
+            if exclude.iter().any(|p| p.matches_path(path)) {
+                return None;
+            }
+            let stripped: PathBuf = path.components().skip(strip_components).collect();
+            match stripped.as_os_str().is_empty() {
+                true => None,
+                false => Some(stripped),
+            }
+        };
+
+        for archive_path in glob::glob(&src_path.to_string_lossy())? {
+            let archive_path: PathBuf = archive_path?;
+            let archiver: Archiver = Archiver::new(&archive_path)?;
+            self.info(
+                self.cli,
+                format!(
+                    "Extract archive {} => {}",
+                    archive_path.display(),
+                    dest_path.display()
+                ),
+            );
+            let extracted: Vec<PathBuf> = archiver.extract(&dest_path, &filter)?;
+            self.info(
+                self.cli,
+                format!(
+                    "Extracted {} file(s) from '{}'",
+                    extracted.len(),
+                    archiver.name()
+                ),
+            );
+            collected.extend(extracted.into_iter().map(|f| Collected {
+                src: archive_path.clone(),
+                dest: f,
+            }));
+        }
+
+        if collected.is_empty() {
+            return Err(BError::CollectorError(format!(
+                "No files extracted from '{}'",
+                src_path.display()
+            )));
+        }
+
+        Ok(collected)
+    }
+}
+
+impl<'a> ExtractCollector<'a> {
+    fn patterns(patterns: &Vec<String>) -> Result<Vec<glob::Pattern>, BError> {
+        let mut compiled: Vec<glob::Pattern> = vec![];
+        for p in patterns.iter() {
+            compiled.push(glob::Pattern::new(p)?);
+        }
+        Ok(compiled)
+    }
+
+    pub fn new(artifact: &'a WsArtifactsHandler, cli: Option<&'a Cli>) -> Self {
+        ExtractCollector { artifact, cli }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::{Collected, Collector, ExtractCollector};
+    use crate::data::WsBuildData;
+    use crate::error::BError;
+    use crate::fs::Archiver;
+    use crate::helper::Helper;
+    use crate::workspace::WsArtifactsHandler;
+
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    fn helper_test_extract_collector(
+        work_dir: &PathBuf,
+        json_artifacts_config: &str,
+    ) -> Result<Vec<PathBuf>, BError> {
+        let task_build_dir: PathBuf = work_dir.clone().join("task/dir");
+        let rootfs_dir: PathBuf = work_dir.join("rootfs");
+        let files: Vec<PathBuf> = vec![
+            rootfs_dir.join("boot/zImage"),
+            rootfs_dir.join("boot/dtbs/board-a.dtb"),
+            rootfs_dir.join("boot/dtbs/board-a-test.dtb"),
+            rootfs_dir.join("etc/os-release"),
+        ];
+        Helper::create_test_files(&files);
+        let archiver: Archiver = Archiver::new(
+            &task_build_dir.join("tmp/deploy/images/qemux86-64/core-image-minimal.rootfs.tar.bz2"),
+        )?;
+        archiver.add_files(&files, &rootfs_dir)?;
+        let build_data: WsBuildData = Helper::setup_build_data(work_dir, None, None);
+        let artifacts: WsArtifactsHandler = Helper::setup_collector_test_ws(
+            work_dir,
+            &task_build_dir,
+            &vec![],
+            &build_data,
+            json_artifacts_config,
+        );
+        let collector: ExtractCollector = ExtractCollector::new(&artifacts, None);
+        let artifacts_dir: PathBuf = build_data.settings().artifacts_dir();
+        let collected: Vec<Collected> = collector.collect(&task_build_dir, &artifacts_dir)?;
+        let mut dest: Vec<PathBuf> = collected
+            .iter()
+            .map(|c| c.dest.strip_prefix(&artifacts_dir).unwrap().to_path_buf())
+            .collect();
+        dest.sort();
+        Ok(dest)
+    }
+
+    #[test]
+    fn test_extract_collector() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let collected: Vec<PathBuf> = helper_test_extract_collector(
+            &work_dir,
+            r#"
+            {
+                "type": "extract",
+                "source": "tmp/deploy/images/qemux86-64/*.rootfs.tar.bz2",
+                "dest": "kernel",
+                "include": [ "boot/*" ],
+                "exclude": [ "*-test.dtb" ],
+                "strip_components": "1"
+            }"#,
+        )
+        .expect("Failed to collect artifacts");
+        assert_eq!(
+            collected,
+            vec![
+                PathBuf::from("kernel/dtbs/board-a.dtb"),
+                PathBuf::from("kernel/zImage"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_collector_no_files() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let result: Result<Vec<PathBuf>, BError> = helper_test_extract_collector(
+            &work_dir,
+            r#"
+            {
+                "type": "extract",
+                "source": "tmp/deploy/images/qemux86-64/*.rootfs.tar.bz2",
+                "include": [ "lib/modules/*" ]
+            }"#,
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!(
+                "No files extracted from '{}'",
+                work_dir
+                    .join("task/dir/tmp/deploy/images/qemux86-64/*.rootfs.tar.bz2")
+                    .display()
+            )
+        );
+    }
+}
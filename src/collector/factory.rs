@@ -1,7 +1,8 @@
 use crate::cli::Cli;
 use crate::collector::{
-    ArchiveCollector, Collector, ConditionalCollector, DirectoryCollector, FileCollector,
-    LinkCollector, ManifestCollector,
+    ArchiveCollector, Collector, ConditionalCollector, CveCollector, DirectoryCollector,
+    DiskImageCollector, ExtractCollector, FileCollector, LicenseBundleCollector, LinkCollector,
+    ManifestCollector, SbomCollector, TemplateCollector,
 };
 use crate::data::AType;
 use crate::error::BError;
@@ -34,6 +35,24 @@ impl CollectorFactory {
             AType::Conditional => {
                 collector = Box::new(ConditionalCollector::new(artifact, cli));
             }
+            AType::Cve => {
+                collector = Box::new(CveCollector::new(artifact, cli));
+            }
+            AType::Sbom => {
+                collector = Box::new(SbomCollector::new(artifact, cli));
+            }
+            AType::LicenseBundle => {
+                collector = Box::new(LicenseBundleCollector::new(artifact, cli));
+            }
+            AType::Template => {
+                collector = Box::new(TemplateCollector::new(artifact, cli));
+            }
+            AType::Extract => {
+                collector = Box::new(ExtractCollector::new(artifact, cli));
+            }
+            AType::DiskImage => {
+                collector = Box::new(DiskImageCollector::new(artifact, cli));
+            }
         }
         collector.verify_attributes()?;
         Ok(collector)
@@ -3,7 +3,10 @@ use crate::collector::{Collected, Collector};
 use crate::error::BError;
 use crate::workspace::WsArtifactsHandler;
 
+use std::collections::HashSet;
+use std::os::fd::AsRawFd;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 pub struct FileCollector<'a> {
     artifact: &'a WsArtifactsHandler,
@@ -15,10 +18,29 @@ impl<'a> Collector for FileCollector<'a> {
         let dest_str: &str = self.artifact.data().dest();
         let src_path: PathBuf = src.join(PathBuf::from(self.artifact.data().source()));
         let dest_path: PathBuf = dest.join(PathBuf::from(dest_str));
-        let files: Vec<PathBuf> = self.list_files(&src_path)?;
-        let base_dir: &Path = src_path.parent().unwrap();
+        let base_dir: PathBuf = self.base_dir(&src_path);
+        let mut files: Vec<PathBuf> = vec![];
         let mut collected: Vec<Collected> = vec![];
 
+        /*
+         * A source ending with a recursive pattern is matching both the dirs
+         * and the files in them so a file can be listed more than once
+         */
+        let mut listed: HashSet<PathBuf> = HashSet::new();
+        for f in self.list_files(&src_path)? {
+            if listed.insert(f.clone()) && !self.is_excluded(&f, &base_dir)? {
+                files.push(f);
+            }
+        }
+
+        if files.is_empty() && self.artifact.data().optional() {
+            self.info(
+                self.cli,
+                format!("No files matching optional source '{}'", src_path.display()),
+            );
+            return Ok(collected);
+        }
+
         if files.is_empty() && !src_path.exists() {
             return Err(BError::IOError(format!(
                 "File '{}' dose not exists",
@@ -26,27 +48,26 @@ impl<'a> Collector for FileCollector<'a> {
             )));
         }
 
+        self.verify_count(files.len(), &src_path)?;
+
         for f in files.iter() {
             let mut dest_file: PathBuf = dest_path.clone();
             if self.is_dir(&dest_path, dest_str) {
-                let src_prefix: &Path = f.strip_prefix(base_dir)?;
+                let src_prefix: &Path = f.strip_prefix(&base_dir)?;
                 //println!("Prefix: {}", src_prefix.display());
                 dest_file = dest_file.join(PathBuf::from(src_prefix));
             }
+            dest_file = self.rename(&dest_file)?;
 
-            if !f.exists() {
+            if !f.exists() && !self.is_symlink(f) {
                 return Err(BError::IOError(format!(
                     "File '{}' dose not exists",
                     f.display()
                 )));
             }
 
-            self.info(
-                self.cli,
-                format!("Copy file {} => {}", f.display(), dest_file.display()),
-            );
             std::fs::create_dir_all(dest_file.parent().unwrap())?;
-            std::fs::copy(f, &dest_file)?;
+            self.transfer(f, &dest_file)?;
             collected.push(Collected {
                 src: f.clone(),
                 dest: dest_file.clone(),
@@ -68,6 +89,193 @@ impl<'a> Collector for FileCollector<'a> {
 }
 
 impl<'a> FileCollector<'a> {
+    /*
+     * The files are collected relative to the part of the source path
+     * leading up to the first glob pattern so that 'images/**/*.dtb' is
+     * keeping the dirs under images. If there is no pattern in the source
+     * the files are collected relative to the parent of the source.
+     */
+    fn base_dir(&self, src_path: &PathBuf) -> PathBuf {
+        let mut base_dir: PathBuf = PathBuf::new();
+        for component in src_path.components() {
+            let name: String = component.as_os_str().to_string_lossy().to_string();
+            if name.contains(['*', '?', '[']) {
+                return base_dir;
+            }
+            base_dir.push(component);
+        }
+        src_path.parent().unwrap_or(Path::new("")).to_path_buf()
+    }
+
+    /*
+     * An exclude pattern is matched against the path relative to the base
+     * dir and against the file name
+     */
+    fn is_excluded(&self, file: &PathBuf, base_dir: &PathBuf) -> Result<bool, BError> {
+        let relative: &Path = file.strip_prefix(base_dir).unwrap_or(file);
+        for exclude in self.artifact.data().exclude().iter() {
+            let pattern: glob::Pattern = glob::Pattern::new(exclude)?;
+            if pattern.matches_path(relative)
+                || pattern.matches(&file.file_name().unwrap_or_default().to_string_lossy())
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn verify_count(&self, count: usize, src_path: &PathBuf) -> Result<(), BError> {
+        if let Some(min) = self.artifact.data().min()? {
+            if count < min {
+                return Err(BError::CollectorError(format!(
+                    "Expected at least {} file(s) matching '{}' but found {}",
+                    min,
+                    src_path.display(),
+                    count
+                )));
+            }
+        }
+
+        if let Some(max) = self.artifact.data().max()? {
+            if count > max {
+                return Err(BError::CollectorError(format!(
+                    "Expected at most {} file(s) matching '{}' but found {}",
+                    max,
+                    src_path.display(),
+                    count
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /*
+     * Apply the rename rules in order to the name of the collected file
+     */
+    fn rename(&self, dest_file: &PathBuf) -> Result<PathBuf, BError> {
+        let mut name: String = dest_file
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        for (pattern, replace) in self.artifact.data().rename().iter() {
+            let regex: regex::Regex = regex::Regex::new(pattern)
+                .map_err(|err| BError::CollectorError(err.to_string()))?;
+            name = regex.replace_all(&name, replace.as_str()).to_string();
+        }
+        Ok(dest_file.with_file_name(name))
+    }
+
+    fn is_symlink(&self, path: &PathBuf) -> bool {
+        match std::fs::symlink_metadata(path) {
+            Ok(metadata) => metadata.file_type().is_symlink(),
+            Err(_err) => false,
+        }
+    }
+
+    /*
+     * Clone the file using the FICLONE ioctl which is supported by file
+     * systems like btrfs and xfs. The clone is sharing the data blocks with
+     * the source until any of the files are modified.
+     */
+    fn reflink(&self, src: &PathBuf, dest: &PathBuf) -> std::io::Result<()> {
+        let src_file: std::fs::File = std::fs::File::open(src)?;
+        let dest_file: std::fs::File = std::fs::File::create(dest)?;
+        let result: libc::c_int = unsafe {
+            libc::ioctl(
+                dest_file.as_raw_fd(),
+                libc::FICLONE as _,
+                src_file.as_raw_fd(),
+            )
+        };
+        if result != 0 {
+            let err: std::io::Error = std::io::Error::last_os_error();
+            std::fs::remove_file(dest)?;
+            return Err(err);
+        }
+        dest_file.set_permissions(src_file.metadata()?.permissions())?;
+        Ok(())
+    }
+
+    /*
+     * Transfer the file to the artifacts dir using the mode of the
+     * artifact. A hardlink or reflink is only possible if the source and
+     * the artifacts dir are on the same file system if not the file is
+     * copied instead.
+     */
+    fn transfer(&self, src: &PathBuf, dest: &PathBuf) -> Result<(), BError> {
+        let mode: &str = self.artifact.data().mode();
+
+        /*
+         * The dest is always removed so that a dest that is a hardlink from
+         * an earlier collect is not written through when copying the file
+         */
+        if std::fs::symlink_metadata(dest).is_ok() {
+            std::fs::remove_file(dest)?;
+        }
+
+        match mode {
+            "hardlink" => {
+                self.info(
+                    self.cli,
+                    format!("Hardlink file {} => {}", src.display(), dest.display()),
+                );
+                if let Err(err) = std::fs::hard_link(src, dest) {
+                    self.info(
+                        self.cli,
+                        format!("Failed to hardlink file, {} copying it instead", err),
+                    );
+                    std::fs::copy(src, dest)?;
+                }
+            }
+            "reflink" => {
+                self.info(
+                    self.cli,
+                    format!("Reflink file {} => {}", src.display(), dest.display()),
+                );
+                if let Err(err) = self.reflink(src, dest) {
+                    self.info(
+                        self.cli,
+                        format!("Failed to reflink file, {} copying it instead", err),
+                    );
+                    std::fs::copy(src, dest)?;
+                }
+            }
+            "preserve" => {
+                if self.is_symlink(src) {
+                    let target: PathBuf = std::fs::read_link(src)?;
+                    self.info(
+                        self.cli,
+                        format!("Link file {} => {}", dest.display(), target.display()),
+                    );
+                    std::os::unix::fs::symlink(&target, dest)?;
+                    return Ok(());
+                }
+
+                self.info(
+                    self.cli,
+                    format!("Copy file {} => {}", src.display(), dest.display()),
+                );
+                std::fs::copy(src, dest)?;
+                let modified: SystemTime = std::fs::metadata(src)?.modified()?;
+                std::fs::File::options()
+                    .write(true)
+                    .open(dest)?
+                    .set_modified(modified)?;
+            }
+            _ => {
+                self.info(
+                    self.cli,
+                    format!("Copy file {} => {}", src.display(), dest.display()),
+                );
+                std::fs::copy(src, dest)?;
+            }
+        }
+
+        Ok(())
+    }
+
     fn is_dir(&self, dest_path: &PathBuf, dest_str: &str) -> bool {
         let file_name = dest_path.file_name();
 
@@ -92,11 +300,26 @@ impl<'a> FileCollector<'a> {
         match glob_pattern_path.to_str() {
             Some(pattern) => {
                 //println!("pattern: {:?}", pattern);
-                for entry in glob::glob(pattern)? {
+                /*
+                 * A trailing '**' is only matching the sub dirs so the files
+                 * directly in the dir would be missed
+                 */
+                let pattern: String = match pattern.ends_with("**") {
+                    true => format!("{}/*", pattern),
+                    false => pattern.to_string(),
+                };
+                for entry in glob::glob(&pattern)? {
                     match entry {
                         Ok(path) => {
                             //println!("{:?}", path.clone().display());
-                            if path.is_dir() {
+                            /*
+                             * When preserving the files a link to a dir is
+                             * collected as a link instead of the content
+                             */
+                            if path.is_dir()
+                                && !(self.artifact.data().mode() == "preserve"
+                                    && self.is_symlink(&path))
+                            {
                                 let mut f: Vec<PathBuf> = self.list_files(&path.join("*"))?;
                                 files.append(&mut f);
                             } else {
@@ -132,9 +355,32 @@ mod tests {
     use crate::workspace::WsArtifactsHandler;
 
     use indexmap::{indexmap, IndexMap};
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
     use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
     use tempdir::TempDir;
 
+    fn helper_test_file_collector_mode(
+        work_dir: &PathBuf,
+        task_build_dir: &PathBuf,
+        json_artifacts_config: &str,
+    ) -> (WsBuildData, Vec<Collected>) {
+        let files: Vec<PathBuf> = vec![task_build_dir.join("images/image.wic")];
+        let build_data: WsBuildData = Helper::setup_build_data(work_dir, None, None);
+        let artifacts: WsArtifactsHandler = Helper::setup_collector_test_ws(
+            work_dir,
+            task_build_dir,
+            &files,
+            &build_data,
+            json_artifacts_config,
+        );
+        let collector: FileCollector = FileCollector::new(&artifacts, None);
+        let collected: Vec<Collected> = collector
+            .collect(task_build_dir, &build_data.settings().artifacts_dir())
+            .expect("Failed to collect artifacts");
+        (build_data, collected)
+    }
+
     #[test]
     fn test_file_collector_source() {
         let src_file_name: &str = "file.txt";
@@ -448,4 +694,292 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_file_collector_mode_hardlink() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let task_build_dir: PathBuf = work_dir.clone().join("task/dir");
+        let (build_data, _collected) = helper_test_file_collector_mode(
+            &work_dir,
+            &task_build_dir,
+            r#"{ "source": "images/image.wic", "mode": "hardlink" }"#,
+        );
+        let src: std::fs::Metadata =
+            std::fs::metadata(task_build_dir.join("images/image.wic")).unwrap();
+        let dest: std::fs::Metadata =
+            std::fs::metadata(build_data.settings().artifacts_dir().join("image.wic")).unwrap();
+        assert_eq!(src.ino(), dest.ino());
+        assert_eq!(dest.nlink(), 2);
+    }
+
+    #[test]
+    fn test_file_collector_mode_hardlink_then_copy() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let task_build_dir: PathBuf = work_dir.clone().join("task/dir");
+        helper_test_file_collector_mode(
+            &work_dir,
+            &task_build_dir,
+            r#"{ "source": "images/image.wic", "mode": "hardlink" }"#,
+        );
+        let (build_data, _collected) = helper_test_file_collector_mode(
+            &work_dir,
+            &task_build_dir,
+            r#"{ "source": "images/image.wic", "mode": "copy" }"#,
+        );
+        let src: std::fs::Metadata =
+            std::fs::metadata(task_build_dir.join("images/image.wic")).unwrap();
+        let dest: std::fs::Metadata =
+            std::fs::metadata(build_data.settings().artifacts_dir().join("image.wic")).unwrap();
+        assert_ne!(src.ino(), dest.ino());
+        assert_eq!(src.nlink(), 1);
+        assert_eq!(dest.nlink(), 1);
+    }
+
+    #[test]
+    fn test_file_collector_mode_reflink() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let task_build_dir: PathBuf = work_dir.clone().join("task/dir");
+        let (build_data, _collected) = helper_test_file_collector_mode(
+            &work_dir,
+            &task_build_dir,
+            r#"{ "source": "images/image.wic", "mode": "reflink" }"#,
+        );
+        // If the file system is not supporting reflinks the file is copied
+        assert_eq!(
+            std::fs::read(task_build_dir.join("images/image.wic")).unwrap(),
+            std::fs::read(build_data.settings().artifacts_dir().join("image.wic")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_file_collector_mode_preserve() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let task_build_dir: PathBuf = work_dir.clone().join("task/dir");
+        let images_dir: PathBuf = task_build_dir.join("images");
+        std::fs::create_dir_all(&images_dir).unwrap();
+        std::fs::write(images_dir.join("image-1.0.wic"), "image").unwrap();
+        std::fs::set_permissions(
+            images_dir.join("image-1.0.wic"),
+            std::fs::Permissions::from_mode(0o750),
+        )
+        .unwrap();
+        let modified: SystemTime = SystemTime::UNIX_EPOCH + Duration::from_secs(1700000000);
+        std::fs::File::options()
+            .write(true)
+            .open(images_dir.join("image-1.0.wic"))
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        std::os::unix::fs::symlink("image-1.0.wic", images_dir.join("image.wic.link")).unwrap();
+        let (build_data, collected) = helper_test_file_collector_mode(
+            &work_dir,
+            &task_build_dir,
+            r#"{ "source": "images/image*", "dest": "images/", "mode": "preserve" }"#,
+        );
+        let artifacts_dir: PathBuf = build_data.settings().artifacts_dir().join("images");
+        assert_eq!(collected.len(), 3);
+        let link: PathBuf = artifacts_dir.join("image.wic.link");
+        assert!(std::fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(
+            std::fs::read_link(&link).unwrap(),
+            PathBuf::from("image-1.0.wic")
+        );
+        let image: std::fs::Metadata =
+            std::fs::metadata(artifacts_dir.join("image-1.0.wic")).unwrap();
+        assert_eq!(image.permissions().mode() & 0o777, 0o750);
+        assert_eq!(image.modified().unwrap(), modified);
+    }
+
+    fn helper_test_file_collector_selection(
+        work_dir: &PathBuf,
+        json_artifacts_config: &str,
+    ) -> Result<Vec<PathBuf>, BError> {
+        let task_build_dir: PathBuf = work_dir.clone().join("task/dir");
+        let files: Vec<PathBuf> = vec![
+            task_build_dir.join("images/zImage"),
+            task_build_dir.join("images/dtbs/board-a.dtb"),
+            task_build_dir.join("images/dtbs/board-a-test.dtb"),
+            task_build_dir.join("images/dtbs/overlays/board-b.dtb"),
+            task_build_dir.join("images/image-qemux86-64-20240101120000.wic"),
+        ];
+        let build_data: WsBuildData = Helper::setup_build_data(work_dir, None, None);
+        let artifacts: WsArtifactsHandler = Helper::setup_collector_test_ws(
+            work_dir,
+            &task_build_dir,
+            &files,
+            &build_data,
+            json_artifacts_config,
+        );
+        let collector: FileCollector = FileCollector::new(&artifacts, None);
+        let artifacts_dir: PathBuf = build_data.settings().artifacts_dir();
+        let collected: Vec<Collected> = collector.collect(&task_build_dir, &artifacts_dir)?;
+        let mut dest: Vec<PathBuf> = collected
+            .iter()
+            .map(|c| c.dest.strip_prefix(&artifacts_dir).unwrap().to_path_buf())
+            .collect();
+        dest.sort();
+        Ok(dest)
+    }
+
+    #[test]
+    fn test_file_collector_recursive_glob_exclude() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let collected: Vec<PathBuf> = helper_test_file_collector_selection(
+            &work_dir,
+            r#"
+            {
+                "source": "images/**/*.dtb",
+                "dest": "dtbs/",
+                "exclude": [ "*-test.dtb" ]
+            }"#,
+        )
+        .expect("Failed to collect artifacts");
+        assert_eq!(
+            collected,
+            vec![
+                PathBuf::from("dtbs/dtbs/board-a.dtb"),
+                PathBuf::from("dtbs/dtbs/overlays/board-b.dtb"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_file_collector_recursive_glob_dirs() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let collected: Vec<PathBuf> = helper_test_file_collector_selection(
+            &work_dir,
+            r#"
+            {
+                "source": "images/dtbs/**",
+                "dest": "dtbs/",
+                "max": "3"
+            }"#,
+        )
+        .expect("Failed to collect artifacts");
+        assert_eq!(
+            collected,
+            vec![
+                PathBuf::from("dtbs/board-a-test.dtb"),
+                PathBuf::from("dtbs/board-a.dtb"),
+                PathBuf::from("dtbs/overlays/board-b.dtb"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_file_collector_optional() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let collected: Vec<PathBuf> = helper_test_file_collector_selection(
+            &work_dir,
+            r#"
+            {
+                "source": "images/*.ext4",
+                "optional": "true"
+            }"#,
+        )
+        .expect("Failed to collect artifacts");
+        assert!(collected.is_empty());
+        let collected: Vec<PathBuf> = helper_test_file_collector_selection(
+            &work_dir,
+            r#"
+            {
+                "source": "images/*.ext4",
+                "optional": true
+            }"#,
+        )
+        .expect("Failed to collect artifacts");
+        assert!(collected.is_empty());
+        let result: Result<Vec<PathBuf>, BError> = helper_test_file_collector_selection(
+            &work_dir,
+            r#"
+            {
+                "source": "images/*.ext4"
+            }"#,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_file_collector_min_max() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let result: Result<Vec<PathBuf>, BError> = helper_test_file_collector_selection(
+            &work_dir,
+            r#"
+            {
+                "source": "images/**/*.dtb",
+                "max": "2"
+            }"#,
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!(
+                "Expected at most 2 file(s) matching '{}' but found 3",
+                work_dir.join("task/dir/images/**/*.dtb").display()
+            )
+        );
+        let result: Result<Vec<PathBuf>, BError> = helper_test_file_collector_selection(
+            &work_dir,
+            r#"
+            {
+                "source": "images/*.dtb",
+                "optional": "true",
+                "min": "1"
+            }"#,
+        );
+        assert!(result.unwrap().is_empty());
+        let result: Result<Vec<PathBuf>, BError> = helper_test_file_collector_selection(
+            &work_dir,
+            r#"
+            {
+                "source": "images/dtbs/*.dtb",
+                "min": "3"
+            }"#,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_file_collector_rename() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let collected: Vec<PathBuf> = helper_test_file_collector_selection(
+            &work_dir,
+            r#"
+            {
+                "source": "images/*.wic",
+                "rename": [
+                    {
+                        "pattern": "-[0-9]{14}",
+                        "replace": ""
+                    },
+                    {
+                        "pattern": "^image-(.*)\\.wic$",
+                        "replace": "$1-release.wic"
+                    }
+                ]
+            }"#,
+        )
+        .expect("Failed to collect artifacts");
+        assert_eq!(collected, vec![PathBuf::from("qemux86-64-release.wic")]);
+    }
 }
@@ -0,0 +1,429 @@
+use crate::cli::Cli;
+use crate::collector::{Collected, Collector};
+use crate::error::BError;
+use crate::fs::Archiver;
+use crate::workspace::WsArtifactsHandler;
+
+use indexmap::IndexMap;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tempdir::TempDir;
+
+/*
+ * A package listed in the license manifest of an image. The license
+ * manifest is written by bitbake to the licenses dir in the deploy dir
+ * with one block per package separated by an empty line.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LicensePackage {
+    pub name: String,
+    pub version: String,
+    pub recipe: String,
+    pub license: String,
+}
+
+/*
+ * Gather everything needed for license compliance of the images in one
+ * archive. The bundle is laid out as
+ *
+ *   manifests/<image>/license.manifest
+ *   licenses/<recipe>/<license texts>
+ *   sources/<recipe>-<version>-<revision>/<archived sources>
+ *   index.json
+ *
+ * The sources are only available when building with the archiver class,
+ * see 'bakery build --archiver'.
+ */
+pub struct LicenseBundleCollector<'a> {
+    artifact: &'a WsArtifactsHandler,
+    cli: Option<&'a Cli>,
+}
+
+impl<'a> Collector for LicenseBundleCollector<'a> {
+    fn collect(&self, src: &PathBuf, dest: &PathBuf) -> Result<Vec<Collected>, BError> {
+        let bundle_name: &str = self.artifact.data().name();
+        let bundle_path: PathBuf = dest.join(PathBuf::from(bundle_name));
+        let deploy_dir: PathBuf = src.join(PathBuf::from(self.artifact.data().source()));
+        /*
+         * The bundle is staged in the artifacts dir, the same as for the
+         * archive collector, to not fill up /tmp with the sources
+         */
+        std::fs::create_dir_all(dest)?;
+        let temp_dir: TempDir = TempDir::new_in(dest, ".bakery-license-bundle")?;
+        let bundle_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let mut files: Vec<PathBuf> = vec![];
+        let mut index: Vec<Value> = vec![];
+        let mut license_files: IndexMap<String, Vec<String>> = IndexMap::new();
+        let mut source_files: IndexMap<String, Vec<String>> = IndexMap::new();
+
+        self.info(
+            self.cli,
+            format!("Collecting license bundle '{}'", bundle_name),
+        );
+
+        let manifests: Vec<PathBuf> =
+            self.glob(&deploy_dir.join("licenses/**/license.manifest"))?;
+        if manifests.is_empty() {
+            return Err(BError::CollectorError(format!(
+                "No license manifests found in '{}'",
+                deploy_dir.join("licenses").display()
+            )));
+        }
+
+        for manifest in manifests.iter() {
+            let image: String = manifest
+                .parent()
+                .and_then(|p| p.file_name())
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            let manifest_dest: PathBuf = bundle_dir.join("manifests").join(&image);
+            files.push(self.copy(manifest, &manifest_dest)?);
+
+            for package in Self::parse_manifest(&std::fs::read_to_string(manifest)?) {
+                /*
+                 * Several packages are normally built from the same recipe so
+                 * the license texts and sources are only collected once per
+                 * recipe and then reused for the rest of the packages
+                 */
+                if !license_files.contains_key(&package.recipe) {
+                    let collected: Vec<String> = self.collect_licenses(
+                        &deploy_dir,
+                        &package.recipe,
+                        &bundle_dir,
+                        &mut files,
+                    )?;
+                    license_files.insert(package.recipe.clone(), collected);
+                }
+                let source_key: String = format!("{}-{}", package.recipe, package.version);
+                if !source_files.contains_key(&source_key) {
+                    let collected: Vec<String> =
+                        self.collect_sources(&deploy_dir, &package, &bundle_dir, &mut files)?;
+                    source_files.insert(source_key.clone(), collected);
+                }
+
+                index.push(json!({
+                    "image": image,
+                    "package": package.name,
+                    "version": package.version,
+                    "recipe": package.recipe,
+                    "license": package.license,
+                    "license_files": license_files[&package.recipe],
+                    "sources": source_files[&source_key],
+                }));
+            }
+        }
+
+        let index_path: PathBuf = bundle_dir.join("index.json");
+        std::fs::write(
+            &index_path,
+            serde_json::to_string_pretty(&json!({ "packages": index }))?,
+        )?;
+        files.push(index_path);
+
+        let mut archiver: Archiver = Archiver::new(&bundle_path)?;
+        if let Some(level) = self.artifact.data().level()? {
+            archiver.set_level(level)?;
+        }
+        if let Some(source_date_epoch) = self.artifact.data().reproducible()? {
+            archiver.set_reproducible(source_date_epoch);
+        }
+        archiver.add_files(&files, &bundle_dir)?;
+        self.info(
+            self.cli,
+            format!(
+                "License bundle for {} package(s) available at {}",
+                index.len(),
+                bundle_path.display()
+            ),
+        );
+
+        Ok(vec![Collected {
+            src: PathBuf::from(""),
+            dest: bundle_path,
+        }])
+    }
+
+    fn verify_attributes(&self) -> Result<(), BError> {
+        if self.artifact.data().name().is_empty() {
+            return Err(BError::ValueError(String::from(
+                "License bundle node requires name attribute!",
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl<'a> LicenseBundleCollector<'a> {
+    fn glob(&self, pattern: &PathBuf) -> Result<Vec<PathBuf>, BError> {
+        let mut paths: Vec<PathBuf> = vec![];
+        for entry in glob::glob(&pattern.to_string_lossy())? {
+            paths.push(entry?);
+        }
+        Ok(paths)
+    }
+
+    /*
+     * The license texts are stored per recipe and depending on the Yocto
+     * release the recipe dir is either directly under the licenses dir or
+     * under a dir for the package arch
+     */
+    fn collect_licenses(
+        &self,
+        deploy_dir: &PathBuf,
+        recipe: &str,
+        bundle_dir: &PathBuf,
+        files: &mut Vec<PathBuf>,
+    ) -> Result<Vec<String>, BError> {
+        let mut license_files: Vec<String> = vec![];
+        for recipe_dir in self
+            .glob(&deploy_dir.join("licenses").join(recipe))?
+            .into_iter()
+            .chain(self.glob(&deploy_dir.join("licenses/*").join(recipe))?)
+        {
+            for f in self.glob(&recipe_dir.join("*"))? {
+                if f.is_dir() {
+                    continue;
+                }
+                let copied: PathBuf = self.copy(&f, &bundle_dir.join("licenses").join(recipe))?;
+                license_files.push(self.relative(&copied, bundle_dir));
+                if !files.contains(&copied) {
+                    files.push(copied);
+                }
+            }
+        }
+        Ok(license_files)
+    }
+
+    /*
+     * The archiver is storing the sources in a dir named after the recipe,
+     * version and revision under a dir for the package arch
+     */
+    fn collect_sources(
+        &self,
+        deploy_dir: &PathBuf,
+        package: &LicensePackage,
+        bundle_dir: &PathBuf,
+        files: &mut Vec<PathBuf>,
+    ) -> Result<Vec<String>, BError> {
+        let mut source_files: Vec<String> = vec![];
+        let pattern: PathBuf = deploy_dir
+            .join("sources/*")
+            .join(format!("{}-{}-*", package.recipe, package.version));
+        for source_dir in self.glob(&pattern)? {
+            let dir_name: String = source_dir
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            for f in self.glob(&source_dir.join("*"))? {
+                if f.is_dir() {
+                    continue;
+                }
+                let copied: PathBuf = self.copy(&f, &bundle_dir.join("sources").join(&dir_name))?;
+                source_files.push(self.relative(&copied, bundle_dir));
+                if !files.contains(&copied) {
+                    files.push(copied);
+                }
+            }
+        }
+        Ok(source_files)
+    }
+
+    fn copy(&self, file: &PathBuf, dest_dir: &PathBuf) -> Result<PathBuf, BError> {
+        let dest_file: PathBuf = dest_dir.join(file.file_name().unwrap_or_default());
+        std::fs::create_dir_all(dest_dir)?;
+        std::fs::copy(file, &dest_file)?;
+        Ok(dest_file)
+    }
+
+    fn relative(&self, file: &PathBuf, bundle_dir: &PathBuf) -> String {
+        file.strip_prefix(bundle_dir)
+            .unwrap_or(file)
+            .to_string_lossy()
+            .to_string()
+    }
+
+    pub fn parse_manifest(content: &str) -> Vec<LicensePackage> {
+        let mut packages: Vec<LicensePackage> = vec![];
+        let mut package: LicensePackage = LicensePackage::default();
+
+        for line in content.lines().chain(std::iter::once("")) {
+            match line.split_once(':') {
+                Some((key, value)) => {
+                    let value: String = value.trim().to_string();
+                    match key.trim() {
+                        "PACKAGE NAME" => package.name = value,
+                        "PACKAGE VERSION" => package.version = value,
+                        "RECIPE NAME" => package.recipe = value,
+                        "LICENSE" => package.license = value,
+                        _ => {}
+                    }
+                }
+                None => {
+                    if !package.name.is_empty() {
+                        packages.push(package.clone());
+                    }
+                    package = LicensePackage::default();
+                }
+            }
+        }
+
+        packages
+    }
+
+    pub fn new(artifact: &'a WsArtifactsHandler, cli: Option<&'a Cli>) -> Self {
+        LicenseBundleCollector { artifact, cli }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::license::LicensePackage;
+    use crate::collector::{Collected, Collector, LicenseBundleCollector};
+    use crate::data::WsBuildData;
+    use crate::fs::Archiver;
+    use crate::helper::Helper;
+    use crate::workspace::WsArtifactsHandler;
+
+    use serde_json::Value;
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    static LICENSE_MANIFEST: &str = r#"PACKAGE NAME: busybox
+PACKAGE VERSION: 1.36.1
+RECIPE NAME: busybox
+LICENSE: GPL-2.0-only & bzip2-1.0.4
+
+PACKAGE NAME: libz1
+PACKAGE VERSION: 1.3
+RECIPE NAME: zlib
+LICENSE: Zlib
+"#;
+
+    #[test]
+    fn test_license_bundle_parse_manifest() {
+        let packages: Vec<LicensePackage> =
+            LicenseBundleCollector::parse_manifest(LICENSE_MANIFEST);
+        assert_eq!(
+            packages,
+            vec![
+                LicensePackage {
+                    name: String::from("busybox"),
+                    version: String::from("1.36.1"),
+                    recipe: String::from("busybox"),
+                    license: String::from("GPL-2.0-only & bzip2-1.0.4"),
+                },
+                LicensePackage {
+                    name: String::from("libz1"),
+                    version: String::from("1.3"),
+                    recipe: String::from("zlib"),
+                    license: String::from("Zlib"),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_license_bundle_collector() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let task_build_dir: PathBuf = work_dir.clone().join("task/dir");
+        let deploy_dir: PathBuf = task_build_dir.join("tmp/deploy");
+        let files: Vec<PathBuf> = vec![
+            deploy_dir.join("licenses/qemux86_64/test-image-qemux86-64/image_license.manifest"),
+            deploy_dir.join("licenses/core2-64/busybox/generic_GPL-2.0-only"),
+            deploy_dir.join("licenses/core2-64/zlib/zlib.h"),
+            deploy_dir.join("sources/x86_64-poky-linux/busybox-1.36.1-r0/busybox-1.36.1.tar.bz2"),
+        ];
+        let json_artifacts_config: &str = r#"
+        {
+            "type": "license-bundle",
+            "name": "license-bundle.tar.gz",
+            "source": "tmp/deploy"
+        }"#;
+        let build_data: WsBuildData = Helper::setup_build_data(&work_dir, None, None);
+        let artifacts: WsArtifactsHandler = Helper::setup_collector_test_ws(
+            &work_dir,
+            &task_build_dir,
+            &files,
+            &build_data,
+            json_artifacts_config,
+        );
+        std::fs::write(
+            deploy_dir.join("licenses/qemux86_64/test-image-qemux86-64/license.manifest"),
+            format!(
+                "{}\n{}",
+                LICENSE_MANIFEST,
+                "PACKAGE NAME: busybox-syslog\nPACKAGE VERSION: 1.36.1\nRECIPE NAME: busybox\nLICENSE: GPL-2.0-only\n"
+            ),
+        )
+        .unwrap();
+        let collector: LicenseBundleCollector = LicenseBundleCollector::new(&artifacts, None);
+        assert!(collector.verify_attributes().is_ok());
+        let artifacts_dir: PathBuf = build_data.settings().artifacts_dir();
+        let collected: Vec<Collected> = collector
+            .collect(&task_build_dir, &artifacts_dir)
+            .expect("Failed to collect artifacts");
+        let bundle: PathBuf = artifacts_dir.join("license-bundle.tar.gz");
+        assert_eq!(
+            collected,
+            vec![Collected {
+                src: PathBuf::from(""),
+                dest: bundle.clone()
+            }]
+        );
+        let unpack_dir: PathBuf = work_dir.join("unpack");
+        let archiver: Archiver = Archiver::new(&bundle).expect("Failed to setup archiver!");
+        let mut archived: Vec<PathBuf> = Helper::list_files_in_archive(&archiver, &unpack_dir)
+            .expect("Failed to list files in archive");
+        archived.sort();
+        assert_eq!(
+            archived,
+            vec![
+                PathBuf::from("index.json"),
+                PathBuf::from("licenses/busybox/generic_GPL-2.0-only"),
+                PathBuf::from("licenses/zlib/zlib.h"),
+                PathBuf::from("manifests/test-image-qemux86-64/license.manifest"),
+                PathBuf::from("sources/busybox-1.36.1-r0/busybox-1.36.1.tar.bz2"),
+            ]
+        );
+        let index: Value = serde_json::from_str(
+            &std::fs::read_to_string(unpack_dir.join("unpack/index.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            index["packages"][0]["license"],
+            "GPL-2.0-only & bzip2-1.0.4"
+        );
+        assert_eq!(
+            index["packages"][0]["sources"][0],
+            "sources/busybox-1.36.1-r0/busybox-1.36.1.tar.bz2"
+        );
+        assert_eq!(index["packages"][1]["recipe"], "zlib");
+        assert_eq!(
+            index["packages"][1]["license_files"][0],
+            "licenses/zlib/zlib.h"
+        );
+        assert!(index["packages"][1]["sources"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+        assert_eq!(index["packages"][2]["package"], "busybox-syslog");
+        assert_eq!(
+            index["packages"][2]["license_files"],
+            index["packages"][0]["license_files"]
+        );
+        assert_eq!(
+            index["packages"][2]["sources"],
+            index["packages"][0]["sources"]
+        );
+        /* The staging dir is removed once the bundle is created */
+        assert!(std::fs::read_dir(&artifacts_dir).unwrap().all(|e| !e
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .starts_with(".bakery")));
+    }
+}
@@ -4,7 +4,8 @@ use crate::error::BError;
 use crate::fs::Manifest;
 use crate::workspace::WsArtifactsHandler;
 
-use std::path::PathBuf;
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
 
 pub struct ManifestCollector<'a> {
     artifact: &'a WsArtifactsHandler,
@@ -12,16 +13,31 @@ pub struct ManifestCollector<'a> {
 }
 
 impl<'a> Collector for ManifestCollector<'a> {
-    fn collect(&self, _src: &PathBuf, dest: &PathBuf) -> Result<Vec<Collected>, BError> {
+    fn collect(&self, src: &PathBuf, dest: &PathBuf) -> Result<Vec<Collected>, BError> {
+        self.collect_with(src, dest, &vec![])
+    }
+
+    fn collect_with(
+        &self,
+        _src: &PathBuf,
+        dest: &PathBuf,
+        collected: &Vec<Collected>,
+    ) -> Result<Vec<Collected>, BError> {
         let manifest_file: &str = self.artifact.data().name();
         let manifest_path: PathBuf = dest.join(PathBuf::from(manifest_file));
         let manifest: Manifest = Manifest::new(&manifest_path)?;
+        let content: String;
 
         self.info(
             self.cli,
             format!("Creating manifest file '{}'", manifest_file),
         );
-        manifest.write(self.artifact.data().manifest())?;
+        if self.artifact.data().include_collected() {
+            content = self.content(dest, collected)?;
+        } else {
+            content = self.artifact.data().manifest().to_string();
+        }
+        manifest.write(&content)?;
         self.info(
             self.cli,
             format!(
@@ -48,6 +64,47 @@ impl<'a> Collector for ManifestCollector<'a> {
 }
 
 impl<'a> ManifestCollector<'a> {
+    /*
+     * Add the artifacts collected before the manifest with the path
+     * relative to the dest, the size and the sha256 checksum together with
+     * the context of the build to the manifest content.
+     */
+    fn content(&self, dest: &PathBuf, collected: &Vec<Collected>) -> Result<String, BError> {
+        let mut content: Map<String, Value> =
+            match serde_json::from_str(self.artifact.data().manifest())? {
+                Value::Object(map) => map,
+                _ => {
+                    return Err(BError::ParseManifestError(String::from(
+                        "The manifest content must be a json object!",
+                    )));
+                }
+            };
+
+        let mut artifacts: Vec<Value> = vec![];
+        for c in collected.iter() {
+            if !c.dest.is_file() {
+                continue;
+            }
+            let path: &Path = c.dest.strip_prefix(dest).unwrap_or(&c.dest);
+            artifacts.push(json!({
+                "path": path.to_string_lossy(),
+                "size": std::fs::metadata(&c.dest)?.len(),
+                "sha256": Manifest::checksum(&c.dest)?,
+            }));
+        }
+
+        let context: Map<String, Value> = self
+            .artifact
+            .data()
+            .context()
+            .iter()
+            .map(|(key, value)| (key.to_uppercase(), json!(value)))
+            .collect();
+        content.insert(String::from("artifacts"), Value::Array(artifacts));
+        content.insert(String::from("context"), Value::Object(context));
+        Ok(Value::Object(content).to_string())
+    }
+
     pub fn new(artifact: &'a WsArtifactsHandler, cli: Option<&'a Cli>) -> Self {
         ManifestCollector { artifact, cli }
     }
@@ -55,12 +112,14 @@ impl<'a> ManifestCollector<'a> {
 
 #[cfg(test)]
 mod tests {
-    use crate::collector::{Collected, Collector, ManifestCollector};
+    use crate::collector::{Collected, Collector, DirectoryCollector, ManifestCollector};
     use crate::configs::Context;
     use crate::data::WsBuildData;
+    use crate::fs::Manifest;
     use crate::helper::Helper;
     use crate::workspace::WsArtifactsHandler;
     use indexmap::{indexmap, IndexMap};
+    use serde_json::Value;
     use std::fs::File;
     use std::io::Read;
     use std::path::PathBuf;
@@ -185,4 +244,76 @@ mod tests {
         let json_manifest_content: &str = r#"{"data":{"test4":"value4","test5":"value5","test6":"var3"},"test1":"var1","test2":"value2","test3":"var2"}"#;
         assert_eq!(json_manifest_content, contents);
     }
+
+    #[test]
+    fn test_manifest_collector_include_collected() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let task_build_dir: PathBuf = work_dir.clone().join("task/dir");
+        let files: Vec<PathBuf> = vec![
+            task_build_dir.join("file1.txt"),
+            task_build_dir.join("file2.txt"),
+        ];
+        let json_artifacts_config: &str = r#"
+        {
+            "type": "directory",
+            "name": "release",
+            "artifacts": [
+                {
+                    "source": "file1.txt"
+                },
+                {
+                    "source": "file2.txt",
+                    "dest": "sub/"
+                },
+                {
+                    "type": "manifest",
+                    "name": "manifest.yaml",
+                    "include_collected": true,
+                    "content": {
+                        "machine": "$#[MACHINE]"
+                    }
+                }
+            ]
+        }"#;
+        let build_data: WsBuildData = Helper::setup_build_data(&work_dir, None, None);
+        let mut artifacts: WsArtifactsHandler = Helper::setup_collector_test_ws(
+            &work_dir,
+            &task_build_dir,
+            &files,
+            &build_data,
+            json_artifacts_config,
+        );
+        let variables: IndexMap<String, String> = indexmap! {
+            "MACHINE".to_string() => "qemux86-64".to_string(),
+        };
+        artifacts.expand_ctx(&Context::new(&variables)).unwrap();
+        let collector: DirectoryCollector = DirectoryCollector::new(&artifacts, None);
+        collector
+            .collect(&task_build_dir, &build_data.settings().artifacts_dir())
+            .expect("Failed to collect artifacts");
+        let release_dir: PathBuf = build_data.settings().artifacts_dir().join("release");
+        let manifest: Value = serde_json::to_value(
+            serde_norway::from_str::<serde_norway::Value>(
+                &std::fs::read_to_string(release_dir.join("manifest.yaml")).unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(manifest["machine"], "qemux86-64");
+        assert_eq!(manifest["context"]["MACHINE"], "qemux86-64");
+        assert_eq!(manifest["artifacts"][0]["path"], "file1.txt");
+        assert_eq!(manifest["artifacts"][1]["path"], "sub/file2.txt");
+        assert_eq!(
+            manifest["artifacts"][1]["size"],
+            std::fs::metadata(release_dir.join("sub/file2.txt"))
+                .unwrap()
+                .len()
+        );
+        assert_eq!(
+            manifest["artifacts"][1]["sha256"],
+            Manifest::checksum(&release_dir.join("sub/file2.txt")).unwrap()
+        );
+    }
 }
@@ -1,25 +1,37 @@
 pub mod archive;
 pub mod conditional;
+pub mod cve;
 pub mod directory;
+pub mod diskimage;
+pub mod extract;
 pub mod factory;
 pub mod file;
+pub mod license;
 pub mod link;
 pub mod manifest;
+pub mod sbom;
+pub mod template;
 
 pub use archive::ArchiveCollector;
 pub use conditional::ConditionalCollector;
+pub use cve::CveCollector;
 pub use directory::DirectoryCollector;
+pub use diskimage::DiskImageCollector;
+pub use extract::ExtractCollector;
 pub use factory::CollectorFactory;
 pub use file::FileCollector;
+pub use license::LicenseBundleCollector;
 pub use link::LinkCollector;
 pub use manifest::ManifestCollector;
+pub use sbom::SbomCollector;
+pub use template::TemplateCollector;
 
 use crate::cli::Cli;
 use crate::error::BError;
 
 use std::path::PathBuf;
 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct Collected {
     pub src: PathBuf,
     pub dest: PathBuf,
@@ -30,6 +42,20 @@ pub trait Collector {
         Ok(vec![])
     }
 
+    /*
+     * Collect the artifacts with a list of the artifacts that has already
+     * been collected. Only collectors that needs to know what has been
+     * collected before them like the manifest need to implement it.
+     */
+    fn collect_with(
+        &self,
+        src: &PathBuf,
+        dest: &PathBuf,
+        _collected: &Vec<Collected>,
+    ) -> Result<Vec<Collected>, BError> {
+        self.collect(src, dest)
+    }
+
     fn verify_attributes(&self) -> Result<(), BError> {
         Ok(())
     }
@@ -0,0 +1,123 @@
+use crate::cli::Cli;
+use crate::collector::{Collected, Collector};
+use crate::error::BError;
+use crate::workspace::WsArtifactsHandler;
+
+use std::path::PathBuf;
+
+/*
+ * Collect the SPDX documents created by the create-spdx class. Depending
+ * on the Yocto release the SBOM of an image is either a single json
+ * document or an archive with one document per package together with an
+ * index.
+ */
+pub struct SbomCollector<'a> {
+    artifact: &'a WsArtifactsHandler,
+    cli: Option<&'a Cli>,
+}
+
+impl<'a> Collector for SbomCollector<'a> {
+    fn collect(&self, src: &PathBuf, dest: &PathBuf) -> Result<Vec<Collected>, BError> {
+        let src_path: PathBuf = src.join(PathBuf::from(self.artifact.data().source()));
+        let dest_path: PathBuf = dest.join(PathBuf::from(self.artifact.data().dest()));
+        let mut collected: Vec<Collected> = vec![];
+
+        for f in glob::glob(&src_path.to_string_lossy())? {
+            let f: PathBuf = f?;
+            if f.is_dir() {
+                continue;
+            }
+
+            let dest_file: PathBuf = dest_path.join(f.file_name().unwrap_or_default());
+            self.info(
+                self.cli,
+                format!(
+                    "Copy SPDX document {} => {}",
+                    f.display(),
+                    dest_file.display()
+                ),
+            );
+            std::fs::create_dir_all(&dest_path)?;
+            std::fs::copy(&f, &dest_file)?;
+            collected.push(Collected {
+                src: f.clone(),
+                dest: dest_file,
+            });
+        }
+
+        if collected.is_empty() {
+            return Err(BError::CollectorError(format!(
+                "No SPDX documents found matching '{}'",
+                src_path.display()
+            )));
+        }
+
+        self.info(
+            self.cli,
+            format!("Collected {} SPDX document(s)", collected.len()),
+        );
+
+        Ok(collected)
+    }
+}
+
+impl<'a> SbomCollector<'a> {
+    pub fn new(artifact: &'a WsArtifactsHandler, cli: Option<&'a Cli>) -> Self {
+        SbomCollector { artifact, cli }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::{Collected, Collector, SbomCollector};
+    use crate::data::WsBuildData;
+    use crate::helper::Helper;
+    use crate::workspace::WsArtifactsHandler;
+
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_sbom_collector() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let task_build_dir: PathBuf = work_dir.clone().join("task/dir");
+        let deploy_dir: PathBuf = task_build_dir.join("tmp/deploy/images/qemux86-64");
+        let files: Vec<PathBuf> = vec![
+            deploy_dir.join("test-image.spdx.json"),
+            deploy_dir.join("test-image.rootfs.json"),
+        ];
+        let json_artifacts_config: &str = r#"
+        {
+            "type": "sbom",
+            "source": "tmp/deploy/images/*/*.spdx*",
+            "dest": "sbom"
+        }"#;
+        let build_data: WsBuildData = Helper::setup_build_data(&work_dir, None, None);
+        let artifacts: WsArtifactsHandler = Helper::setup_collector_test_ws(
+            &work_dir,
+            &task_build_dir,
+            &files,
+            &build_data,
+            json_artifacts_config,
+        );
+        let collector: SbomCollector = SbomCollector::new(&artifacts, None);
+        assert!(collector.verify_attributes().is_ok());
+        let collected: Vec<Collected> = collector
+            .collect(&task_build_dir, &build_data.settings().artifacts_dir())
+            .expect("Failed to collect artifacts");
+        let dest: PathBuf = build_data
+            .settings()
+            .artifacts_dir()
+            .join("sbom/test-image.spdx.json");
+        assert_eq!(
+            collected,
+            vec![Collected {
+                src: deploy_dir.join("test-image.spdx.json"),
+                dest: dest.clone(),
+            }]
+        );
+        assert!(dest.exists());
+    }
+}
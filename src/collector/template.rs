@@ -0,0 +1,239 @@
+use crate::cli::Cli;
+use crate::collector::{Collected, Collector};
+use crate::configs::Context;
+use crate::error::BError;
+use crate::workspace::WsArtifactsHandler;
+
+use std::path::PathBuf;
+
+/*
+ * Render a text file like release notes or a build info file. The
+ * template is either read from a source file or specified inline as a
+ * list of lines and any context variable in it is expanded before it is
+ * written to the artifacts dir.
+ */
+pub struct TemplateCollector<'a> {
+    artifact: &'a WsArtifactsHandler,
+    cli: Option<&'a Cli>,
+}
+
+impl<'a> Collector for TemplateCollector<'a> {
+    fn collect(&self, _src: &PathBuf, dest: &PathBuf) -> Result<Vec<Collected>, BError> {
+        let dest_path: PathBuf = dest
+            .join(PathBuf::from(self.artifact.data().dest()))
+            .join(PathBuf::from(self.artifact.data().name()));
+        let src_path: PathBuf;
+        let content: String;
+
+        if self.artifact.data().source().is_empty() {
+            src_path = PathBuf::from("");
+            content = format!("{}\n", self.artifact.data().content().join("\n"));
+        } else {
+            /*
+             * The templates are part of the workspace so a relative source
+             * is relative to the workspace dir and not the task build dir
+             */
+            src_path = self
+                .artifact
+                .work_dir()
+                .join(PathBuf::from(self.artifact.data().source()));
+            if !src_path.is_file() {
+                return Err(BError::CollectorError(format!(
+                    "Template '{}' dose not exists",
+                    src_path.display()
+                )));
+            }
+            let ctx: Context = Context::new(self.artifact.data().context());
+            content = ctx.expand_str(&std::fs::read_to_string(&src_path)?)?;
+        }
+
+        self.info(
+            self.cli,
+            format!(
+                "Render template '{}' => {}",
+                self.artifact.data().name(),
+                dest_path.display()
+            ),
+        );
+        if let Some(parent) = dest_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&dest_path, content)?;
+
+        /*
+         * A template can be a script like flashing instructions so the
+         * permissions of the source file are kept
+         */
+        if src_path.is_file() {
+            std::fs::set_permissions(&dest_path, std::fs::metadata(&src_path)?.permissions())?;
+        }
+
+        Ok(vec![Collected {
+            src: src_path,
+            dest: dest_path,
+        }])
+    }
+
+    fn verify_attributes(&self) -> Result<(), BError> {
+        if self.artifact.data().name().is_empty() {
+            return Err(BError::ValueError(String::from(
+                "Template node requires a name!",
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl<'a> TemplateCollector<'a> {
+    pub fn new(artifact: &'a WsArtifactsHandler, cli: Option<&'a Cli>) -> Self {
+        TemplateCollector { artifact, cli }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::{Collected, Collector, TemplateCollector};
+    use crate::configs::Context;
+    use crate::data::WsBuildData;
+    use crate::helper::Helper;
+    use crate::workspace::WsArtifactsHandler;
+
+    use indexmap::{indexmap, IndexMap};
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    fn helper_test_context(work_dir: &PathBuf) -> Context {
+        let variables: IndexMap<String, String> = indexmap! {
+            "WORK_DIR".to_string() => work_dir.to_string_lossy().to_string(),
+            "BUILD_ID".to_string() => "42".to_string(),
+            "MACHINE".to_string() => "qemux86-64".to_string(),
+            "IMAGE".to_string() => "core-image-minimal".to_string(),
+        };
+        Context::new(&variables)
+    }
+
+    #[test]
+    fn test_template_collector_content() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let task_build_dir: PathBuf = work_dir.clone().join("task/dir");
+        let json_artifacts_config: &str = r#"
+        {
+            "type": "template",
+            "name": "build-info",
+            "dest": "info",
+            "content": [
+                "BUILD_ID=$#[BUILD_ID]",
+                "MACHINE=$#[MACHINE]"
+            ]
+        }"#;
+        let build_data: WsBuildData = Helper::setup_build_data(&work_dir, None, None);
+        let mut artifacts: WsArtifactsHandler = Helper::setup_collector_test_ws(
+            &work_dir,
+            &task_build_dir,
+            &vec![],
+            &build_data,
+            json_artifacts_config,
+        );
+        artifacts
+            .expand_ctx(&helper_test_context(&work_dir))
+            .unwrap();
+        let collector: TemplateCollector = TemplateCollector::new(&artifacts, None);
+        assert!(collector.verify_attributes().is_ok());
+        let collected: Vec<Collected> = collector
+            .collect(&task_build_dir, &build_data.settings().artifacts_dir())
+            .expect("Failed to collect artifacts");
+        let dest: PathBuf = build_data
+            .settings()
+            .artifacts_dir()
+            .join("info/build-info");
+        assert_eq!(
+            collected,
+            vec![Collected {
+                src: PathBuf::from(""),
+                dest: dest.clone(),
+            }]
+        );
+        assert_eq!(
+            std::fs::read_to_string(&dest).unwrap(),
+            "BUILD_ID=42\nMACHINE=qemux86-64\n"
+        );
+    }
+
+    #[test]
+    fn test_template_collector_source() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let task_build_dir: PathBuf = work_dir.clone().join("task/dir");
+        let template: PathBuf = work_dir.join("templates/flash.sh");
+        std::fs::create_dir_all(template.parent().unwrap()).unwrap();
+        std::fs::write(
+            &template,
+            "#!/bin/sh\n# Flash build $#[BUILD_ID]\nbmaptool copy $#[IMAGE]-$#[MACHINE].wic $1\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&template, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let json_artifacts_config: &str = r#"
+        {
+            "type": "template",
+            "name": "flash.sh",
+            "source": "templates/flash.sh"
+        }"#;
+        let build_data: WsBuildData = Helper::setup_build_data(&work_dir, None, None);
+        let mut artifacts: WsArtifactsHandler = Helper::setup_collector_test_ws(
+            &work_dir,
+            &task_build_dir,
+            &vec![],
+            &build_data,
+            json_artifacts_config,
+        );
+        artifacts
+            .expand_ctx(&helper_test_context(&work_dir))
+            .unwrap();
+        let collector: TemplateCollector = TemplateCollector::new(&artifacts, None);
+        let collected: Vec<Collected> = collector
+            .collect(&task_build_dir, &build_data.settings().artifacts_dir())
+            .expect("Failed to collect artifacts");
+        let dest: PathBuf = build_data.settings().artifacts_dir().join("flash.sh");
+        assert_eq!(collected.len(), 1);
+        assert_eq!(collected[0].src, template);
+        assert_eq!(collected[0].dest, dest);
+        assert_eq!(
+            std::fs::read_to_string(&dest).unwrap(),
+            "#!/bin/sh\n# Flash build 42\nbmaptool copy core-image-minimal-qemux86-64.wic $1\n"
+        );
+        assert_eq!(
+            std::fs::metadata(&dest).unwrap().permissions().mode() & 0o777,
+            0o755
+        );
+    }
+
+    #[test]
+    fn test_template_collector_missing_source() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let task_build_dir: PathBuf = work_dir.clone().join("task/dir");
+        let json_artifacts_config: &str = r#"
+        {
+            "type": "template",
+            "name": "release-notes.md",
+            "source": "release-notes.md.in"
+        }"#;
+        let build_data: WsBuildData = Helper::setup_build_data(&work_dir, None, None);
+        let artifacts: WsArtifactsHandler = Helper::setup_collector_test_ws(
+            &work_dir,
+            &task_build_dir,
+            &vec![],
+            &build_data,
+            json_artifacts_config,
+        );
+        let collector: TemplateCollector = TemplateCollector::new(&artifacts, None);
+        assert!(collector
+            .collect(&task_build_dir, &build_data.settings().artifacts_dir())
+            .is_err());
+    }
+}
@@ -0,0 +1,866 @@
+use indexmap::IndexMap;
+use std::path::PathBuf;
+
+use crate::cli::Cli;
+use crate::commands::{BBaseCommand, BCommand};
+use crate::data::{AType, WsArtifactData, WsContextData};
+use crate::error::BError;
+use crate::fs::retention::LATEST;
+use crate::fs::{Change, Checksums, Retention};
+use crate::workspace::{Mode, Workspace, WsArtifactsHandler, WsTaskHandler};
+
+static BCOMMAND: &str = "artifacts";
+static BCOMMAND_ABOUT: &str =
+    "List, verify, diff, collect or prune the artifacts collected by the tasks of a build config.";
+pub struct ArtifactsCommand {
+    cmd: BBaseCommand,
+    // Your struct fields and methods here
+}
+
+impl BCommand for ArtifactsCommand {
+    fn get_config_name(&self, cli: &Cli) -> String {
+        if let Some(sub_matches) = cli.get_args().subcommand_matches(BCOMMAND) {
+            if sub_matches.contains_id("config") {
+                if let Some(value) = sub_matches.get_one::<String>("config") {
+                    return value.clone();
+                }
+            }
+        }
+
+        return String::from("default");
+    }
+
+    fn cmd_str(&self) -> &str {
+        &self.cmd.cmd_str
+    }
+
+    fn subcommand(&self) -> &clap::Command {
+        &self.cmd.sub_cmd
+    }
+
+    fn is_docker_required(&self) -> bool {
+        self.cmd.require_docker
+    }
+
+    fn execute(&self, cli: &Cli, workspace: &mut Workspace) -> Result<(), BError> {
+        let config: String = self.get_arg_str(cli, "config", BCOMMAND)?;
+        let action: String = self.get_arg_str(cli, "action", BCOMMAND)?;
+        let ctx: Vec<String> = self.get_arg_many(cli, "ctx", BCOMMAND)?;
+        /*
+         * The build context is setup the same way as when building so that
+         * a layout like $#[BKRY_NAME]/$#[BKRY_PLATFORM_RELEASE] is resolving
+         * to the artifacts of the same build.
+         */
+        let extra_ctx: IndexMap<String, String> = self.setup_build_context(cli, BCOMMAND)?;
+        let args_context: IndexMap<String, String> = self.setup_context(ctx);
+        let mut context: WsContextData = WsContextData::new(&args_context)?;
+        context.update(&extra_ctx);
+
+        if !workspace.valid_config(config.as_str()) {
+            return Err(BError::CliError(format!(
+                "Unsupported build config '{}'",
+                config
+            )));
+        }
+
+        if workspace.settings().mode() == Mode::SETUP {
+            return Err(BError::CmdInsideWorkspace(self.cmd.cmd_str.to_string()));
+        }
+
+        workspace.update_ctx(&context)?;
+
+        match action.as_str() {
+            "list" => self.list(cli, workspace),
+            "verify" => self.verify(cli, workspace),
+            "diff" => self.diff(cli, workspace),
+            "collect" => self.collect(cli, workspace),
+            "prune" => self.prune(cli, workspace),
+            _ => Err(BError::CliError(format!("Unsupported action '{}'", action))),
+        }
+    }
+}
+
+impl ArtifactsCommand {
+    fn tasks<'a>(
+        &self,
+        cli: &Cli,
+        workspace: &'a Workspace,
+    ) -> Result<Vec<&'a WsTaskHandler>, BError> {
+        let tasks: Vec<String> = self.get_arg_many(cli, "tasks", BCOMMAND)?;
+        if tasks.len() == 1 && tasks[0] == "all" {
+            return Ok(workspace.config().tasks().values().collect());
+        }
+        tasks
+            .iter()
+            .map(|t| workspace.config().task(t))
+            .collect::<Result<Vec<&WsTaskHandler>, BError>>()
+    }
+
+    /*
+     * Print an artifact and its children with the paths resolved the same
+     * way as the collectors are resolving them. For artifacts with a source
+     * the number of files currently matching the source is included.
+     */
+    fn list_artifact(
+        &self,
+        cli: &Cli,
+        artifact: &WsArtifactsHandler,
+        src: &PathBuf,
+        dest: &PathBuf,
+        depth: usize,
+    ) -> Result<(), BError> {
+        let data: &WsArtifactData = artifact.data();
+        let indent: String = "  ".repeat(depth);
+        let mut dest_path: PathBuf = dest.clone();
+        if !data.dest().is_empty() {
+            dest_path = dest.join(data.dest());
+        }
+        match data.atype() {
+            AType::Directory
+            | AType::Archive
+            | AType::Manifest
+            | AType::Link
+            | AType::LicenseBundle => {
+                dest_path = dest.join(data.name());
+            }
+            AType::Template => {
+                dest_path = dest_path.join(data.name());
+            }
+            AType::Conditional => {
+                dest_path = dest.clone();
+            }
+            _ => {}
+        }
+
+        let mut source: String = String::from("-");
+        let mut status: String = String::new();
+        if !data.source().is_empty() {
+            let src_path: PathBuf = src.join(data.source());
+            let matches: usize = glob::glob(&src_path.to_string_lossy())?
+                .filter_map(|p| p.ok())
+                .count();
+            source = src_path.display().to_string();
+            status = match matches {
+                0 => String::from(" [missing]"),
+                n => format!(" [found {}]", n),
+            };
+        }
+        if *data.atype() == AType::Conditional {
+            status = match data.condition() {
+                true => String::from(" [condition met]"),
+                false => String::from(" [condition not met]"),
+            };
+        }
+
+        cli.stdout(format!(
+            "{}{:<15} {} -> {}{}",
+            indent,
+            data.atype().to_string(),
+            source,
+            dest_path.display(),
+            status
+        ));
+
+        for child in artifact.children().iter() {
+            self.list_artifact(cli, child, src, &dest_path, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    fn list(&self, cli: &Cli, workspace: &Workspace) -> Result<(), BError> {
+        let artifacts_dir: PathBuf = workspace.settings().artifacts_dir();
+        for task in self.tasks(cli, workspace)? {
+            cli.stdout(format!("{}:", task.data().name()));
+            if task.artifacts().is_empty() {
+                cli.stdout(String::from("  No artifacts"));
+            }
+            for artifact in task.artifacts().iter() {
+                self.list_artifact(cli, artifact, task.data().build_dir(), &artifacts_dir, 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn verify(&self, cli: &Cli, workspace: &Workspace) -> Result<(), BError> {
+        let collected_dir: PathBuf = workspace.config().build_data().collected_dir();
+        let mut verified: usize = 0;
+        let mut failed: usize = 0;
+        for task in self.tasks(cli, workspace)? {
+            let record: PathBuf = collected_dir.join(format!("{}.sha256", task.data().name()));
+            if !record.exists() {
+                continue;
+            }
+            let checksums: Checksums = Checksums::read(&PathBuf::new(), &record)?;
+            let failures: Vec<(PathBuf, String)> = checksums.verify()?;
+            for (path, reason) in failures.iter() {
+                cli.stdout(format!("{}: {}", path.display(), reason));
+            }
+            verified += checksums.entries().len();
+            failed += failures.len();
+        }
+
+        if verified == 0 {
+            return Err(BError::CliError(format!(
+                "No collected artifacts recorded in {:?}, collect the artifacts first with checksums enabled in the workspace settings",
+                collected_dir
+            )));
+        }
+
+        if failed != 0 {
+            return Err(BError::CliError(format!(
+                "{} of {} artifacts failed verification",
+                failed, verified
+            )));
+        }
+
+        cli.info(format!("All {} artifacts verified", verified));
+        Ok(())
+    }
+
+    /*
+     * The artifact sets to compare can be a path or the name of a directory
+     * in the artifacts layout, for example a release. If only one set is
+     * specified it is compared with the 'latest' artifacts.
+     */
+    fn artifacts_set(&self, workspace: &Workspace, name: &str) -> PathBuf {
+        let path: PathBuf = PathBuf::from(name);
+        if path.is_absolute() {
+            return path;
+        }
+        if let Some(versions_dir) = workspace.settings().artifacts_versions_dir() {
+            if versions_dir.join(&path).exists() {
+                return versions_dir.join(&path);
+            }
+        }
+        workspace.settings().artifacts_root_dir().join(&path)
+    }
+
+    fn diff(&self, cli: &Cli, workspace: &Workspace) -> Result<(), BError> {
+        let mut sets: Vec<String> = self.get_arg_many(cli, "sets", BCOMMAND)?;
+        if sets.len() == 1 && workspace.settings().artifacts_versions_dir().is_some() {
+            sets.push(String::from(LATEST));
+        }
+        if sets.len() != 2 {
+            return Err(BError::CliError(String::from(
+                "The diff action requires two sets of artifacts to compare",
+            )));
+        }
+
+        let old: Checksums = Checksums::scan(&self.artifacts_set(workspace, &sets[0]))?;
+        let new: Checksums = Checksums::scan(&self.artifacts_set(workspace, &sets[1]))?;
+        let changes: Vec<(PathBuf, Change)> = old.diff(&new);
+        let (mut added, mut removed, mut changed): (usize, usize, usize) = (0, 0, 0);
+        for (path, change) in changes.iter() {
+            match change {
+                Change::Added(size) => {
+                    added += 1;
+                    cli.stdout(format!("added    {} +{}", path.display(), size));
+                }
+                Change::Removed(size) => {
+                    removed += 1;
+                    cli.stdout(format!("removed  {} -{}", path.display(), size));
+                }
+                Change::Changed(old_size, new_size) => {
+                    changed += 1;
+                    cli.stdout(format!(
+                        "changed  {} {} -> {} ({:+})",
+                        path.display(),
+                        old_size,
+                        new_size,
+                        *new_size as i64 - *old_size as i64
+                    ));
+                }
+            }
+        }
+        cli.info(format!(
+            "{} added, {} removed, {} changed",
+            added, removed, changed
+        ));
+        Ok(())
+    }
+
+    /*
+     * Re-run the collection for the tasks without building them, for
+     * example after fixing an artifact definition in the build config.
+     */
+    fn collect(&self, cli: &Cli, workspace: &Workspace) -> Result<(), BError> {
+        let force: bool = self.get_arg_many(cli, "tasks", BCOMMAND)? != vec!["all"];
+        for task in self.tasks(cli, workspace)? {
+            if !force && (task.data().disabled() || !task.data().condition()) {
+                cli.info(format!("Task '{}' disabled, skipping", task.data().name()));
+                continue;
+            }
+            task.collect(cli, workspace.config().build_data())?;
+        }
+        workspace.update_artifacts(cli)
+    }
+
+    /*
+     * Apply the retention policy to the artifacts of the build config. The
+     * policy from the workspace settings can be overridden from the command
+     * line, for example to clean up the artifacts before a release.
+     */
+    fn prune(&self, cli: &Cli, workspace: &Workspace) -> Result<(), BError> {
+        let keep: String = self.get_arg_str(cli, "keep", BCOMMAND)?;
+        let max_age: String = self.get_arg_str(cli, "max_age", BCOMMAND)?;
+        let dry_run: bool = self.get_arg_flag(cli, "dry_run", BCOMMAND)?;
+        let versions_dir: PathBuf;
+
+        match workspace.settings().artifacts_versions_dir() {
+            Some(dir) => {
+                versions_dir = dir;
+            }
+            None => {
+                return Err(BError::CliError(String::from(
+                    "No versioned artifacts layout defined in the workspace settings, nothing to prune",
+                )));
+            }
+        }
+
+        let mut retention: Retention = workspace.settings().artifacts_retention();
+        if !keep.is_empty() || !max_age.is_empty() {
+            retention = Retention::new(
+                Self::parse_number(&keep, "keep")? as usize,
+                Self::parse_number(&max_age, "max-age")?,
+            );
+        }
+
+        if !retention.enabled() {
+            cli.info(String::from(
+                "No retention policy defined, use --keep or --max-age",
+            ));
+            return Ok(());
+        }
+
+        let expired: Vec<PathBuf>;
+        if dry_run {
+            expired = retention.expired(&versions_dir, &vec![])?;
+        } else {
+            expired = retention.prune(&versions_dir, &vec![])?;
+        }
+
+        if expired.is_empty() {
+            cli.info(format!("Nothing to prune in {:?}", versions_dir));
+        }
+        for dir in expired.iter() {
+            if dry_run {
+                cli.info(format!("Would remove artifacts {:?}", dir));
+            } else {
+                cli.info(format!("Removed artifacts {:?}", dir));
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_number(value: &str, name: &str) -> Result<u64, BError> {
+        if value.is_empty() {
+            return Ok(0);
+        }
+        value.parse::<u64>().map_err(|_err| {
+            BError::CliError(format!(
+                "Invalid {} '{}', expecting a positive number",
+                name, value
+            ))
+        })
+    }
+
+    pub fn new() -> Self {
+        let subcmd: clap::Command = clap::Command::new(BCOMMAND)
+        .about(BCOMMAND_ABOUT)
+        .arg(
+            clap::Arg::new("action")
+                .value_name("action")
+                .required(true)
+                .value_parser(["list", "verify", "diff", "collect", "prune"])
+                .help("List the artifacts collected by the tasks, verify the collected artifacts against the recorded checksums, diff two sets of artifacts, collect the artifacts of the tasks again without building or remove the artifacts from earlier builds according to the retention policy."),
+          )
+          .arg(
+            clap::Arg::new("sets")
+                .action(clap::ArgAction::Append)
+                .value_name("artifacts")
+                .help("The two sets of artifacts to diff. Either a path or a directory in the artifacts layout like a release. If only one is specified it is compared with the latest artifacts."),
+          )
+          .arg(
+            clap::Arg::new("tasks")
+                .short('t')
+                .long("tasks")
+                .value_name("tasks")
+                .default_value("all")
+                .value_delimiter(',')
+                .help("The task(s) to list, verify or collect the artifacts for."),
+          )
+          .arg(
+            clap::Arg::new("config")
+                .short('c')
+                .long("config")
+                .help("The build config to manage the artifacts for")
+                .value_name("name")
+                .required(true),
+          )
+          .arg(
+            clap::Arg::new("platform_version")
+                .short('r')
+                .long("platform-version")
+                .value_name("x.y.z")
+                .default_value("0.0.0")
+                .help("Platform version number for the build. Will be available as context variable BKRY_PLATFORM_VERSION"),
+          )
+          .arg(
+            clap::Arg::new("build_sha")
+                .short('s')
+                .long("build-sha")
+                .value_name("sha")
+                .default_value("dev")
+                .help("Sha for the current build. Will be available as a context variable BKRY_BUILD_SHA"),
+          )
+          .arg(
+            clap::Arg::new("variant")
+                .short('a')
+                .long("variant")
+                .value_name("variant")
+                .default_value("dev")
+                .value_parser(["dev", "test", "release"])
+                .help("Specify the variant of the build it can be one of release, dev or test. Will be available as a context variable BKRY_BUILD_VARIANT"),
+          )
+          .arg(
+            clap::Arg::new("build_id")
+                .short('n')
+                .long("build-id")
+                .value_name("nbr")
+                .default_value("0")
+                .help("Build id number can be used if x.y.z is not enough for some reason and will be part of BKRY_PLATFORM_RELEASE x.y.z-w"),
+          )
+          .arg(
+            clap::Arg::new("keep")
+                .long("keep")
+                .value_name("count")
+                .default_value("")
+                .help("Keep the given number of the most recent artifacts, overrides the workspace settings."),
+          )
+          .arg(
+            clap::Arg::new("max_age")
+                .long("max-age")
+                .value_name("days")
+                .default_value("")
+                .help("Remove artifacts older than the given number of days, overrides the workspace settings."),
+          )
+          .arg(
+            clap::Arg::new("dry_run")
+                .action(clap::ArgAction::SetTrue)
+                .long("dry-run")
+                .help("Only list the artifacts that would be removed."),
+          )
+          .arg(
+            clap::Arg::new("ctx")
+                .action(clap::ArgAction::Append)
+                .short('x')
+                .long("context")
+                .value_name("KEY=VALUE")
+                .help("Adding variable to the context. Any KEY that already exists in the context will be overwriten."),
+          );
+        // Initialize and return a new ArtifactsCommand instance
+        ArtifactsCommand {
+            // Initialize fields if any
+            cmd: BBaseCommand {
+                cmd_str: String::from(BCOMMAND),
+                sub_cmd: subcmd,
+                interactive: false,
+                require_docker: false,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+    use tempdir::TempDir;
+
+    use crate::cli::*;
+    use crate::commands::{ArtifactsCommand, BCommand};
+    use crate::error::BError;
+    use crate::fs::Retention;
+    use crate::workspace::{Workspace, WsBuildConfigHandler, WsSettingsHandler};
+
+    fn helper_test_artifacts_subcommand(
+        json_ws_settings: &str,
+        json_build_config: &str,
+        work_dir: &PathBuf,
+        cmd_line: Vec<&str>,
+    ) -> Result<(), BError> {
+        let settings: WsSettingsHandler =
+            WsSettingsHandler::from_str(work_dir, json_ws_settings, None)?;
+        let config: WsBuildConfigHandler =
+            WsBuildConfigHandler::from_str(json_build_config, &settings)?;
+        let mut workspace: Workspace =
+            Workspace::new(Some(work_dir.to_owned()), Some(settings), Some(config))?;
+        let cli: Cli = Cli::new(
+            Box::new(BLogger::new()),
+            Box::new(BSystem::new()),
+            clap::Command::new("bakery"),
+            Some(cmd_line),
+        );
+        let cmd: ArtifactsCommand = ArtifactsCommand::new();
+        cmd.execute(&cli, &mut workspace)
+    }
+
+    #[test]
+    fn test_cmd_artifacts_prune() {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            },
+            "artifacts": {
+                "layout": "$#[BKRY_NAME]/$#[BKRY_PLATFORM_RELEASE]",
+                "keep": "2"
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {}
+        }
+        "#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let versions_dir: PathBuf = work_dir.join("artifacts/default");
+        for (index, release) in ["1.0.0-1", "1.0.0-2", "1.0.0-3", "1.0.0-4"]
+            .iter()
+            .enumerate()
+        {
+            let dir: PathBuf = versions_dir.join(release);
+            std::fs::create_dir_all(&dir).expect("Failed to create dir");
+            File::open(&dir)
+                .expect("Failed to open dir")
+                .set_modified(SystemTime::now() - Duration::from_secs(10 - index as u64))
+                .expect("Failed to set modified time");
+        }
+        Retention::link_latest(&versions_dir.join("1.0.0-1")).expect("Failed to link latest");
+        let result: Result<(), BError> = helper_test_artifacts_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            vec!["bakery", "artifacts", "prune", "--config", "default"],
+        );
+        assert!(result.is_ok());
+        assert!(versions_dir.join("1.0.0-1").exists());
+        assert!(!versions_dir.join("1.0.0-2").exists());
+        assert!(versions_dir.join("1.0.0-3").exists());
+        assert!(versions_dir.join("1.0.0-4").exists());
+        assert!(versions_dir.join("latest").exists());
+    }
+
+    #[test]
+    fn test_cmd_artifacts_prune_release_first_layout() {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            },
+            "artifacts": {
+                "layout": "$#[BKRY_PLATFORM_RELEASE]/$#[BKRY_NAME]",
+                "keep": "1"
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {}
+        }
+        "#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let release_dir: PathBuf = work_dir.join("artifacts/0.0.0");
+        for name in ["default", "other-1", "other-2"] {
+            std::fs::create_dir_all(release_dir.join(name)).expect("Failed to create dir");
+        }
+        let result: Result<(), BError> = helper_test_artifacts_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            vec![
+                "bakery",
+                "artifacts",
+                "prune",
+                "--config",
+                "default",
+                "--keep",
+                "1",
+            ],
+        );
+        match result {
+            Err(BError::CliError(msg)) => {
+                assert_eq!(
+                    msg,
+                    "No versioned artifacts layout defined in the workspace settings, nothing to prune"
+                );
+            }
+            _ => panic!("Expected a CliError"),
+        }
+        assert!(release_dir.join("default").exists());
+        assert!(release_dir.join("other-1").exists());
+        assert!(release_dir.join("other-2").exists());
+    }
+
+    #[test]
+    fn test_cmd_artifacts_collect_verify() {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            },
+            "artifacts": {
+                "checksums": "true"
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {},
+            "tasks": {
+                "image": {
+                    "index": "1",
+                    "name": "image",
+                    "type": "non-bitbake",
+                    "builddir": "task/dir",
+                    "build": "build-cmd",
+                    "artifacts": [
+                        {
+                            "source": "file1.txt"
+                        },
+                        {
+                            "type": "directory",
+                            "name": "dir",
+                            "artifacts": [
+                                {
+                                    "source": "file2.txt"
+                                }
+                            ]
+                        }
+                    ]
+                }
+            }
+        }
+        "#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let task_build_dir: PathBuf = work_dir.join("task/dir");
+        std::fs::create_dir_all(&task_build_dir).expect("Failed to create dir");
+        std::fs::write(task_build_dir.join("file1.txt"), "file1").expect("Failed to write file");
+        std::fs::write(task_build_dir.join("file2.txt"), "file2").expect("Failed to write file");
+        let result: Result<(), BError> = helper_test_artifacts_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            vec!["bakery", "artifacts", "list", "--config", "default"],
+        );
+        assert!(result.is_ok());
+        let result: Result<(), BError> = helper_test_artifacts_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            vec!["bakery", "artifacts", "verify", "--config", "default"],
+        );
+        assert!(result.is_err());
+        let result: Result<(), BError> = helper_test_artifacts_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            vec![
+                "bakery",
+                "artifacts",
+                "collect",
+                "--config",
+                "default",
+                "--tasks",
+                "image",
+            ],
+        );
+        assert!(result.is_ok());
+        assert!(work_dir.join("artifacts/file1.txt").exists());
+        assert!(work_dir.join("artifacts/dir/file2.txt").exists());
+        assert!(work_dir
+            .join("builds/default/collected/image.sha256")
+            .exists());
+        let result: Result<(), BError> = helper_test_artifacts_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            vec!["bakery", "artifacts", "verify", "--config", "default"],
+        );
+        assert!(result.is_ok());
+        std::fs::write(work_dir.join("artifacts/dir/file2.txt"), "modified")
+            .expect("Failed to write file");
+        let result: Result<(), BError> = helper_test_artifacts_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            vec!["bakery", "artifacts", "verify", "--config", "default"],
+        );
+        match result {
+            Ok(()) => {
+                panic!("Expected the verification to fail");
+            }
+            Err(e) => {
+                assert_eq!(e.to_string(), "1 of 2 artifacts failed verification");
+            }
+        }
+    }
+
+    #[test]
+    fn test_cmd_artifacts_collect_platform_version() {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            },
+            "artifacts": {
+                "layout": "$#[BKRY_NAME]/$#[BKRY_PLATFORM_RELEASE]"
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {},
+            "tasks": {
+                "image": {
+                    "index": "1",
+                    "name": "image",
+                    "type": "non-bitbake",
+                    "builddir": "task/dir",
+                    "build": "build-cmd",
+                    "artifacts": [
+                        {
+                            "source": "file1.txt"
+                        }
+                    ]
+                }
+            }
+        }
+        "#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let task_build_dir: PathBuf = work_dir.join("task/dir");
+        std::fs::create_dir_all(&task_build_dir).expect("Failed to create dir");
+        std::fs::write(task_build_dir.join("file1.txt"), "file1").expect("Failed to write file");
+        let result: Result<(), BError> = helper_test_artifacts_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            vec![
+                "bakery",
+                "artifacts",
+                "collect",
+                "--config",
+                "default",
+                "--platform-version",
+                "1.2.3",
+                "--build-id",
+                "4",
+            ],
+        );
+        assert!(result.is_ok());
+        assert!(work_dir
+            .join("artifacts/default/1.2.3-4/file1.txt")
+            .exists());
+        assert!(!work_dir
+            .join("builds/default/collected/image.sha256")
+            .exists());
+    }
+
+    #[test]
+    fn test_cmd_artifacts_diff() {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            },
+            "artifacts": {
+                "layout": "$#[BKRY_NAME]/$#[BKRY_PLATFORM_RELEASE]"
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {}
+        }
+        "#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let versions_dir: PathBuf = work_dir.join("artifacts/default");
+        std::fs::create_dir_all(versions_dir.join("1.0.0-1")).expect("Failed to create dir");
+        std::fs::create_dir_all(versions_dir.join("1.0.0-2")).expect("Failed to create dir");
+        std::fs::write(versions_dir.join("1.0.0-1/image.wic"), "image")
+            .expect("Failed to write file");
+        std::fs::write(versions_dir.join("1.0.0-2/image.wic"), "image v2")
+            .expect("Failed to write file");
+        Retention::link_latest(&versions_dir.join("1.0.0-2")).expect("Failed to link latest");
+        let result: Result<(), BError> = helper_test_artifacts_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            vec![
+                "bakery",
+                "artifacts",
+                "diff",
+                "--config",
+                "default",
+                "1.0.0-1",
+            ],
+        );
+        assert!(result.is_ok());
+        let result: Result<(), BError> = helper_test_artifacts_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            vec![
+                "bakery",
+                "artifacts",
+                "diff",
+                "--config",
+                "default",
+                "1.0.0-1",
+                "1.0.0-3",
+            ],
+        );
+        assert!(result.is_err());
+    }
+}
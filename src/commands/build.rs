@@ -1,15 +1,13 @@
-use indexmap::{indexmap, IndexMap};
+use indexmap::IndexMap;
 use std::collections::HashMap;
+use std::path::PathBuf;
 
-use crate::cli::Cli;
+use crate::cli::{Cli, TraceSpan};
 use crate::commands::{BBaseCommand, BCommand};
-use crate::data::context::{
-    CTX_KEY_BUILD_ID, CTX_KEY_BUILD_SHA, CTX_KEY_BUILD_VARIANT, CTX_KEY_PLATFORM_RELEASE,
-    CTX_KEY_PLATFORM_VERSION, CTX_KEY_RELEASE_BUILD,
-};
-use crate::data::WsContextData;
+use crate::data::{TType, WarningsPolicy, WsContextData};
 use crate::error::BError;
-use crate::executers::Docker;
+use crate::executers::{BBService, BBServices, Docker};
+use crate::fs::BBLog;
 use crate::workspace::{Mode, Workspace, WsTaskHandler};
 
 static BCOMMAND: &str = "build";
@@ -48,20 +46,19 @@ impl BCommand for BuildCommand {
 
     fn execute(&self, cli: &Cli, workspace: &mut Workspace) -> Result<(), BError> {
         let config: String = self.get_arg_str(cli, "config", BCOMMAND)?;
-        let version: String = self.get_arg_str(cli, "platform_version", BCOMMAND)?;
-        let build_id: String = self.get_arg_str(cli, "build_id", BCOMMAND)?;
-        let sha: String = self.get_arg_str(cli, "build_sha", BCOMMAND)?;
         let build_history: bool = self.get_arg_flag(cli, "build_history", BCOMMAND)?;
-        let archiver: bool = self.get_arg_flag(cli, "archiver", BCOMMAND)?;
-        let debug_symbols: bool = self.get_arg_flag(cli, "debug_symbols", BCOMMAND)?;
         let tar_balls: bool = self.get_arg_flag(cli, "tar_balls", BCOMMAND)?;
+        let offline: bool = self.get_arg_flag(cli, "offline", BCOMMAND)?;
+        let hermetic: bool =
+            self.get_arg_flag(cli, "hermetic", BCOMMAND)? || workspace.settings().docker_hermetic();
         let dry_run: bool = self.get_arg_flag(cli, "dry_run", BCOMMAND)?;
+        let fail_on_warnings: bool = self.get_arg_flag(cli, "fail_on_warnings", BCOMMAND)?;
+        let trace: String = self.get_arg_str(cli, "trace", BCOMMAND)?;
         let interactive: bool = self.get_arg_bool(cli, "interactive", BCOMMAND)?;
         let ctx: Vec<String> = self.get_arg_many(cli, "ctx", BCOMMAND)?;
         let env: Vec<String> = self.get_arg_many(cli, "env", BCOMMAND)?;
         let volumes: Vec<String> = self.get_arg_many(cli, "volume", BCOMMAND)?;
         let tasks: Vec<String> = self.get_arg_many(cli, "tasks", BCOMMAND)?;
-        let variant: String = self.get_arg_str(cli, "variant", BCOMMAND)?;
         let verbose: bool = self.get_arg_flag(cli, "verbose", BCOMMAND)?;
         let mut bb_variables: Vec<String> = Vec::new();
 
@@ -82,11 +79,57 @@ impl BCommand for BuildCommand {
          * However, not all commands should run inside Docker, and if we're already inside
          * a container, we must avoid bootstrapping into another one.
          */
+        if hermetic && workspace.settings().docker_disabled() {
+            return Err(BError::CliError(String::from(
+                "A hermetic build requires docker to be enabled in the workspace settings",
+            )));
+        }
+
         if !workspace.settings().docker_disabled()
             && self.is_docker_required()
             && !cli.inside_docker()
         {
-            return self.bootstrap(&cli.get_cmd_line(), cli, workspace, &volumes, interactive);
+            /*
+             * The trace file is written by bakery inside docker and the time
+             * spent bootstrapping into docker is then added to it
+             */
+            let trace_file: PathBuf = PathBuf::from(&trace);
+            if !trace.is_empty() && trace_file.exists() {
+                std::fs::remove_file(&trace_file)?;
+            }
+
+            /*
+             * A hermetic build is done in two phases. First all the sources
+             * are fetched into the download cache by a container with network
+             * access and then the build is done in a container without any
+             * network access.
+             */
+            if hermetic {
+                let fetch_cmd_line: Vec<String> = self.fetch_cmd_line(cli)?;
+                let span: TraceSpan = cli.trace().begin("hermetic fetch", "docker");
+                let result: Result<(), BError> =
+                    self.bootstrap(&fetch_cmd_line, cli, workspace, &volumes, interactive);
+                cli.trace().end(span);
+                if result.is_err() && !trace.is_empty() {
+                    cli.trace().write(&trace_file, false)?;
+                }
+                result?;
+            }
+
+            if trace.is_empty() {
+                return self.bootstrap_build(cli, workspace, &volumes, interactive, hermetic);
+            }
+
+            let span: TraceSpan = cli.trace().begin("docker bootstrap", "docker");
+            let result: Result<(), BError> =
+                self.bootstrap_build(cli, workspace, &volumes, interactive, hermetic);
+            cli.trace().end(span);
+            cli.trace().write(&trace_file, true)?;
+            return result;
+        }
+
+        if !trace.is_empty() {
+            cli.trace().enable();
         }
 
         /*
@@ -99,6 +142,30 @@ impl BCommand for BuildCommand {
             bb_variables.push("BB_GENERATE_MIRROR_TARBALLS = \"1\"".to_string());
         }
 
+        /*
+         * In a hermetic build bitbake is not allowed to access the network
+         * and any task trying to fetch anything will fail the build.
+         */
+        if hermetic {
+            /*
+             * A hermetic build started outside of docker is fetching all the
+             * sources before it is bootstrapped into a container without any
+             * network access. When bakery is started inside a container that
+             * has network access nothing is fetched up front and it is only
+             * bitbake that is prevented from accessing the network.
+             */
+            if cli.inside_docker() && self.network_available() {
+                cli._warn(String::from(
+                    "Hermetic build inside docker with network access, the sources are not fetched up front and only BB_NO_NETWORK is set",
+                ));
+            }
+            cli.set_hermetic(true);
+        }
+
+        if offline || hermetic {
+            bb_variables.push("BB_NO_NETWORK = \"1\"".to_string());
+        }
+
         if build_history {
             bb_variables.push("INHERIT += \"buildhistory\"".to_string());
             bb_variables.push("BUILDHISTORY_COMMIT = \"1\"".to_string());
@@ -107,46 +174,10 @@ impl BCommand for BuildCommand {
         let env_variables: HashMap<String, String> = self.setup_env(env);
         let mut args_context: IndexMap<String, String> = self.setup_context(ctx);
 
-        let mut extra_ctx: IndexMap<String, String> = indexmap! {
-            String::from(CTX_KEY_PLATFORM_VERSION) => version.clone(),
-            String::from(CTX_KEY_BUILD_ID) => build_id.clone(),
-            String::from(CTX_KEY_BUILD_SHA) => sha,
-            String::from(CTX_KEY_RELEASE_BUILD) => "0".to_string(),
-            String::from(CTX_KEY_BUILD_VARIANT) => variant.clone(),
-            String::from(CTX_KEY_PLATFORM_RELEASE) => format!("{}-{}", version, build_id),
-        };
-
-        if archiver {
-            bb_variables.push("INHERIT += \"archiver\"".to_string());
-            bb_variables.push("ARCHIVER_MODE[src] = \"original\"".to_string());
-            args_context.insert("BKRY_ARCHIVER".to_string(), "1".to_string());
-        }
-
-        if debug_symbols {
-            bb_variables.push("IMAGE_GEN_DEBUGFS = \"1\"".to_string());
-            bb_variables.push("IMAGE_FSTYPES_DEBUGFS = \"tar.bz2\"".to_string());
-            args_context.insert("BKRY_DEBUG_SYMBOLS".to_string(), "1".to_string());
-        }
-
-        if variant == "release" {
-            /*
-             * Build commands defined in the build config needs to
-             * know if it is release build or not running by including
-             * the BKRY_BUILD_VARIANT to the context we can expose this to
-             * the build commands. We are keeping BKRY_RELEASE_BUILD for
-             * backwards compatibility but should be replaced with BKRY_BUILD_VARIANT
-             */
-            extra_ctx.insert(String::from(CTX_KEY_BUILD_VARIANT), "release".to_string());
-            extra_ctx.insert(String::from(CTX_KEY_RELEASE_BUILD), "1".to_string());
-        }
+        let extra_ctx: IndexMap<String, String> = self.setup_build_context(cli, BCOMMAND)?;
 
-        // We need to add the extra context variables to the list of bitbake variables
-        // so they can be added to the bitbake local.conf file if used as env variables
-        // they can also be injected as context variables
-        for (key, value) in extra_ctx.clone() {
-            let bb_key: &str = key.strip_prefix("BKRY_").unwrap();
-            bb_variables.push(format!("{} ?= \"{}\"", bb_key, value));
-        }
+        self.setup_build_features(cli, BCOMMAND, &mut bb_variables, &mut args_context)?;
+        bb_variables.append(&mut self.setup_build_bb_variables(&extra_ctx));
 
         // Update the config context with the context from the args
         let mut context: WsContextData = WsContextData::new(&args_context)?;
@@ -158,6 +189,19 @@ impl BCommand for BuildCommand {
             workspace.settings().artifacts_dir()
         ));
 
+        /*
+         * Report the caches used by bitbake. When the workspace is sharing
+         * caches with other builds they are always reported so it is clear
+         * from the build log what mirrors and servers were used.
+         */
+        for cache in workspace.config().build_data().bitbake().caches() {
+            if workspace.settings().cache_shared() {
+                cli.info(format!("Using {}", cache));
+            } else {
+                cli.debug(format!("Using {}", cache));
+            }
+        }
+
         if verbose {
             let variables: IndexMap<String, String> = workspace.context()?;
             cli.debug(format!("Context varibles for build config '{}':", config));
@@ -166,18 +210,153 @@ impl BCommand for BuildCommand {
             });
         }
 
+        /*
+         * Start the bitbake services enabled in the workspace settings that
+         * are not already running. Services started by this build are stopped
+         * again when the build is done even if it fails. Services started
+         * using 'bakery services start' are left running.
+         */
+        let services: BBServices = BBServices::new(cli, workspace.config().build_data());
+        let mut started: Vec<BBService> = vec![];
+        if !dry_run {
+            started = services.start()?;
+        }
+
+        let mut logs: IndexMap<String, PathBuf> = IndexMap::new();
+        let result: Result<(), BError> = self.build_tasks(
+            cli,
+            workspace,
+            &tasks,
+            &bb_variables,
+            &env_variables,
+            dry_run,
+            interactive,
+            fail_on_warnings,
+            &mut logs,
+        );
+
+        /*
+         * Failing to stop the services should not hide the result of the build
+         */
+        if let Err(err) = services.stop(&started) {
+            cli.error(format!("Failed to stop bitbake services: {}", err));
+        }
+        if result.is_ok() && !dry_run {
+            workspace.update_artifacts(cli)?;
+        }
+        self.log_summary(cli, workspace, &logs)?;
+        if !trace.is_empty() {
+            cli.trace().write(&PathBuf::from(&trace), false)?;
+        }
+        result
+    }
+}
+
+impl BuildCommand {
+    fn bootstrap_build(
+        &self,
+        cli: &Cli,
+        workspace: &Workspace,
+        volumes: &Vec<String>,
+        interactive: bool,
+        hermetic: bool,
+    ) -> Result<(), BError> {
+        if hermetic {
+            return self.bootstrap_hermetic(
+                &cli.get_cmd_line(),
+                cli,
+                workspace,
+                volumes,
+                interactive,
+            );
+        }
+        self.bootstrap(&cli.get_cmd_line(), cli, workspace, volumes, interactive)
+    }
+
+    /*
+     * The fetch phase of a hermetic build is using the fetch sub-command
+     * with the same build config, tasks and args as the build so that the
+     * local.conf used when fetching is the same as when building.
+     */
+    fn fetch_cmd_line(&self, cli: &Cli) -> Result<Vec<String>, BError> {
+        let mut cmd_line: Vec<String> = vec![cli.get_cmd_line()[0].clone(), String::from("fetch")];
+        for (arg, id) in [
+            ("--config", "config"),
+            ("--platform-version", "platform_version"),
+            ("--build-id", "build_id"),
+            ("--build-sha", "build_sha"),
+            ("--variant", "variant"),
+            ("--interactive", "interactive"),
+        ] {
+            cmd_line.append(&mut vec![
+                arg.to_string(),
+                self.get_arg_str(cli, id, BCOMMAND)?,
+            ]);
+        }
+        cmd_line.append(&mut vec![
+            String::from("--tasks"),
+            self.get_arg_many(cli, "tasks", BCOMMAND)?.join(","),
+        ]);
+        for (arg, id) in [
+            ("--tar-balls", "tar_balls"),
+            ("--archiver", "archiver"),
+            ("--cve-check", "cve_check"),
+            ("--sbom", "sbom"),
+            ("--debug-symbols", "debug_symbols"),
+        ] {
+            if self.get_arg_flag(cli, id, BCOMMAND)? {
+                cmd_line.push(arg.to_string());
+            }
+        }
+        for c in self.get_arg_many(cli, "ctx", BCOMMAND)? {
+            cmd_line.append(&mut vec![String::from("--context"), c]);
+        }
+        for e in self.get_arg_many(cli, "env", BCOMMAND)? {
+            cmd_line.append(&mut vec![String::from("--env"), e]);
+        }
+        Ok(cmd_line)
+    }
+
+    /*
+     * A container started without any network access only has the
+     * loopback interface.
+     */
+    fn network_available(&self) -> bool {
+        match std::fs::read_dir("/sys/class/net") {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok())
+                .any(|e| e.file_name() != "lo"),
+            Err(_err) => false,
+        }
+    }
+
+    fn build_tasks(
+        &self,
+        cli: &Cli,
+        workspace: &Workspace,
+        tasks: &Vec<String>,
+        bb_variables: &Vec<String>,
+        env_variables: &HashMap<String, String>,
+        dry_run: bool,
+        interactive: bool,
+        fail_on_warnings: bool,
+        logs: &mut IndexMap<String, PathBuf>,
+    ) -> Result<(), BError> {
         if tasks.len() > 1 {
             // More then one task was specified on the command line
             for t_name in tasks {
                 let task: &WsTaskHandler = workspace.config().task(&t_name)?;
-                task.build(
+                self.build_task(
                     cli,
-                    &workspace.config().build_data(),
-                    &bb_variables,
-                    &env_variables,
+                    workspace,
+                    task,
+                    bb_variables,
+                    env_variables,
                     dry_run,
                     interactive,
                     true,
+                    fail_on_warnings,
+                    logs,
                 )?;
             }
         } else {
@@ -186,35 +365,235 @@ impl BCommand for BuildCommand {
             if task == "all" {
                 // The alias "all" was specified on the command line or it none was specified and "all" was used
                 for (_t_name, task) in workspace.config().tasks() {
-                    task.build(
+                    self.build_task(
                         cli,
-                        &workspace.config().build_data(),
-                        &bb_variables,
-                        &env_variables,
+                        workspace,
+                        task,
+                        bb_variables,
+                        env_variables,
                         dry_run,
                         interactive,
                         false,
+                        fail_on_warnings,
+                        logs,
                     )?;
                 }
             } else {
                 // One task was specified on the command line
                 let task: &WsTaskHandler = workspace.config().task(tasks.get(0).unwrap())?;
-                task.build(
+                self.build_task(
                     cli,
-                    &workspace.config().build_data(),
-                    &bb_variables,
-                    &env_variables,
+                    workspace,
+                    task,
+                    bb_variables,
+                    env_variables,
                     dry_run,
                     interactive,
                     true,
+                    fail_on_warnings,
+                    logs,
                 )?;
             }
         }
         Ok(())
     }
-}
 
-impl BuildCommand {
+    /*
+     * Add a span to the trace for the task and for a bitbake task also
+     * the recipe tasks executed by bitbake from the buildstats.
+     */
+    fn build_task(
+        &self,
+        cli: &Cli,
+        workspace: &Workspace,
+        task: &WsTaskHandler,
+        bb_variables: &Vec<String>,
+        env_variables: &HashMap<String, String>,
+        dry_run: bool,
+        interactive: bool,
+        force: bool,
+        fail_on_warnings: bool,
+        logs: &mut IndexMap<String, PathBuf>,
+    ) -> Result<(), BError> {
+        let span: TraceSpan = cli.trace().begin(task.data().name(), "task");
+        let result: Result<(), BError> = self.run_task(
+            cli,
+            workspace,
+            task,
+            bb_variables,
+            env_variables,
+            dry_run,
+            interactive,
+            force,
+            fail_on_warnings,
+            logs,
+        );
+        let since: u64 = span.start();
+        cli.trace().end(span);
+
+        if !dry_run && *task.data().ttype() == TType::Bitbake {
+            cli.trace().add_buildstats(
+                &workspace
+                    .config()
+                    .build_data()
+                    .bitbake()
+                    .tmp_dir()
+                    .join("buildstats"),
+                since,
+            )?;
+        }
+        result
+    }
+
+    /*
+     * When log capture is enabled in the workspace settings the output of
+     * each task is written to <builds_dir>/<config>/logs/<task>-<timestamp>.log
+     * in addition to the terminal. The output of a bitbake task is always
+     * captured when there is a warnings policy to enforce for the task.
+     */
+    fn run_task(
+        &self,
+        cli: &Cli,
+        workspace: &Workspace,
+        task: &WsTaskHandler,
+        bb_variables: &Vec<String>,
+        env_variables: &HashMap<String, String>,
+        dry_run: bool,
+        interactive: bool,
+        force: bool,
+        fail_on_warnings: bool,
+        logs: &mut IndexMap<String, PathBuf>,
+    ) -> Result<(), BError> {
+        let policy: WarningsPolicy = self.warnings_policy(task, fail_on_warnings);
+        if dry_run || (!workspace.settings().logs_capture() && policy == WarningsPolicy::Allow) {
+            return task.build(
+                cli,
+                &workspace.config().build_data(),
+                bb_variables,
+                env_variables,
+                dry_run,
+                interactive,
+                force,
+            );
+        }
+
+        let logs_dir: PathBuf = workspace.config().build_data().logs_dir();
+        std::fs::create_dir_all(&logs_dir)?;
+        let log: PathBuf = logs_dir.join(format!(
+            "{}-{}.log",
+            task.data().name(),
+            chrono::offset::Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        logs.insert(task.data().name().to_string(), log.clone());
+
+        cli.set_log_file(Some(log.clone()));
+        let result: Result<(), BError> = task.build(
+            cli,
+            &workspace.config().build_data(),
+            bb_variables,
+            env_variables,
+            dry_run,
+            interactive,
+            force,
+        );
+        cli.set_log_file(None);
+        result?;
+
+        self.check_warnings(task, &log, &policy)
+    }
+
+    fn warnings_policy(&self, task: &WsTaskHandler, fail_on_warnings: bool) -> WarningsPolicy {
+        if *task.data().ttype() != TType::Bitbake {
+            return WarningsPolicy::Allow;
+        }
+
+        if fail_on_warnings {
+            return WarningsPolicy::Fail;
+        }
+
+        task.data().warnings()
+    }
+
+    /*
+     * The warnings reported by bitbake are recorded grouped by recipe in
+     * <log>.warnings.json next to the log and the task is failing if the
+     * number of warnings are not allowed by the warnings policy.
+     */
+    fn check_warnings(
+        &self,
+        task: &WsTaskHandler,
+        log: &PathBuf,
+        policy: &WarningsPolicy,
+    ) -> Result<(), BError> {
+        if !log.exists() || *task.data().ttype() != TType::Bitbake {
+            return Ok(());
+        }
+
+        let bblog: BBLog = BBLog::from_file(log)?;
+        let count: u32 = bblog.warnings_count();
+        if count > 0 {
+            let mut warnings: serde_json::Map<String, serde_json::Value> = serde_json::Map::new();
+            for (recipe, lines) in bblog.warnings_by_recipe() {
+                warnings.insert(recipe, serde_json::Value::from(lines));
+            }
+            std::fs::write(
+                log.with_extension("warnings.json"),
+                serde_json::to_string_pretty(&warnings)?,
+            )?;
+        }
+
+        let allowed: u32 = match policy {
+            WarningsPolicy::Allow => return Ok(()),
+            WarningsPolicy::Fail => 0,
+            WarningsPolicy::Max(max) => *max,
+        };
+
+        if count > allowed {
+            return Err(BError::CmdError(format!(
+                "Task '{}' failed with {} bitbake warning(s) where {} is allowed",
+                task.data().name(),
+                count,
+                allowed
+            )));
+        }
+
+        Ok(())
+    }
+
+    /*
+     * Print a compact summary of the captured logs at the end of the build.
+     * For bitbake tasks the output is parsed to pick out the failed tasks
+     * and the logs for the failed tasks so there is no need to search for
+     * them in the full output.
+     */
+    fn log_summary(
+        &self,
+        cli: &Cli,
+        workspace: &Workspace,
+        logs: &IndexMap<String, PathBuf>,
+    ) -> Result<(), BError> {
+        for (name, log) in logs {
+            // No log is created if the task was skipped
+            if !log.exists() {
+                continue;
+            }
+
+            cli.info(format!(
+                "Log for task '{}' stored in {}",
+                name,
+                log.display()
+            ));
+            let task: &WsTaskHandler = workspace.config().task(name)?;
+            if *task.data().ttype() == TType::Bitbake {
+                let bblog: BBLog = BBLog::from_file(log)?;
+                for line in bblog.summary() {
+                    cli.info(format!("  {}", line));
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn setup_env(&self, env: Vec<String>) -> HashMap<String, String> {
         let variables: HashMap<String, String> = env
             .iter()
@@ -281,6 +660,43 @@ impl BuildCommand {
                     .long("archiver")
                     .help("Setting context variable BKRY_ARCHIVER to 1 which will result in adding the archiver class to the local.conf. For more information see https://www.yoctoproject.org/docs/latest/mega-manual/mega-manual.html#ref-classes-archiver."),
             )
+            .arg(
+                clap::Arg::new("cve_check")
+                    .action(clap::ArgAction::SetTrue)
+                    .long("cve-check")
+                    .help("Setting context variable BKRY_CVE_CHECK to 1 which will result in adding the cve-check class to the local.conf. The CVE reports can be collected using the 'cve' artifact type."),
+            )
+            .arg(
+                clap::Arg::new("sbom")
+                    .action(clap::ArgAction::SetTrue)
+                    .long("sbom")
+                    .help("Setting context variable BKRY_SBOM to 1 which will result in adding the create-spdx class to the local.conf. The SPDX documents can be collected using the 'sbom' artifact type."),
+            )
+            .arg(
+                clap::Arg::new("offline")
+                    .action(clap::ArgAction::SetTrue)
+                    .long("offline")
+                    .help("Adding BB_NO_NETWORK = \"1\" to the local.conf which will make the build fail if bitbake tries to access the network. All the sources needs to be available in the download cache, see 'bakery fetch'."),
+            )
+            .arg(
+                clap::Arg::new("hermetic")
+                    .action(clap::ArgAction::SetTrue)
+                    .long("hermetic")
+                    .help("Fetch all the sources first and then run the build in a docker container without network access. Any task trying to access the network will fail the build. Requires docker to be enabled."),
+            )
+            .arg(
+                clap::Arg::new("trace")
+                    .long("trace")
+                    .value_name("file")
+                    .default_value("")
+                    .help("Write a trace of the build in the Chrome trace event format to file. The trace contains a span for each task and the bitbake tasks from the buildstats and can be loaded into https://ui.perfetto.dev."),
+            )
+            .arg(
+                clap::Arg::new("fail_on_warnings")
+                    .action(clap::ArgAction::SetTrue)
+                    .long("fail-on-warnings")
+                    .help("Fail the build if any bitbake task is reporting a warning. This will override the warnings policy defined for the task in the build config."),
+            )
             .arg(
                 clap::Arg::new("debug_symbols")
                     .action(clap::ArgAction::SetTrue)
@@ -595,6 +1011,10 @@ mod tests {
         default_bb_variables.push_str("BUILD_VARIANT ?= \"dev\"\n");
         default_bb_variables.push_str("PLATFORM_RELEASE ?= \"0.0.0-0\"\n");
         local_conf_content.push_str(bb_variables.unwrap_or(&default_bb_variables));
+        local_conf_content.push_str(&format!(
+            "include {}/conf/local.conf.user\n",
+            build_dir.to_string_lossy().to_string()
+        ));
         helper_verify_bitbake_conf(
             &local_conf_path,
             &local_conf_content,
@@ -641,29 +1061,32 @@ mod tests {
         let build_dir: PathBuf = work_dir.join("builds/default");
         let mut mocked_system: MockSystem = MockSystem::new();
         mocked_system
-            .expect_check_call()
-            .with(mockall::predicate::eq(CallParams {
-                cmd_line: vec![
-                    "cd",
-                    &build_dir.to_string_lossy().to_string(),
-                    "&&",
-                    "devtool",
-                    "create-workspace",
-                    "&&",
-                    "bitbake",
-                    "test-image",
-                ]
-                .iter()
-                .map(|s| s.to_string())
-                .collect(),
-                env: HashMap::from([(
-                    String::from("BB_ENV_PASSTHROUGH_ADDITIONS"),
-                    String::from("SSTATE_DIR DL_DIR TMPDIR"),
-                )]),
-                shell: true,
-            }))
+            .expect_check_call_log()
+            .with(
+                mockall::predicate::eq(CallParams {
+                    cmd_line: vec![
+                        "cd",
+                        &build_dir.to_string_lossy().to_string(),
+                        "&&",
+                        "devtool",
+                        "create-workspace",
+                        "&&",
+                        "bitbake",
+                        "test-image",
+                    ]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+                    env: HashMap::from([(
+                        String::from("BB_ENV_PASSTHROUGH_ADDITIONS"),
+                        String::from("SSTATE_DIR DL_DIR TMPDIR"),
+                    )]),
+                    shell: true,
+                }),
+                mockall::predicate::always(),
+            )
             .once()
-            .returning(|_x| Ok(()));
+            .returning(|_x, _y| Ok(()));
         mocked_system
             .expect_init_env_file()
             .returning(|_x, _y| Ok(HashMap::new()));
@@ -716,22 +1139,25 @@ mod tests {
         let build_dir: PathBuf = work_dir.join("test-dir");
         let mut mocked_system: MockSystem = MockSystem::new();
         mocked_system
-            .expect_check_call()
-            .with(mockall::predicate::eq(CallParams {
-                cmd_line: vec![
-                    "cd",
-                    &build_dir.to_string_lossy().to_string(),
-                    "&&",
-                    "test.sh",
-                ]
-                .iter()
-                .map(|s| s.to_string())
-                .collect(),
-                env: HashMap::new(),
-                shell: true,
-            }))
+            .expect_check_call_log()
+            .with(
+                mockall::predicate::eq(CallParams {
+                    cmd_line: vec![
+                        "cd",
+                        &build_dir.to_string_lossy().to_string(),
+                        "&&",
+                        "test.sh",
+                    ]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+                    env: HashMap::new(),
+                    shell: true,
+                }),
+                mockall::predicate::always(),
+            )
             .once()
-            .returning(|_x| Ok(()));
+            .returning(|_x, _y| Ok(()));
         mocked_system
             .expect_init_env_file()
             .returning(|_x, _y| Ok(HashMap::new()));
@@ -746,6 +1172,282 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cmd_build_capture_logs() {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            },
+            "docker": {
+                "disabled": "true"
+            },
+            "logs": {
+                "capture": "true"
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {},
+            "tasks": {
+                "image": {
+                    "index": "1",
+                    "name": "image",
+                    "recipes": [
+                        "test-image"
+                    ]
+                }
+            }
+        }
+        "#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let logs_dir: PathBuf = work_dir.join("builds/default/logs");
+        let mut mocked_system: MockSystem = MockSystem::new();
+        mocked_system
+            .expect_init_env_file()
+            .returning(|_x, _y| Ok(HashMap::new()));
+        mocked_system
+            .expect_check_call_log()
+            .with(
+                mockall::predicate::always(),
+                mockall::predicate::function({
+                    let logs_dir: PathBuf = logs_dir.clone();
+                    move |log: &PathBuf| log.starts_with(&logs_dir)
+                }),
+            )
+            .once()
+            .returning(|_x, log| {
+                let mut output: String = String::new();
+                output.push_str("ERROR: Logfile of failure stored in: /tmp/log.do_compile.1234\n");
+                output.push_str(
+                    "ERROR: Task (/test-image.bb:do_compile) failed with exit code '1'\n",
+                );
+                std::fs::write(log, output).expect("Failed to write log");
+                Err(BError::CliError(String::from("exit status: 1")))
+            });
+        let mut mocked_logger: MockLogger = MockLogger::new();
+        mocked_logger
+            .expect_info()
+            .with(mockall::predicate::eq(String::from(
+                "  Failed task /test-image.bb:do_compile",
+            )))
+            .once()
+            .returning(|_x| ());
+        mocked_logger
+            .expect_info()
+            .with(mockall::predicate::eq(String::from(
+                "  Failure log /tmp/log.do_compile.1234",
+            )))
+            .once()
+            .returning(|_x| ());
+        mocked_logger.expect_info().returning(|_x| ());
+        let result: Result<(), BError> = helper_test_build_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            Box::new(mocked_logger),
+            Box::new(mocked_system),
+            vec!["bakery", "build", "--config", "default"],
+        );
+        assert!(result.is_err());
+        assert_eq!(
+            std::fs::read_dir(&logs_dir)
+                .expect("Failed to read logs dir")
+                .count(),
+            1
+        );
+    }
+
+    fn helper_test_build_warnings(
+        warnings: &str,
+        cmd_line: Vec<&str>,
+    ) -> (Result<(), BError>, PathBuf) {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            },
+            "docker": {
+                "disabled": "true"
+            }
+        }"#;
+        let json_build_config: String = format!(
+            r#"
+        {{
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {{}},
+            "tasks": {{
+                "image": {{
+                    "index": "1",
+                    "name": "image",
+                    "warnings": "{}",
+                    "recipes": [
+                        "test-image"
+                    ]
+                }}
+            }}
+        }}
+        "#,
+            warnings
+        );
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let logs_dir: PathBuf = work_dir.join("builds/default/logs");
+        let mut mocked_system: MockSystem = MockSystem::new();
+        mocked_system
+            .expect_init_env_file()
+            .returning(|_x, _y| Ok(HashMap::new()));
+        mocked_system
+            .expect_check_call_log()
+            .once()
+            .returning(|_x, log| {
+                let mut output: String = String::new();
+                output.push_str("WARNING: test-recipe-1.0-r0 do_package_qa: QA Issue: one\n");
+                output.push_str("WARNING: test-recipe-1.0-r0 do_package_qa: QA Issue: two\n");
+                output.push_str("Summary: There were 2 WARNING messages.\n");
+                std::fs::write(log, output).expect("Failed to write log");
+                Ok(())
+            });
+        let result: Result<(), BError> = helper_test_build_subcommand(
+            json_ws_settings,
+            &json_build_config,
+            &work_dir,
+            Box::new(BLogger::new()),
+            Box::new(mocked_system),
+            cmd_line,
+        );
+        (result, logs_dir)
+    }
+
+    #[test]
+    fn test_cmd_build_fail_on_warnings() {
+        let (result, logs_dir) = helper_test_build_warnings(
+            "allow",
+            vec![
+                "bakery",
+                "build",
+                "--config",
+                "default",
+                "--fail-on-warnings",
+            ],
+        );
+        match result {
+            Ok(()) => panic!("Expected the build to fail"),
+            Err(err) => assert_eq!(
+                err.to_string(),
+                "Task 'image' failed with 2 bitbake warning(s) where 0 is allowed"
+            ),
+        }
+        let warnings: Vec<PathBuf> = std::fs::read_dir(&logs_dir)
+            .expect("Failed to read logs dir")
+            .map(|e| e.expect("Failed to read entry").path())
+            .filter(|p| p.to_string_lossy().ends_with(".warnings.json"))
+            .collect();
+        assert_eq!(warnings.len(), 1);
+        let content: String =
+            std::fs::read_to_string(&warnings[0]).expect("Failed to read warnings");
+        let data: serde_json::Value = serde_json::from_str(&content).expect("Invalid json");
+        assert_eq!(data["test-recipe-1.0-r0"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_cmd_build_warnings_budget() {
+        let (result, _logs_dir) =
+            helper_test_build_warnings("2", vec!["bakery", "build", "--config", "default"]);
+        assert!(result.is_ok());
+        let (result, _logs_dir) =
+            helper_test_build_warnings("1", vec!["bakery", "build", "--config", "default"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cmd_build_trace() {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            },
+            "docker": {
+                "disabled": "true"
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {},
+            "tasks": {
+                "task-name": {
+                    "index": "1",
+                    "name": "task-name",
+                    "type": "non-bitbake",
+                    "builddir": "test-dir",
+                    "build": "test.sh",
+                    "clean": "rm -rf test-dir"
+                }
+            }
+        }
+        "#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let trace_file: PathBuf = work_dir.join("trace/build.json");
+        let mut mocked_system: MockSystem = MockSystem::new();
+        mocked_system
+            .expect_check_call_log()
+            .returning(|_x, _y| Ok(()));
+        mocked_system.expect_env().returning(|| HashMap::new());
+        let result: Result<(), BError> = helper_test_build_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            Box::new(BLogger::new()),
+            Box::new(mocked_system),
+            vec![
+                "bakery",
+                "build",
+                "--config",
+                "default",
+                "--trace",
+                &trace_file.to_string_lossy(),
+            ],
+        );
+        assert!(result.is_ok());
+        let trace: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(&trace_file).expect("Failed to read trace"),
+        )
+        .expect("Invalid json");
+        let names: Vec<&str> = trace["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|e| e["ph"] == "X")
+            .map(|e| e["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["collect task-name", "task-name"]);
+    }
+
     #[test]
     fn test_cmd_build_docker_bitbake() {
         let json_ws_settings: &str = r#"
@@ -1142,29 +1844,32 @@ mod tests {
         let build_dir: PathBuf = work_dir.join("builds/default");
         let mut mocked_system: MockSystem = MockSystem::new();
         mocked_system
-            .expect_check_call()
-            .with(mockall::predicate::eq(CallParams {
-                cmd_line: vec![
-                    "cd",
-                    &build_dir.to_string_lossy().to_string(),
-                    "&&",
-                    "devtool",
-                    "create-workspace",
-                    "&&",
-                    "bitbake",
-                    "test-image",
-                ]
-                .iter()
-                .map(|s| s.to_string())
-                .collect(),
-                env: HashMap::from([(
-                    String::from("BB_ENV_PASSTHROUGH_ADDITIONS"),
-                    String::from("SSTATE_DIR DL_DIR TMPDIR"),
-                )]),
-                shell: true,
-            }))
+            .expect_check_call_log()
+            .with(
+                mockall::predicate::eq(CallParams {
+                    cmd_line: vec![
+                        "cd",
+                        &build_dir.to_string_lossy().to_string(),
+                        "&&",
+                        "devtool",
+                        "create-workspace",
+                        "&&",
+                        "bitbake",
+                        "test-image",
+                    ]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+                    env: HashMap::from([(
+                        String::from("BB_ENV_PASSTHROUGH_ADDITIONS"),
+                        String::from("SSTATE_DIR DL_DIR TMPDIR"),
+                    )]),
+                    shell: true,
+                }),
+                mockall::predicate::always(),
+            )
             .once()
-            .returning(|_x| Ok(()));
+            .returning(|_x, _y| Ok(()));
         mocked_system
             .expect_init_env_file()
             .returning(|_x, _y| Ok(HashMap::new()));
@@ -1237,55 +1942,61 @@ mod tests {
         let build_dir: PathBuf = work_dir.join("builds/default");
         let mut mocked_system: MockSystem = MockSystem::new();
         mocked_system
-            .expect_check_call()
-            .with(mockall::predicate::eq(CallParams {
-                cmd_line: vec![
-                    "cd",
-                    &build_dir.to_string_lossy().to_string(),
-                    "&&",
-                    "devtool",
-                    "create-workspace",
-                    "&&",
-                    "bitbake",
-                    "image",
-                ]
-                .iter()
-                .map(|s| s.to_string())
-                .collect(),
-                env: HashMap::from([(
-                    String::from("BB_ENV_PASSTHROUGH_ADDITIONS"),
-                    String::from("SSTATE_DIR DL_DIR TMPDIR"),
-                )]),
-                shell: true,
-            }))
+            .expect_check_call_log()
+            .with(
+                mockall::predicate::eq(CallParams {
+                    cmd_line: vec![
+                        "cd",
+                        &build_dir.to_string_lossy().to_string(),
+                        "&&",
+                        "devtool",
+                        "create-workspace",
+                        "&&",
+                        "bitbake",
+                        "image",
+                    ]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+                    env: HashMap::from([(
+                        String::from("BB_ENV_PASSTHROUGH_ADDITIONS"),
+                        String::from("SSTATE_DIR DL_DIR TMPDIR"),
+                    )]),
+                    shell: true,
+                }),
+                mockall::predicate::always(),
+            )
             .once()
-            .returning(|_x| Ok(()));
+            .returning(|_x, _y| Ok(()));
         mocked_system
-            .expect_check_call()
-            .with(mockall::predicate::eq(CallParams {
-                cmd_line: vec![
-                    "cd",
-                    &build_dir.to_string_lossy().to_string(),
-                    "&&",
-                    "devtool",
-                    "create-workspace",
-                    "&&",
-                    "bitbake",
-                    "image",
-                    "-c",
-                    "do_populate_sdk",
-                ]
-                .iter()
-                .map(|s| s.to_string())
-                .collect(),
-                env: HashMap::from([(
-                    String::from("BB_ENV_PASSTHROUGH_ADDITIONS"),
-                    String::from("SSTATE_DIR DL_DIR TMPDIR"),
-                )]),
-                shell: true,
-            }))
+            .expect_check_call_log()
+            .with(
+                mockall::predicate::eq(CallParams {
+                    cmd_line: vec![
+                        "cd",
+                        &build_dir.to_string_lossy().to_string(),
+                        "&&",
+                        "devtool",
+                        "create-workspace",
+                        "&&",
+                        "bitbake",
+                        "image",
+                        "-c",
+                        "do_populate_sdk",
+                    ]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+                    env: HashMap::from([(
+                        String::from("BB_ENV_PASSTHROUGH_ADDITIONS"),
+                        String::from("SSTATE_DIR DL_DIR TMPDIR"),
+                    )]),
+                    shell: true,
+                }),
+                mockall::predicate::always(),
+            )
             .once()
-            .returning(|_x| Ok(()));
+            .returning(|_x, _y| Ok(()));
         mocked_system
             .expect_init_env_file()
             .returning(|_x, _y| Ok(HashMap::new()));
@@ -1321,6 +2032,121 @@ mod tests {
         helper_test_local_conf_args(&mut vec!["--tar-balls"], Some(&local_conf_lines), None);
     }
 
+    #[test]
+    fn test_cmd_build_arg_offline() {
+        let mut local_conf_lines: String = String::from("");
+        local_conf_lines.push_str("BB_NO_NETWORK = \"1\"\n");
+        helper_test_local_conf_args(&mut vec!["--offline"], Some(&local_conf_lines), None);
+    }
+
+    #[test]
+    fn test_cmd_build_hermetic_docker_disabled() {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            },
+            "docker": {
+                "disabled": "true"
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {}
+        }
+        "#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let result: Result<(), BError> = helper_test_build_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            Box::new(BLogger::new()),
+            Box::new(MockSystem::new()),
+            vec!["bakery", "build", "--config", "default", "--hermetic"],
+        );
+        match result {
+            Ok(()) => panic!("Expected the build to fail"),
+            Err(err) => assert_eq!(
+                err.to_string(),
+                "A hermetic build requires docker to be enabled in the workspace settings"
+            ),
+        }
+    }
+
+    #[test]
+    fn test_cmd_build_hermetic_fetch_cmd_line() {
+        let cli: Cli = Cli::new(
+            Box::new(BLogger::new()),
+            Box::new(MockSystem::new()),
+            clap::Command::new("bakery"),
+            Some(vec![
+                "bakery",
+                "build",
+                "--config",
+                "default",
+                "--tasks",
+                "image,sdk",
+                "--variant",
+                "release",
+                "--platform-version",
+                "1.2.3",
+                "--build-id",
+                "4",
+                "--archiver",
+                "--hermetic",
+                "--interactive",
+                "false",
+                "--context",
+                "KEY=VALUE",
+            ]),
+        );
+        let cmd: BuildCommand = BuildCommand::new();
+        let cmd_line: Vec<String> = cmd
+            .fetch_cmd_line(&cli)
+            .expect("Failed to setup fetch command line");
+        assert_eq!(
+            cmd_line,
+            vec![
+                "bakery",
+                "fetch",
+                "--config",
+                "default",
+                "--platform-version",
+                "1.2.3",
+                "--build-id",
+                "4",
+                "--build-sha",
+                "dev",
+                "--variant",
+                "release",
+                "--interactive",
+                "false",
+                "--tasks",
+                "image,sdk",
+                "--archiver",
+                "--context",
+                "KEY=VALUE",
+            ]
+        );
+        // The fetch sub-command should support all the args forwarded by the build
+        let fetch: Cli = Cli::new(
+            Box::new(BLogger::new()),
+            Box::new(MockSystem::new()),
+            clap::Command::new("bakery"),
+            Some(cmd_line.iter().map(|s| s.as_str()).collect()),
+        );
+        assert!(fetch.get_args().subcommand_matches("fetch").is_some());
+    }
+
     #[test]
     fn test_cmd_build_arg_debug_symbols() {
         let mut local_conf_lines: String = String::from("");
@@ -1337,6 +2163,20 @@ mod tests {
         helper_test_local_conf_args(&mut vec!["--archiver"], Some(&local_conf_lines), None);
     }
 
+    #[test]
+    fn test_cmd_build_arg_cve_check() {
+        let mut local_conf_lines: String = String::from("");
+        local_conf_lines.push_str("INHERIT += \"cve-check\"\n");
+        helper_test_local_conf_args(&mut vec!["--cve-check"], Some(&local_conf_lines), None);
+    }
+
+    #[test]
+    fn test_cmd_build_arg_sbom() {
+        let mut local_conf_lines: String = String::from("");
+        local_conf_lines.push_str("INHERIT += \"create-spdx\"\n");
+        helper_test_local_conf_args(&mut vec!["--sbom"], Some(&local_conf_lines), None);
+    }
+
     #[test]
     fn test_cmd_build_arg_bitbake_variables() {
         let mut bb_variables: String = String::from("");
@@ -1412,24 +2252,27 @@ mod tests {
         let build_dir: PathBuf = work_dir.join("build/dir3/dir2");
         let mut mocked_system: MockSystem = MockSystem::new();
         mocked_system
-            .expect_check_call()
-            .with(mockall::predicate::eq(CallParams {
-                cmd_line: vec![
-                    "cd",
-                    &build_dir.to_string_lossy().to_string(),
-                    "&&",
-                    "test.sh",
-                    "build",
-                    "test",
-                ]
-                .iter()
-                .map(|s| s.to_string())
-                .collect(),
-                env: HashMap::new(),
-                shell: true,
-            }))
+            .expect_check_call_log()
+            .with(
+                mockall::predicate::eq(CallParams {
+                    cmd_line: vec![
+                        "cd",
+                        &build_dir.to_string_lossy().to_string(),
+                        "&&",
+                        "test.sh",
+                        "build",
+                        "test",
+                    ]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+                    env: HashMap::new(),
+                    shell: true,
+                }),
+                mockall::predicate::always(),
+            )
             .once()
-            .returning(|_x| Ok(()));
+            .returning(|_x, _y| Ok(()));
         mocked_system
             .expect_init_env_file()
             .returning(|_x, _y| Ok(HashMap::new()));
@@ -0,0 +1,328 @@
+use indexmap::IndexMap;
+use serde_json::Value;
+use std::path::PathBuf;
+
+use crate::cli::Cli;
+use crate::commands::{BBaseCommand, BCommand};
+use crate::data::WsContextData;
+use crate::error::BError;
+use crate::fs::{BHImageDiff, BuildHistory};
+use crate::workspace::{Mode, Workspace};
+
+static BCOMMAND: &str = "buildhistory";
+static BCOMMAND_ABOUT: &str = "Compare the images in two builds recorded in the buildhistory.";
+pub struct BuildHistoryCommand {
+    cmd: BBaseCommand,
+    // Your struct fields and methods here
+}
+
+impl BCommand for BuildHistoryCommand {
+    fn get_config_name(&self, cli: &Cli) -> String {
+        if let Some(sub_matches) = cli.get_args().subcommand_matches(BCOMMAND) {
+            if sub_matches.contains_id("config") {
+                if let Some(value) = sub_matches.get_one::<String>("config") {
+                    return value.clone();
+                }
+            }
+        }
+
+        return String::from("default");
+    }
+
+    fn cmd_str(&self) -> &str {
+        &self.cmd.cmd_str
+    }
+
+    fn subcommand(&self) -> &clap::Command {
+        &self.cmd.sub_cmd
+    }
+
+    fn is_docker_required(&self) -> bool {
+        self.cmd.require_docker
+    }
+
+    fn execute(&self, cli: &Cli, workspace: &mut Workspace) -> Result<(), BError> {
+        let config: String = self.get_arg_str(cli, "config", BCOMMAND)?;
+        let rev1: String = self.get_arg_str(cli, "rev1", BCOMMAND)?;
+        let rev2: String = self.get_arg_str(cli, "rev2", BCOMMAND)?;
+        let json: bool = self.get_arg_flag(cli, "json", BCOMMAND)?;
+        let ctx: Vec<String> = self.get_arg_many(cli, "ctx", BCOMMAND)?;
+        let args_context: IndexMap<String, String> = self.setup_context(ctx);
+        let context: WsContextData = WsContextData::new(&args_context)?;
+
+        if !workspace.valid_config(config.as_str()) {
+            return Err(BError::CliError(format!(
+                "Unsupported build config '{}'",
+                config
+            )));
+        }
+
+        if workspace.settings().mode() == Mode::SETUP {
+            return Err(BError::CmdInsideWorkspace(self.cmd.cmd_str.to_string()));
+        }
+
+        workspace.update_ctx(&context)?;
+
+        /*
+         * The buildhistory is only available if the build was done with
+         * --build-history and is read directly from the git repository
+         * in the bitbake build dir so there is no need to bootstrap into
+         * docker.
+         */
+        let dir: PathBuf = workspace.config().build_data().bitbake().buildhistory_dir();
+        if !dir.join(".git").exists() {
+            return Err(BError::CliError(format!(
+                "No buildhistory found in '{}', make sure to build with --build-history",
+                dir.display()
+            )));
+        }
+
+        let history: BuildHistory = BuildHistory::new(cli, &dir);
+        let diffs: Vec<BHImageDiff> = history.diff(&rev1, &rev2)?;
+        if json {
+            let result: Value = Value::Array(diffs.iter().map(|d| d.to_json()).collect());
+            cli.stdout(serde_json::to_string_pretty(&result)?);
+        } else {
+            for diff in diffs.iter() {
+                for line in diff.summary() {
+                    cli.stdout(line);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BuildHistoryCommand {
+    pub fn new() -> Self {
+        let subcmd: clap::Command = clap::Command::new(BCOMMAND)
+        .about(BCOMMAND_ABOUT)
+        .arg(
+            clap::Arg::new("action")
+                .value_name("action")
+                .required(true)
+                .value_parser(["diff"])
+                .help("Compare the images between two revisions of the buildhistory."),
+          )
+          .arg(
+            clap::Arg::new("rev1")
+                .value_name("rev1")
+                .default_value("HEAD~1")
+                .help("The buildhistory revision to compare from."),
+          )
+          .arg(
+            clap::Arg::new("rev2")
+                .value_name("rev2")
+                .default_value("HEAD")
+                .help("The buildhistory revision to compare to."),
+          )
+          .arg(
+            clap::Arg::new("json")
+                .action(clap::ArgAction::SetTrue)
+                .long("json")
+                .help("Print the result as json."),
+          )
+          .arg(
+            clap::Arg::new("config")
+                .short('c')
+                .long("config")
+                .help("The build config the buildhistory was recorded for")
+                .value_name("name")
+                .required(true),
+          )
+          .arg(
+            clap::Arg::new("ctx")
+                .action(clap::ArgAction::Append)
+                .short('x')
+                .long("context")
+                .value_name("KEY=VALUE")
+                .help("Adding variable to the context. Any KEY that already exists in the context will be overwriten."),
+          );
+        // Initialize and return a new BuildHistoryCommand instance
+        BuildHistoryCommand {
+            // Initialize fields if any
+            cmd: BBaseCommand {
+                cmd_str: String::from(BCOMMAND),
+                sub_cmd: subcmd,
+                interactive: true,
+                require_docker: false,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    use crate::cli::*;
+    use crate::commands::{BCommand, BuildHistoryCommand};
+    use crate::error::BError;
+    use crate::workspace::{Workspace, WsBuildConfigHandler, WsSettingsHandler};
+
+    fn helper_test_buildhistory_subcommand(
+        json_ws_settings: &str,
+        json_build_config: &str,
+        work_dir: &PathBuf,
+        logger: Box<dyn Logger>,
+        system: Box<dyn System>,
+        cmd_line: Vec<&str>,
+    ) -> Result<(), BError> {
+        let settings: WsSettingsHandler =
+            WsSettingsHandler::from_str(work_dir, json_ws_settings, None)?;
+        let config: WsBuildConfigHandler =
+            WsBuildConfigHandler::from_str(json_build_config, &settings)?;
+        let mut workspace: Workspace =
+            Workspace::new(Some(work_dir.to_owned()), Some(settings), Some(config))?;
+        let cli: Cli = Cli::new(logger, system, clap::Command::new("bakery"), Some(cmd_line));
+        let cmd: BuildHistoryCommand = BuildHistoryCommand::new();
+        cmd.execute(&cli, &mut workspace)
+    }
+
+    fn helper_git(
+        mocked_system: &mut MockSystem,
+        dir: &PathBuf,
+        args: Vec<&str>,
+        output: &'static str,
+    ) {
+        let mut cmd_line: Vec<String> = vec!["git".to_string(), "-C".to_string()];
+        cmd_line.push(dir.display().to_string());
+        cmd_line.extend(args.iter().map(|a| a.to_string()));
+        mocked_system
+            .expect_check_output()
+            .with(mockall::predicate::eq(CallParams {
+                cmd_line,
+                env: HashMap::new(),
+                shell: true,
+            }))
+            .once()
+            .returning(move |_x| Ok(String::from(output)));
+    }
+
+    #[test]
+    fn test_cmd_buildhistory_diff() {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {}
+        }
+        "#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let dir: PathBuf = work_dir.join("builds/default/buildhistory");
+        std::fs::create_dir_all(dir.join(".git")).expect("Failed to create buildhistory");
+        let image: &str = "images/qemux86_64/glibc/test-image";
+        let mut mocked_system: MockSystem = MockSystem::new();
+        for rev in ["v1", "v2"] {
+            helper_git(
+                &mut mocked_system,
+                &dir,
+                vec!["ls-tree", "-r", "--name-only", rev, "images"],
+                "images/qemux86_64/glibc/test-image/image-info.txt\n",
+            );
+        }
+        helper_git(
+            &mut mocked_system,
+            &dir,
+            vec!["show", &format!("v1:{}/installed-package-sizes.txt", image)],
+            "1024\tKiB busybox\n",
+        );
+        helper_git(
+            &mut mocked_system,
+            &dir,
+            vec!["show", &format!("v1:{}/installed-packages.txt", image)],
+            "busybox_1.36.0-r0_core2-64.ipk\n",
+        );
+        helper_git(
+            &mut mocked_system,
+            &dir,
+            vec!["show", &format!("v1:{}/image-info.txt", image)],
+            "IMAGESIZE = 4096\n",
+        );
+        helper_git(
+            &mut mocked_system,
+            &dir,
+            vec!["show", &format!("v2:{}/installed-package-sizes.txt", image)],
+            "1100\tKiB busybox\n",
+        );
+        helper_git(
+            &mut mocked_system,
+            &dir,
+            vec!["show", &format!("v2:{}/installed-packages.txt", image)],
+            "busybox_1.36.1-r0_core2-64.ipk\n",
+        );
+        helper_git(
+            &mut mocked_system,
+            &dir,
+            vec!["show", &format!("v2:{}/image-info.txt", image)],
+            "IMAGESIZE = 4172\n",
+        );
+        let result: Result<(), BError> = helper_test_buildhistory_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            Box::new(BLogger::new()),
+            Box::new(mocked_system),
+            vec![
+                "bakery",
+                "buildhistory",
+                "diff",
+                "--config",
+                "default",
+                "v1",
+                "v2",
+                "--json",
+            ],
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_cmd_buildhistory_missing() {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {}
+        }
+        "#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let result: Result<(), BError> = helper_test_buildhistory_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            Box::new(BLogger::new()),
+            Box::new(MockSystem::new()),
+            vec!["bakery", "buildhistory", "diff", "--config", "default"],
+        );
+        assert!(result.is_err());
+    }
+}
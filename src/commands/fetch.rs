@@ -0,0 +1,440 @@
+use indexmap::IndexMap;
+use std::collections::HashMap;
+
+use crate::cli::Cli;
+use crate::commands::{BBaseCommand, BCommand};
+use crate::data::WsContextData;
+use crate::error::BError;
+use crate::workspace::{Mode, Workspace, WsTaskHandler};
+
+static BCOMMAND: &str = "fetch";
+static BCOMMAND_ABOUT: &str =
+    "Fetch all the sources required by the bitbake tasks of a build config into the download cache.";
+pub struct FetchCommand {
+    cmd: BBaseCommand,
+    // Your struct fields and methods here
+}
+
+impl BCommand for FetchCommand {
+    fn get_config_name(&self, cli: &Cli) -> String {
+        if let Some(sub_matches) = cli.get_args().subcommand_matches(BCOMMAND) {
+            if sub_matches.contains_id("config") {
+                if let Some(value) = sub_matches.get_one::<String>("config") {
+                    return value.clone();
+                }
+            }
+        }
+
+        return String::from("default");
+    }
+
+    fn cmd_str(&self) -> &str {
+        &self.cmd.cmd_str
+    }
+
+    fn subcommand(&self) -> &clap::Command {
+        &self.cmd.sub_cmd
+    }
+
+    fn is_docker_required(&self) -> bool {
+        self.cmd.require_docker
+    }
+
+    fn execute(&self, cli: &Cli, workspace: &mut Workspace) -> Result<(), BError> {
+        let config: String = self.get_arg_str(cli, "config", BCOMMAND)?;
+        let tar_balls: bool = self.get_arg_flag(cli, "tar_balls", BCOMMAND)?;
+        let interactive: bool = self.get_arg_bool(cli, "interactive", BCOMMAND)?;
+        let ctx: Vec<String> = self.get_arg_many(cli, "ctx", BCOMMAND)?;
+        let env: Vec<String> = self.get_arg_many(cli, "env", BCOMMAND)?;
+        let tasks: Vec<String> = self.get_arg_many(cli, "tasks", BCOMMAND)?;
+        let mut bb_variables: Vec<String> = Vec::new();
+
+        if workspace.settings().mode() == Mode::SETUP {
+            return Err(BError::CmdInsideWorkspace(self.cmd.cmd_str.to_string()));
+        }
+
+        if !workspace.valid_config(config.as_str()) {
+            return Err(BError::CliError(format!(
+                "Unsupported build config '{}'",
+                config
+            )));
+        }
+
+        /*
+         * If Docker is enabled in the workspace settings, Bakery will be bootstrapped into
+         * a Docker container where all baking operations are performed.
+         */
+        if !workspace.settings().docker_disabled()
+            && self.is_docker_required()
+            && !cli.inside_docker()
+        {
+            return self.bootstrap(&cli.get_cmd_line(), cli, workspace, &vec![], interactive);
+        }
+
+        /*
+         * The downloads are stored in the DL_DIR of the workspace cache so
+         * generating mirror tarballs makes it possible to use the download
+         * cache as a source mirror for other workspaces.
+         */
+        if tar_balls {
+            bb_variables.push("BB_GENERATE_MIRROR_TARBALLS = \"1\"".to_string());
+        }
+
+        /*
+         * The local.conf is generated the same way as when building so the
+         * build config is fetching the same sources as the build.
+         */
+        let extra_ctx: IndexMap<String, String> = self.setup_build_context(cli, BCOMMAND)?;
+        bb_variables.append(&mut self.setup_build_bb_variables(&extra_ctx));
+
+        let env_variables: HashMap<String, String> = self.setup_env(env);
+        let mut args_context: IndexMap<String, String> = self.setup_context(ctx);
+        self.setup_build_features(cli, BCOMMAND, &mut bb_variables, &mut args_context)?;
+        let mut context: WsContextData = WsContextData::new(&args_context)?;
+        context.update(&extra_ctx);
+        workspace.update_ctx(&context)?;
+
+        if tasks.len() == 1 && tasks[0] == "all" {
+            for (_t_name, task) in workspace.config().tasks() {
+                task.fetch(
+                    cli,
+                    workspace.config().build_data(),
+                    &bb_variables,
+                    &env_variables,
+                    interactive,
+                    false,
+                )?;
+            }
+        } else {
+            for t_name in tasks.iter() {
+                let task: &WsTaskHandler = workspace.config().task(t_name)?;
+                task.fetch(
+                    cli,
+                    workspace.config().build_data(),
+                    &bb_variables,
+                    &env_variables,
+                    interactive,
+                    true,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FetchCommand {
+    fn setup_env(&self, env: Vec<String>) -> HashMap<String, String> {
+        let variables: HashMap<String, String> = env
+            .iter()
+            .map(|e| {
+                let v: Vec<&str> = e.split('=').collect();
+                (v[0].to_string(), v[1].to_string())
+            })
+            .collect();
+        variables
+    }
+
+    pub fn new() -> Self {
+        let subcmd: clap::Command = clap::Command::new(BCOMMAND)
+            .about(BCOMMAND_ABOUT)
+            .arg_required_else_help(true)
+            .arg(
+                clap::Arg::new("config")
+                    .short('c')
+                    .long("config")
+                    .help("The build config defining the tasks to fetch the sources for")
+                    .value_name("name")
+                    .required(true),
+            )
+            .arg(
+                clap::Arg::new("tasks")
+                    .short('t')
+                    .long("tasks")
+                    .value_name("tasks")
+                    .default_value("all")
+                    .value_delimiter(',')
+                    .help("The task(s) to fetch the sources for."),
+            )
+            .arg(
+                clap::Arg::new("tar_balls")
+                    .action(clap::ArgAction::SetTrue)
+                    .long("tar-balls")
+                    .help("This will add BB_GENERATE_MIRROR_TARBALLS=1 to the local.conf so that the git and svn repositories are also stored as tarballs in the download cache."),
+            )
+            .arg(
+                clap::Arg::new("archiver")
+                    .action(clap::ArgAction::SetTrue)
+                    .long("archiver")
+                    .help("Setting context variable BKRY_ARCHIVER to 1 which will result in adding the archiver class to the local.conf."),
+            )
+            .arg(
+                clap::Arg::new("cve_check")
+                    .action(clap::ArgAction::SetTrue)
+                    .long("cve-check")
+                    .help("Setting context variable BKRY_CVE_CHECK to 1 which will result in adding the cve-check class to the local.conf."),
+            )
+            .arg(
+                clap::Arg::new("sbom")
+                    .action(clap::ArgAction::SetTrue)
+                    .long("sbom")
+                    .help("Setting context variable BKRY_SBOM to 1 which will result in adding the create-spdx class to the local.conf."),
+            )
+            .arg(
+                clap::Arg::new("debug_symbols")
+                    .action(clap::ArgAction::SetTrue)
+                    .long("debug-symbols")
+                    .help("Setting context variable BKRY_DEBUG_SYMBOLS to 1 which will result in adding IMAGE_GEN_DEBUGFS=1 to the local.conf."),
+            )
+            .arg(
+                clap::Arg::new("platform_version")
+                    .short('r')
+                    .long("platform-version")
+                    .value_name("x.y.z")
+                    .default_value("0.0.0")
+                    .help("Platform version number for the build. Will be available as context variable BKRY_PLATFORM_VERSION"),
+            )
+            .arg(
+                clap::Arg::new("build_sha")
+                    .short('s')
+                    .long("build-sha")
+                    .value_name("sha")
+                    .default_value("dev")
+                    .help("Sha for the current build. Will be available as a context variable BKRY_BUILD_SHA"),
+            )
+            .arg(
+                clap::Arg::new("variant")
+                    .short('a')
+                    .long("variant")
+                    .value_name("variant")
+                    .default_value("dev")
+                    .value_parser(["dev", "test", "release"])
+                    .help("Specify the variant of the build it can be one of release, dev or test. Will be available as a context variable BKRY_BUILD_VARIANT"),
+            )
+            .arg(
+                clap::Arg::new("build_id")
+                    .short('n')
+                    .long("build-id")
+                    .value_name("nbr")
+                    .default_value("0")
+                    .help("Build id number can be used if x.y.z is not enough for some reason and will be part of BKRY_PLATFORM_RELEASE x.y.z-w"),
+            )
+            .arg(
+                clap::Arg::new("env")
+                    .action(clap::ArgAction::Append)
+                    .short('e')
+                    .long("env")
+                    .value_name("KEY=VALUE")
+                    .help("Extra variables to add to build env for bitbake."),
+            )
+            .arg(
+                clap::Arg::new("interactive")
+                    .short('i')
+                    .long("interactive")
+                    .value_name("interactive")
+                    .default_value("true")
+                    .value_parser(["true", "false"])
+                    .help("Determines whether a fetch inside Docker should be interactive. This can be useful to set to false when running in CI environments."),
+            )
+            .arg(
+                clap::Arg::new("ctx")
+                    .action(clap::ArgAction::Append)
+                    .short('x')
+                    .long("context")
+                    .value_name("KEY=VALUE")
+                    .help("Adding variable to the context. Any KEY that already exists in the context will be overwriten."),
+            );
+        // Initialize and return a new FetchCommand instance
+        FetchCommand {
+            // Initialize fields if any
+            cmd: BBaseCommand {
+                cmd_str: String::from(BCOMMAND),
+                sub_cmd: subcmd,
+                interactive: true,
+                require_docker: true,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    use crate::cli::*;
+    use crate::commands::{BCommand, FetchCommand};
+    use crate::error::BError;
+    use crate::workspace::{Workspace, WsBuildConfigHandler, WsSettingsHandler};
+
+    fn helper_test_fetch_subcommand(
+        json_ws_settings: &str,
+        json_build_config: &str,
+        work_dir: &PathBuf,
+        logger: Box<dyn Logger>,
+        system: Box<dyn System>,
+        cmd_line: Vec<&str>,
+    ) -> Result<(), BError> {
+        let settings: WsSettingsHandler =
+            WsSettingsHandler::from_str(work_dir, json_ws_settings, None)?;
+        let config: WsBuildConfigHandler =
+            WsBuildConfigHandler::from_str(json_build_config, &settings)?;
+        let mut workspace: Workspace =
+            Workspace::new(Some(work_dir.to_owned()), Some(settings), Some(config))?;
+        let cli: Cli = Cli::new(logger, system, clap::Command::new("bakery"), Some(cmd_line));
+        let cmd: FetchCommand = FetchCommand::new();
+        cmd.execute(&cli, &mut workspace)
+    }
+
+    #[test]
+    fn test_cmd_fetch() {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            },
+            "docker": {
+                "disabled": "true"
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {},
+            "tasks": {
+                "image": {
+                    "index": "1",
+                    "name": "image",
+                    "recipes": [
+                        "test-image"
+                    ]
+                },
+                "sign": {
+                    "index": "2",
+                    "name": "sign",
+                    "type": "non-bitbake",
+                    "builddir": "test-dir",
+                    "build": "sign.sh"
+                }
+            }
+        }
+        "#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let build_dir: PathBuf = work_dir.join("builds/default");
+        let mut mocked_system: MockSystem = MockSystem::new();
+        mocked_system
+            .expect_check_call()
+            .with(mockall::predicate::eq(CallParams {
+                cmd_line: vec![
+                    "cd",
+                    &build_dir.to_string_lossy().to_string(),
+                    "&&",
+                    "devtool",
+                    "create-workspace",
+                    "&&",
+                    "bitbake",
+                    "test-image",
+                    "--runall=fetch",
+                ]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+                env: HashMap::from([(
+                    String::from("BB_ENV_PASSTHROUGH_ADDITIONS"),
+                    String::from("SSTATE_DIR DL_DIR TMPDIR"),
+                )]),
+                shell: true,
+            }))
+            .once()
+            .returning(|_x| Ok(()));
+        mocked_system
+            .expect_init_env_file()
+            .returning(|_x, _y| Ok(HashMap::new()));
+        let result: Result<(), BError> = helper_test_fetch_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            Box::new(BLogger::new()),
+            Box::new(mocked_system),
+            vec!["bakery", "fetch", "--config", "default"],
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_cmd_fetch_variant() {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            },
+            "docker": {
+                "disabled": "true"
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {},
+            "tasks": {
+                "image": {
+                    "index": "1",
+                    "name": "image",
+                    "recipes": [
+                        "test-image"
+                    ]
+                }
+            }
+        }
+        "#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let local_conf: PathBuf = work_dir.join("builds/default/conf/local.conf");
+        let mut mocked_system: MockSystem = MockSystem::new();
+        mocked_system.expect_check_call().returning(|_x| Ok(()));
+        mocked_system
+            .expect_init_env_file()
+            .returning(|_x, _y| Ok(HashMap::new()));
+        let result: Result<(), BError> = helper_test_fetch_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            Box::new(BLogger::new()),
+            Box::new(mocked_system),
+            vec![
+                "bakery",
+                "fetch",
+                "--config",
+                "default",
+                "--variant",
+                "release",
+                "--platform-version",
+                "1.2.3",
+                "--build-id",
+                "4",
+            ],
+        );
+        assert!(result.is_ok());
+        let content: String =
+            std::fs::read_to_string(&local_conf).expect("Failed to read local.conf");
+        assert!(content.contains("BUILD_VARIANT ?= \"release\"\n"));
+        assert!(content.contains("RELEASE_BUILD ?= \"1\"\n"));
+        assert!(content.contains("PLATFORM_RELEASE ?= \"1.2.3-4\"\n"));
+    }
+}
@@ -70,6 +70,111 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_get_fetch_command() {
+        let cmd_handler: CmdHandler = CmdHandler::new();
+        let cmd: Result<&Box<dyn BCommand>, BError> = cmd_handler.get_cmd("fetch");
+
+        match cmd {
+            Ok(command) => {
+                assert_eq!(command.cmd_str(), "fetch");
+            }
+            Err(err) => {
+                assert!(false, "Expected OK result, but got an error '{}'", err);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_bbvar_command() {
+        let cmd_handler: CmdHandler = CmdHandler::new();
+        let cmd: Result<&Box<dyn BCommand>, BError> = cmd_handler.get_cmd("bbvar");
+
+        match cmd {
+            Ok(command) => {
+                assert_eq!(command.cmd_str(), "bbvar");
+            }
+            Err(err) => {
+                assert!(false, "Expected OK result, but got an error '{}'", err);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_query_command() {
+        let cmd_handler: CmdHandler = CmdHandler::new();
+        let cmd: Result<&Box<dyn BCommand>, BError> = cmd_handler.get_cmd("query");
+
+        match cmd {
+            Ok(command) => {
+                assert_eq!(command.cmd_str(), "query");
+            }
+            Err(err) => {
+                assert!(false, "Expected OK result, but got an error '{}'", err);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_sigdiff_command() {
+        let cmd_handler: CmdHandler = CmdHandler::new();
+        let cmd: Result<&Box<dyn BCommand>, BError> = cmd_handler.get_cmd("sigdiff");
+
+        match cmd {
+            Ok(command) => {
+                assert_eq!(command.cmd_str(), "sigdiff");
+            }
+            Err(err) => {
+                assert!(false, "Expected OK result, but got an error '{}'", err);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_buildhistory_command() {
+        let cmd_handler: CmdHandler = CmdHandler::new();
+        let cmd: Result<&Box<dyn BCommand>, BError> = cmd_handler.get_cmd("buildhistory");
+
+        match cmd {
+            Ok(command) => {
+                assert_eq!(command.cmd_str(), "buildhistory");
+            }
+            Err(err) => {
+                assert!(false, "Expected OK result, but got an error '{}'", err);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_artifacts_command() {
+        let cmd_handler: CmdHandler = CmdHandler::new();
+        let cmd: Result<&Box<dyn BCommand>, BError> = cmd_handler.get_cmd("artifacts");
+
+        match cmd {
+            Ok(command) => {
+                assert_eq!(command.cmd_str(), "artifacts");
+            }
+            Err(err) => {
+                assert!(false, "Expected OK result, but got an error '{}'", err);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_services_command() {
+        let cmd_handler: CmdHandler = CmdHandler::new();
+        let cmd: Result<&Box<dyn BCommand>, BError> = cmd_handler.get_cmd("services");
+
+        match cmd {
+            Ok(command) => {
+                assert_eq!(command.cmd_str(), "services");
+            }
+            Err(err) => {
+                assert!(false, "Expected OK result, but got an error '{}'", err);
+            }
+        }
+    }
+
     #[test]
     fn test_get_shell_command() {
         let cmd_handler: CmdHandler = CmdHandler::new();
@@ -1,18 +1,29 @@
+pub mod artifacts;
+pub mod bbvar;
 pub mod build;
+pub mod buildhistory;
 pub mod clean;
 pub mod deploy;
+pub mod fetch;
 pub mod handler;
 pub mod list;
+pub mod query;
+pub mod services;
 pub mod setup;
 pub mod shell;
+pub mod sigdiff;
 pub mod sync;
 pub mod upload;
 
-use indexmap::IndexMap;
+use indexmap::{indexmap, IndexMap};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
 use crate::cli::Cli;
+use crate::data::context::{
+    CTX_KEY_BUILD_ID, CTX_KEY_BUILD_SHA, CTX_KEY_BUILD_VARIANT, CTX_KEY_PLATFORM_RELEASE,
+    CTX_KEY_PLATFORM_VERSION, CTX_KEY_RELEASE_BUILD,
+};
 use crate::error::BError;
 use crate::executers::docker::Docker;
 use crate::workspace::Workspace;
@@ -30,6 +41,95 @@ pub trait BCommand {
         context
     }
 
+    /*
+     * Setup the context variables from the build args platform version,
+     * build id, build sha and variant. Any command that is setting up a
+     * build config the same way as the build command needs to have the
+     * same args as the build command.
+     */
+    fn setup_build_context(
+        &self,
+        cli: &Cli,
+        cmd: &str,
+    ) -> Result<IndexMap<String, String>, BError> {
+        let version: String = self.get_arg_str(cli, "platform_version", cmd)?;
+        let build_id: String = self.get_arg_str(cli, "build_id", cmd)?;
+        let sha: String = self.get_arg_str(cli, "build_sha", cmd)?;
+        let variant: String = self.get_arg_str(cli, "variant", cmd)?;
+        let mut extra_ctx: IndexMap<String, String> = indexmap! {
+            String::from(CTX_KEY_PLATFORM_VERSION) => version.clone(),
+            String::from(CTX_KEY_BUILD_ID) => build_id.clone(),
+            String::from(CTX_KEY_BUILD_SHA) => sha,
+            String::from(CTX_KEY_RELEASE_BUILD) => "0".to_string(),
+            String::from(CTX_KEY_BUILD_VARIANT) => variant.clone(),
+            String::from(CTX_KEY_PLATFORM_RELEASE) => format!("{}-{}", version, build_id),
+        };
+
+        if variant == "release" {
+            /*
+             * Build commands defined in the build config needs to
+             * know if it is release build or not running by including
+             * the BKRY_BUILD_VARIANT to the context we can expose this to
+             * the build commands. We are keeping BKRY_RELEASE_BUILD for
+             * backwards compatibility but should be replaced with BKRY_BUILD_VARIANT
+             */
+            extra_ctx.insert(String::from(CTX_KEY_BUILD_VARIANT), "release".to_string());
+            extra_ctx.insert(String::from(CTX_KEY_RELEASE_BUILD), "1".to_string());
+        }
+
+        Ok(extra_ctx)
+    }
+
+    /*
+     * Setup the bitbake variables and context variables for the optional
+     * bitbake classes enabled by the build args archiver, cve check, sbom
+     * and debug symbols.
+     */
+    fn setup_build_features(
+        &self,
+        cli: &Cli,
+        cmd: &str,
+        bb_variables: &mut Vec<String>,
+        args_context: &mut IndexMap<String, String>,
+    ) -> Result<(), BError> {
+        if self.get_arg_flag(cli, "archiver", cmd)? {
+            bb_variables.push("INHERIT += \"archiver\"".to_string());
+            bb_variables.push("ARCHIVER_MODE[src] = \"original\"".to_string());
+            args_context.insert("BKRY_ARCHIVER".to_string(), "1".to_string());
+        }
+
+        if self.get_arg_flag(cli, "cve_check", cmd)? {
+            bb_variables.push("INHERIT += \"cve-check\"".to_string());
+            args_context.insert("BKRY_CVE_CHECK".to_string(), "1".to_string());
+        }
+
+        if self.get_arg_flag(cli, "sbom", cmd)? {
+            bb_variables.push("INHERIT += \"create-spdx\"".to_string());
+            args_context.insert("BKRY_SBOM".to_string(), "1".to_string());
+        }
+
+        if self.get_arg_flag(cli, "debug_symbols", cmd)? {
+            bb_variables.push("IMAGE_GEN_DEBUGFS = \"1\"".to_string());
+            bb_variables.push("IMAGE_FSTYPES_DEBUGFS = \"tar.bz2\"".to_string());
+            args_context.insert("BKRY_DEBUG_SYMBOLS".to_string(), "1".to_string());
+        }
+
+        Ok(())
+    }
+
+    // We need to add the extra context variables to the list of bitbake variables
+    // so they can be added to the bitbake local.conf file if used as env variables
+    // they can also be injected as context variables
+    fn setup_build_bb_variables(&self, extra_ctx: &IndexMap<String, String>) -> Vec<String> {
+        extra_ctx
+            .iter()
+            .map(|(key, value)| {
+                let bb_key: &str = key.strip_prefix("BKRY_").unwrap_or(key);
+                format!("{} ?= \"{}\"", bb_key, value)
+            })
+            .collect()
+    }
+
     fn execute(&self, cli: &Cli, _workspace: &mut Workspace) -> Result<(), BError> {
         cli.info(format!("Execute command {}", self.cmd_str()));
         Ok(())
@@ -53,6 +153,34 @@ pub trait BCommand {
         interactive: bool,
     ) -> Result<(), BError> {
         let docker: Docker = Docker::new(workspace.settings().docker_image(), interactive);
+        self.bootstrap_docker(&docker, cmd_line, cli, workspace, volumes)
+    }
+
+    /*
+     * Bootstrap bakery into a docker container without any network access
+     * so that nothing executed inside of the container can reach the network.
+     */
+    fn bootstrap_hermetic(
+        &self,
+        cmd_line: &Vec<String>,
+        cli: &Cli,
+        workspace: &Workspace,
+        volumes: &Vec<String>,
+        interactive: bool,
+    ) -> Result<(), BError> {
+        let mut docker: Docker = Docker::new(workspace.settings().docker_image(), interactive);
+        docker.set_network("none");
+        self.bootstrap_docker(&docker, cmd_line, cli, workspace, volumes)
+    }
+
+    fn bootstrap_docker(
+        &self,
+        docker: &Docker,
+        cmd_line: &Vec<String>,
+        cli: &Cli,
+        workspace: &Workspace,
+        volumes: &Vec<String>,
+    ) -> Result<(), BError> {
         /*
          * When we bootstrap bakery into docker we should make sure that we pull
          * in the entire env from the parent
@@ -160,18 +288,32 @@ pub fn get_supported_cmds() -> HashMap<&'static str, Box<dyn BCommand>> {
     supported_cmds.insert("upload", Box::new(UploadCommand::new()));
     supported_cmds.insert("setup", Box::new(SetupCommand::new()));
     supported_cmds.insert("sync", Box::new(SyncCommand::new()));
+    supported_cmds.insert("fetch", Box::new(FetchCommand::new()));
+    supported_cmds.insert("bbvar", Box::new(BBVarCommand::new()));
+    supported_cmds.insert("query", Box::new(QueryCommand::new()));
+    supported_cmds.insert("sigdiff", Box::new(SigDiffCommand::new()));
+    supported_cmds.insert("buildhistory", Box::new(BuildHistoryCommand::new()));
+    supported_cmds.insert("services", Box::new(ServicesCommand::new()));
+    supported_cmds.insert("artifacts", Box::new(ArtifactsCommand::new()));
 
     // Add more commands as needed
 
     supported_cmds
 }
 
+pub use artifacts::ArtifactsCommand;
+pub use bbvar::BBVarCommand;
 pub use build::BuildCommand;
+pub use buildhistory::BuildHistoryCommand;
 pub use clean::CleanCommand;
 pub use deploy::DeployCommand;
+pub use fetch::FetchCommand;
 pub use handler::CmdHandler;
 pub use list::ListCommand;
+pub use query::QueryCommand;
+pub use services::ServicesCommand;
 pub use setup::SetupCommand;
 pub use shell::ShellCommand;
+pub use sigdiff::SigDiffCommand;
 pub use sync::SyncCommand;
 pub use upload::UploadCommand;
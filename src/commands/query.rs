@@ -0,0 +1,449 @@
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::cli::Cli;
+use crate::commands::{BBaseCommand, BCommand};
+use crate::data::WsContextData;
+use crate::error::BError;
+use crate::executers::BBQuery;
+use crate::workspace::{Mode, Workspace};
+
+static BCOMMAND: &str = "query";
+static BCOMMAND_ABOUT: &str =
+    "Query the bitbake environment of a build config and print the result as json.";
+pub struct QueryCommand {
+    cmd: BBaseCommand,
+    // Your struct fields and methods here
+}
+
+impl BCommand for QueryCommand {
+    fn get_config_name(&self, cli: &Cli) -> String {
+        if let Some(sub_matches) = cli.get_args().subcommand_matches(BCOMMAND) {
+            if sub_matches.contains_id("config") {
+                if let Some(value) = sub_matches.get_one::<String>("config") {
+                    return value.clone();
+                }
+            }
+        }
+
+        return String::from("default");
+    }
+
+    fn cmd_str(&self) -> &str {
+        &self.cmd.cmd_str
+    }
+
+    fn subcommand(&self) -> &clap::Command {
+        &self.cmd.sub_cmd
+    }
+
+    fn is_docker_required(&self) -> bool {
+        self.cmd.require_docker
+    }
+
+    fn execute(&self, cli: &Cli, workspace: &mut Workspace) -> Result<(), BError> {
+        // The result is printed as json on stdout
+        cli.set_quiet(true);
+        let config: String = self.get_arg_str(cli, "config", BCOMMAND)?;
+        let question: String = self.get_arg_str(cli, "query", BCOMMAND)?;
+        let target: String = self.get_arg_str(cli, "target", BCOMMAND)?;
+        let ctx: Vec<String> = self.get_arg_many(cli, "ctx", BCOMMAND)?;
+        let args_context: IndexMap<String, String> = self.setup_context(ctx);
+        let context: WsContextData = WsContextData::new(&args_context)?;
+        let interactive: bool = self.get_arg_bool(cli, "interactive", BCOMMAND)?;
+
+        if !workspace.valid_config(config.as_str()) {
+            return Err(BError::CliError(format!(
+                "Unsupported build config '{}'",
+                config
+            )));
+        }
+
+        if workspace.settings().mode() == Mode::SETUP {
+            return Err(BError::CmdInsideWorkspace(self.cmd.cmd_str.to_string()));
+        }
+
+        /*
+         * If Docker is enabled in the workspace settings, Bakery will be bootstrapped into
+         * a Docker container where all baking operations are performed.
+         */
+        if !workspace.settings().docker_disabled()
+            && self.is_docker_required()
+            && !cli.inside_docker()
+        {
+            return self.bootstrap(&cli.get_cmd_line(), cli, workspace, &vec![], interactive);
+        }
+
+        workspace.update_ctx(&context)?;
+
+        let query: BBQuery = BBQuery::new(cli, workspace.config().build_data().bitbake());
+        let result: Value = match question.as_str() {
+            "layers" => query.layers()?,
+            "providers" | "packages" if target.is_empty() => {
+                return Err(BError::CliError(format!(
+                    "Query '{}' requires a target",
+                    question
+                )));
+            }
+            "providers" => query.providers(&target)?,
+            _ => query.packages(&target)?,
+        };
+        cli.stdout(serde_json::to_string_pretty(&result)?);
+        Ok(())
+    }
+}
+
+impl QueryCommand {
+    pub fn new() -> Self {
+        let subcmd: clap::Command = clap::Command::new(BCOMMAND)
+        .about(BCOMMAND_ABOUT)
+        .arg(
+            clap::Arg::new("query")
+                .value_name("query")
+                .required(true)
+                .value_parser(["layers", "providers", "packages"])
+                .help("List the layers with priorities, the recipes providing a target or the packages in an image."),
+          )
+          .arg(
+            clap::Arg::new("target")
+                .value_name("target")
+                .default_value("")
+                .help("The target for the providers query or the image for the packages query."),
+          )
+          .arg(
+            clap::Arg::new("config")
+                .short('c')
+                .long("config")
+                .help("The build config used to setup the bitbake environment")
+                .value_name("name")
+                .required(true),
+          )
+          .arg(
+            clap::Arg::new("interactive")
+                .short('i')
+                .long("interactive")
+                .value_name("interactive")
+                .default_value("true")
+                .value_parser(["true", "false"])
+                .help("Determines whether a build inside Docker should be interactive. This can be useful to set to false when running in CI environments."),
+        )
+          .arg(
+            clap::Arg::new("ctx")
+                .action(clap::ArgAction::Append)
+                .short('x')
+                .long("context")
+                .value_name("KEY=VALUE")
+                .help("Adding variable to the context. Any KEY that already exists in the context will be overwriten."),
+          );
+        // Initialize and return a new QueryCommand instance
+        QueryCommand {
+            // Initialize fields if any
+            cmd: BBaseCommand {
+                cmd_str: String::from(BCOMMAND),
+                sub_cmd: subcmd,
+                interactive: true,
+                require_docker: true,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    use crate::cli::*;
+    use crate::commands::{BCommand, QueryCommand};
+    use crate::error::BError;
+    use crate::workspace::{Workspace, WsBuildConfigHandler, WsSettingsHandler};
+
+    fn helper_test_query_subcommand(
+        json_ws_settings: &str,
+        json_build_config: &str,
+        work_dir: &PathBuf,
+        logger: Box<dyn Logger>,
+        system: Box<dyn System>,
+        cmd_line: Vec<&str>,
+    ) -> Result<(), BError> {
+        let settings: WsSettingsHandler =
+            WsSettingsHandler::from_str(work_dir, json_ws_settings, None)?;
+        let config: WsBuildConfigHandler =
+            WsBuildConfigHandler::from_str(json_build_config, &settings)?;
+        let mut workspace: Workspace =
+            Workspace::new(Some(work_dir.to_owned()), Some(settings), Some(config))?;
+        let cli: Cli = Cli::new(logger, system, clap::Command::new("bakery"), Some(cmd_line));
+        let cmd: QueryCommand = QueryCommand::new();
+        cmd.execute(&cli, &mut workspace)
+    }
+
+    #[test]
+    fn test_cmd_query_packages() {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            },
+            "docker": {
+                "disabled": "true"
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {}
+        }
+        "#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let build_dir: String = work_dir.join("builds/default").display().to_string();
+        let conf_dir: PathBuf = work_dir.join("builds/default/conf");
+        std::fs::create_dir_all(&conf_dir).expect("Failed to create conf dir");
+        std::fs::write(conf_dir.join("local.conf"), "").expect("Failed to write local.conf");
+        std::fs::write(conf_dir.join("bblayers.conf"), "").expect("Failed to write bblayers.conf");
+        let mut mocked_system: MockSystem = MockSystem::new();
+        mocked_system
+            .expect_init_env_file()
+            .returning(|_x, _y| Ok(HashMap::new()));
+        let deploy_dir: PathBuf = work_dir.join("builds/default/tmp/deploy/images/test-arch");
+        std::fs::create_dir_all(&deploy_dir).expect("Failed to create deploy dir");
+        std::fs::write(
+            deploy_dir.join("test-image-test-arch.manifest"),
+            "base-files test_arch 3.0.14\n",
+        )
+        .expect("Failed to write manifest");
+        let deploy_dir_str: String = deploy_dir.display().to_string();
+        mocked_system
+            .expect_check_output()
+            .with(mockall::predicate::eq(CallParams {
+                cmd_line: vec![
+                    "cd",
+                    &build_dir,
+                    "&&",
+                    "bitbake-getvar",
+                    "-r",
+                    "test-image",
+                    "--value",
+                    "DEPLOY_DIR_IMAGE",
+                ]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+                env: HashMap::from([(
+                    String::from("BB_ENV_PASSTHROUGH_ADDITIONS"),
+                    String::from("SSTATE_DIR DL_DIR TMPDIR"),
+                )]),
+                shell: true,
+            }))
+            .once()
+            .returning(move |_x| Ok(format!("{}\n", deploy_dir_str)));
+        mocked_system
+            .expect_check_output()
+            .with(mockall::predicate::eq(CallParams {
+                cmd_line: vec![
+                    "cd",
+                    &build_dir,
+                    "&&",
+                    "bitbake-getvar",
+                    "-r",
+                    "test-image",
+                    "--value",
+                    "IMAGE_LINK_NAME",
+                ]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+                env: HashMap::from([(
+                    String::from("BB_ENV_PASSTHROUGH_ADDITIONS"),
+                    String::from("SSTATE_DIR DL_DIR TMPDIR"),
+                )]),
+                shell: true,
+            }))
+            .once()
+            .returning(|_x| Ok(String::from("test-image-test-arch\n")));
+        let result: Result<(), BError> = helper_test_query_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            Box::new(BLogger::new()),
+            Box::new(mocked_system),
+            vec![
+                "bakery",
+                "query",
+                "--config",
+                "default",
+                "packages",
+                "test-image",
+            ],
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_cmd_query_providers_virtual() {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            },
+            "docker": {
+                "disabled": "true"
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {}
+        }
+        "#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let build_dir: String = work_dir.join("builds/default").display().to_string();
+        let conf_dir: PathBuf = work_dir.join("builds/default/conf");
+        std::fs::create_dir_all(&conf_dir).expect("Failed to create conf dir");
+        std::fs::write(conf_dir.join("local.conf"), "").expect("Failed to write local.conf");
+        std::fs::write(conf_dir.join("bblayers.conf"), "").expect("Failed to write bblayers.conf");
+        let env: HashMap<String, String> = HashMap::from([(
+            String::from("BB_ENV_PASSTHROUGH_ADDITIONS"),
+            String::from("SSTATE_DIR DL_DIR TMPDIR"),
+        )]);
+        let mut mocked_system: MockSystem = MockSystem::new();
+        mocked_system
+            .expect_init_env_file()
+            .returning(|_x, _y| Ok(HashMap::new()));
+        mocked_system
+            .expect_check_output()
+            .with(mockall::predicate::eq(CallParams {
+                cmd_line: vec!["cd", &build_dir, "&&", "bitbake", "-e", "virtual/kernel"]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+                env: env.clone(),
+                shell: true,
+            }))
+            .once()
+            .returning(|_x| {
+                Ok(String::from(
+                    r#"# $PREFERRED_PROVIDER_virtual/kernel
+#   set /layers/meta-test/conf/machine/test.conf:3
+#     "linux-test"
+PREFERRED_PROVIDER_virtual/kernel="linux-test"
+# $PN
+PN="linux-test"
+PROVIDES="linux-test  virtual/kernel"
+"#,
+                ))
+            });
+        mocked_system
+            .expect_check_output()
+            .with(mockall::predicate::eq(CallParams {
+                cmd_line: vec![
+                    "cd",
+                    &build_dir,
+                    "&&",
+                    "bitbake-layers",
+                    "show-recipes",
+                    "linux-test",
+                ]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+                env: env.clone(),
+                shell: true,
+            }))
+            .once()
+            .returning(|_x| {
+                Ok(String::from(
+                    "=== Matching recipes: ===\nlinux-test:\n  meta-test            6.6.21\n",
+                ))
+            });
+        let mut mocked_logger: MockLogger = MockLogger::new();
+        mocked_logger
+            .expect_stdout()
+            .withf(|output: &String| {
+                let result: serde_json::Value = serde_json::from_str(output).unwrap();
+                result["target"] == "virtual/kernel"
+                    && result["provider"] == "linux-test"
+                    && result["preferred_provider"] == "linux-test"
+                    && result["provides"] == serde_json::json!(["linux-test", "virtual/kernel"])
+                    && result["recipes"][0]["layer"] == "meta-test"
+            })
+            .once()
+            .returning(|_x| ());
+        let result: Result<(), BError> = helper_test_query_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            Box::new(mocked_logger),
+            Box::new(mocked_system),
+            vec![
+                "bakery",
+                "query",
+                "--config",
+                "default",
+                "providers",
+                "virtual/kernel",
+            ],
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_cmd_query_missing_target() {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            },
+            "docker": {
+                "disabled": "true"
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {}
+        }
+        "#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let mut mocked_system: MockSystem = MockSystem::new();
+        mocked_system
+            .expect_init_env_file()
+            .returning(|_x, _y| Ok(HashMap::new()));
+        let result: Result<(), BError> = helper_test_query_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            Box::new(BLogger::new()),
+            Box::new(mocked_system),
+            vec!["bakery", "query", "--config", "default", "providers"],
+        );
+        match result {
+            Ok(()) => panic!("Expected the query to fail"),
+            Err(err) => assert_eq!(err.to_string(), "Query 'providers' requires a target"),
+        }
+    }
+}
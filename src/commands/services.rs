@@ -0,0 +1,241 @@
+use indexmap::IndexMap;
+
+use crate::cli::Cli;
+use crate::commands::{BBaseCommand, BCommand};
+use crate::data::WsContextData;
+use crate::error::BError;
+use crate::executers::{BBService, BBServices};
+use crate::workspace::{Mode, Workspace};
+
+static BCOMMAND: &str = "services";
+static BCOMMAND_ABOUT: &str =
+    "Start, stop or show the status of the bitbake hash equivalence and PR servers.";
+pub struct ServicesCommand {
+    cmd: BBaseCommand,
+    // Your struct fields and methods here
+}
+
+impl BCommand for ServicesCommand {
+    fn get_config_name(&self, cli: &Cli) -> String {
+        if let Some(sub_matches) = cli.get_args().subcommand_matches(BCOMMAND) {
+            if sub_matches.contains_id("config") {
+                if let Some(value) = sub_matches.get_one::<String>("config") {
+                    return value.clone();
+                }
+            }
+        }
+
+        return String::from("default");
+    }
+
+    fn cmd_str(&self) -> &str {
+        &self.cmd.cmd_str
+    }
+
+    fn subcommand(&self) -> &clap::Command {
+        &self.cmd.sub_cmd
+    }
+
+    fn is_docker_required(&self) -> bool {
+        self.cmd.require_docker
+    }
+
+    fn execute(&self, cli: &Cli, workspace: &mut Workspace) -> Result<(), BError> {
+        let config: String = self.get_arg_str(cli, "config", BCOMMAND)?;
+        let action: String = self.get_arg_str(cli, "action", BCOMMAND)?;
+        let ctx: Vec<String> = self.get_arg_many(cli, "ctx", BCOMMAND)?;
+        let args_context: IndexMap<String, String> = self.setup_context(ctx);
+        let context: WsContextData = WsContextData::new(&args_context)?;
+        let interactive: bool = self.get_arg_bool(cli, "interactive", BCOMMAND)?;
+
+        if !workspace.valid_config(config.as_str()) {
+            return Err(BError::CliError(format!(
+                "Unsupported build config '{}'",
+                config
+            )));
+        }
+
+        if workspace.settings().mode() == Mode::SETUP {
+            return Err(BError::CmdInsideWorkspace(self.cmd.cmd_str.to_string()));
+        }
+
+        /*
+         * If Docker is enabled in the workspace settings, Bakery will be bootstrapped into
+         * a Docker container where all baking operations are performed. Note that any
+         * service started this way will only live as long as the container so to keep
+         * the services running across builds start them from inside a 'bakery shell'.
+         */
+        if !workspace.settings().docker_disabled()
+            && self.is_docker_required()
+            && !cli.inside_docker()
+        {
+            return self.bootstrap(&cli.get_cmd_line(), cli, workspace, &vec![], interactive);
+        }
+
+        workspace.update_ctx(&context)?;
+
+        let services: BBServices = BBServices::new(cli, workspace.config().build_data());
+        match action.as_str() {
+            "start" => {
+                services.start()?;
+            }
+            "stop" => {
+                let enabled: Vec<BBService> = services.enabled();
+                services.stop(&enabled)?;
+            }
+            _ => {
+                services.status()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ServicesCommand {
+    pub fn new() -> Self {
+        let subcmd: clap::Command = clap::Command::new(BCOMMAND)
+        .about(BCOMMAND_ABOUT)
+        .arg(
+            clap::Arg::new("action")
+                .value_name("action")
+                .default_value("status")
+                .value_parser(["start", "stop", "status"])
+                .help("Start or stop the services enabled in the workspace settings or show their status."),
+          )
+          .arg(
+            clap::Arg::new("config")
+                .short('c')
+                .long("config")
+                .help("The build config used to setup the bitbake environment")
+                .value_name("name")
+                .required(true),
+          )
+          .arg(
+            clap::Arg::new("interactive")
+                .short('i')
+                .long("interactive")
+                .value_name("interactive")
+                .default_value("true")
+                .value_parser(["true", "false"])
+                .help("Determines whether a build inside Docker should be interactive. This can be useful to set to false when running in CI environments."),
+        )
+          .arg(
+            clap::Arg::new("ctx")
+                .action(clap::ArgAction::Append)
+                .short('x')
+                .long("context")
+                .value_name("KEY=VALUE")
+                .help("Adding variable to the context. Any KEY that already exists in the context will be overwriten."),
+          );
+        // Initialize and return a new ServicesCommand instance
+        ServicesCommand {
+            // Initialize fields if any
+            cmd: BBaseCommand {
+                cmd_str: String::from(BCOMMAND),
+                sub_cmd: subcmd,
+                interactive: true,
+                require_docker: true,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    use crate::cli::*;
+    use crate::commands::{BCommand, ServicesCommand};
+    use crate::error::BError;
+    use crate::workspace::{Workspace, WsBuildConfigHandler, WsSettingsHandler};
+
+    fn helper_test_services_subcommand(
+        json_ws_settings: &str,
+        json_build_config: &str,
+        work_dir: &PathBuf,
+        logger: Box<dyn Logger>,
+        system: Box<dyn System>,
+        cmd_line: Vec<&str>,
+    ) -> Result<(), BError> {
+        let settings: WsSettingsHandler =
+            WsSettingsHandler::from_str(work_dir, json_ws_settings, None)?;
+        let config: WsBuildConfigHandler =
+            WsBuildConfigHandler::from_str(json_build_config, &settings)?;
+        let mut workspace: Workspace =
+            Workspace::new(Some(work_dir.to_owned()), Some(settings), Some(config))?;
+        let cli: Cli = Cli::new(logger, system, clap::Command::new("bakery"), Some(cmd_line));
+        let cmd: ServicesCommand = ServicesCommand::new();
+        cmd.execute(&cli, &mut workspace)
+    }
+
+    #[test]
+    fn test_cmd_services_start_prserv() {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            },
+            "docker": {
+                "disabled": "true"
+            },
+            "services": {
+                "prserv": "true",
+                "prservport": "8686"
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {}
+        }
+        "#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let services_dir: String = work_dir.join(".cache/services").display().to_string();
+        let mut mocked_system: MockSystem = MockSystem::new();
+        mocked_system
+            .expect_init_env_file()
+            .returning(|_x, _y| Ok(HashMap::new()));
+        mocked_system
+            .expect_check_call()
+            .with(mockall::predicate::eq(CallParams {
+                cmd_line: vec![
+                    "cd".to_string(),
+                    services_dir.clone(),
+                    "&&".to_string(),
+                    "bitbake-prserv".to_string(),
+                    "--start".to_string(),
+                    "--host".to_string(),
+                    "localhost".to_string(),
+                    "--port".to_string(),
+                    "8686".to_string(),
+                    "--file".to_string(),
+                    work_dir.join(".cache/prserv.sqlite3").display().to_string(),
+                    "--log".to_string(),
+                    format!("{}/prserv.log", services_dir),
+                ],
+                env: HashMap::new(),
+                shell: true,
+            }))
+            .once()
+            .returning(|_x| Ok(()));
+        let result: Result<(), BError> = helper_test_services_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            Box::new(BLogger::new()),
+            Box::new(mocked_system),
+            vec!["bakery", "services", "start", "--config", "default"],
+        );
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,260 @@
+use indexmap::IndexMap;
+use std::path::PathBuf;
+
+use crate::cli::Cli;
+use crate::commands::{BBaseCommand, BCommand};
+use crate::data::WsContextData;
+use crate::error::BError;
+use crate::executers::BBSigDiff;
+use crate::workspace::{Mode, Workspace};
+
+static BCOMMAND: &str = "sigdiff";
+static BCOMMAND_ABOUT: &str =
+    "Compare the last two signatures of a recipe task to explain why it was executed again.";
+pub struct SigDiffCommand {
+    cmd: BBaseCommand,
+    // Your struct fields and methods here
+}
+
+impl BCommand for SigDiffCommand {
+    fn get_config_name(&self, cli: &Cli) -> String {
+        if let Some(sub_matches) = cli.get_args().subcommand_matches(BCOMMAND) {
+            if sub_matches.contains_id("config") {
+                if let Some(value) = sub_matches.get_one::<String>("config") {
+                    return value.clone();
+                }
+            }
+        }
+
+        return String::from("default");
+    }
+
+    fn cmd_str(&self) -> &str {
+        &self.cmd.cmd_str
+    }
+
+    fn subcommand(&self) -> &clap::Command {
+        &self.cmd.sub_cmd
+    }
+
+    fn is_docker_required(&self) -> bool {
+        self.cmd.require_docker
+    }
+
+    fn execute(&self, cli: &Cli, workspace: &mut Workspace) -> Result<(), BError> {
+        let config: String = self.get_arg_str(cli, "config", BCOMMAND)?;
+        let target: String = self.get_arg_str(cli, "target", BCOMMAND)?;
+        let verbose: bool = self.get_arg_flag(cli, "verbose", BCOMMAND)?;
+        let ctx: Vec<String> = self.get_arg_many(cli, "ctx", BCOMMAND)?;
+        let args_context: IndexMap<String, String> = self.setup_context(ctx);
+        let context: WsContextData = WsContextData::new(&args_context)?;
+        let interactive: bool = self.get_arg_bool(cli, "interactive", BCOMMAND)?;
+
+        if !workspace.valid_config(config.as_str()) {
+            return Err(BError::CliError(format!(
+                "Unsupported build config '{}'",
+                config
+            )));
+        }
+
+        if workspace.settings().mode() == Mode::SETUP {
+            return Err(BError::CmdInsideWorkspace(self.cmd.cmd_str.to_string()));
+        }
+
+        /*
+         * If Docker is enabled in the workspace settings, Bakery will be bootstrapped into
+         * a Docker container where all baking operations are performed.
+         */
+        if !workspace.settings().docker_disabled()
+            && self.is_docker_required()
+            && !cli.inside_docker()
+        {
+            return self.bootstrap(&cli.get_cmd_line(), cli, workspace, &vec![], interactive);
+        }
+
+        workspace.update_ctx(&context)?;
+
+        let (recipe, task) = BBSigDiff::recipe_task(&target);
+        let sigdiff: BBSigDiff = BBSigDiff::new(cli, workspace.config().build_data().bitbake());
+        let (signatures, output): (Vec<PathBuf>, String) = sigdiff.diff(&recipe, &task)?;
+        cli.info(format!(
+            "Comparing '{}' with '{}'",
+            signatures[0].display(),
+            signatures[1].display()
+        ));
+        for line in BBSigDiff::summary(&output) {
+            cli.stdout(line);
+        }
+        if verbose {
+            cli.stdout(output);
+        }
+        Ok(())
+    }
+}
+
+impl SigDiffCommand {
+    pub fn new() -> Self {
+        let subcmd: clap::Command = clap::Command::new(BCOMMAND)
+        .about(BCOMMAND_ABOUT)
+        .arg(
+            clap::Arg::new("target")
+                .value_name("recipe[:task]")
+                .required(true)
+                .help("The recipe and task to compare the signatures for. If no task is specified do_build is used."),
+          )
+          .arg(
+            clap::Arg::new("verbose")
+                .action(clap::ArgAction::SetTrue)
+                .long("verbose")
+                .help("Print the full output from bitbake-diffsigs after the summary."),
+          )
+          .arg(
+            clap::Arg::new("config")
+                .short('c')
+                .long("config")
+                .help("The build config used to setup the bitbake environment")
+                .value_name("name")
+                .required(true),
+          )
+          .arg(
+            clap::Arg::new("interactive")
+                .short('i')
+                .long("interactive")
+                .value_name("interactive")
+                .default_value("true")
+                .value_parser(["true", "false"])
+                .help("Determines whether a build inside Docker should be interactive. This can be useful to set to false when running in CI environments."),
+        )
+          .arg(
+            clap::Arg::new("ctx")
+                .action(clap::ArgAction::Append)
+                .short('x')
+                .long("context")
+                .value_name("KEY=VALUE")
+                .help("Adding variable to the context. Any KEY that already exists in the context will be overwriten."),
+          );
+        // Initialize and return a new SigDiffCommand instance
+        SigDiffCommand {
+            // Initialize fields if any
+            cmd: BBaseCommand {
+                cmd_str: String::from(BCOMMAND),
+                sub_cmd: subcmd,
+                interactive: true,
+                require_docker: true,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+    use tempdir::TempDir;
+
+    use crate::cli::*;
+    use crate::commands::{BCommand, SigDiffCommand};
+    use crate::error::BError;
+    use crate::workspace::{Workspace, WsBuildConfigHandler, WsSettingsHandler};
+
+    fn helper_test_sigdiff_subcommand(
+        json_ws_settings: &str,
+        json_build_config: &str,
+        work_dir: &PathBuf,
+        logger: Box<dyn Logger>,
+        system: Box<dyn System>,
+        cmd_line: Vec<&str>,
+    ) -> Result<(), BError> {
+        let settings: WsSettingsHandler =
+            WsSettingsHandler::from_str(work_dir, json_ws_settings, None)?;
+        let config: WsBuildConfigHandler =
+            WsBuildConfigHandler::from_str(json_build_config, &settings)?;
+        let mut workspace: Workspace =
+            Workspace::new(Some(work_dir.to_owned()), Some(settings), Some(config))?;
+        let cli: Cli = Cli::new(logger, system, clap::Command::new("bakery"), Some(cmd_line));
+        let cmd: SigDiffCommand = SigDiffCommand::new();
+        cmd.execute(&cli, &mut workspace)
+    }
+
+    fn helper_signature(path: &PathBuf, age: u64) {
+        std::fs::create_dir_all(path.parent().unwrap()).expect("Failed to create dir");
+        let file: std::fs::File = std::fs::File::create(path).expect("Failed to create signature");
+        file.set_modified(SystemTime::now() - Duration::from_secs(age))
+            .expect("Failed to set modified time");
+    }
+
+    #[test]
+    fn test_cmd_sigdiff() {
+        let json_ws_settings: &str = r#"
+        {
+            "version": "6",
+            "builds": {
+                "supported": [
+                    "default"
+                ]
+            },
+            "docker": {
+                "disabled": "true"
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {}
+        }
+        "#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let build_dir: PathBuf = work_dir.join("builds/default");
+        let stamps: PathBuf = build_dir.join("tmp/stamps/core2-64-poky-linux/busybox");
+        let oldest: PathBuf = stamps.join("1.36.1-r0.do_compile.sigdata.aaaa");
+        let older: PathBuf = work_dir.join(
+            ".cache/test-arch/sstate-cache/ab/cd/sstate:busybox:core2-64-poky-linux:1.36.1:r0:core2-64:11:bbbb_compile.tar.zst.siginfo",
+        );
+        let newest: PathBuf = stamps.join("1.36.1-r0.do_compile.sigdata.cccc");
+        helper_signature(&oldest, 30);
+        helper_signature(&older, 20);
+        helper_signature(&newest, 10);
+        helper_signature(&stamps.join("1.36.1-r0.do_install.sigdata.dddd"), 0);
+        let mut mocked_system: MockSystem = MockSystem::new();
+        mocked_system
+            .expect_init_env_file()
+            .returning(|_x, _y| Ok(HashMap::new()));
+        mocked_system
+            .expect_check_output()
+            .with(mockall::predicate::eq(CallParams {
+                cmd_line: vec![
+                    "cd".to_string(),
+                    build_dir.display().to_string(),
+                    "&&".to_string(),
+                    "bitbake-diffsigs".to_string(),
+                    older.display().to_string(),
+                    newest.display().to_string(),
+                ],
+                env: HashMap::new(),
+                shell: true,
+            }))
+            .once()
+            .returning(|_x| Ok(String::from("basehash changed from bbbb to cccc\n")));
+        let result: Result<(), BError> = helper_test_sigdiff_subcommand(
+            json_ws_settings,
+            json_build_config,
+            &work_dir,
+            Box::new(BLogger::new()),
+            Box::new(mocked_system),
+            vec![
+                "bakery",
+                "sigdiff",
+                "--config",
+                "default",
+                "busybox:compile",
+            ],
+        );
+        assert!(result.is_ok());
+    }
+}
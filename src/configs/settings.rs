@@ -31,6 +31,27 @@ pub struct WsSettings {
     pub docker_disabled: String,
     pub docker_top_dir: String,
     pub docker_work_dir: String,
+    pub docker_hermetic: String,
+    pub cache_sstate_mirrors: Vec<String>,
+    pub cache_premirrors: Vec<String>,
+    pub cache_hashserve: String,
+    pub cache_signature_handler: String,
+    pub cache_arch_layout: String,
+    pub services_hashserv: String,
+    pub services_hashserv_bind: String,
+    pub services_prserv: String,
+    pub services_prserv_port: String,
+    pub logs_capture: String,
+    pub artifacts_layout: String,
+    /*
+     * The layout before the context is expanded, used to tell which
+     * dirs of the layout are versioned
+     */
+    pub artifacts_layout_unexpanded: String,
+    pub artifacts_latest: String,
+    pub artifacts_keep: String,
+    pub artifacts_max_age: String,
+    pub artifacts_checksums: String,
 }
 
 impl Config for WsSettings {}
@@ -56,6 +77,22 @@ impl WsSettings {
         let mut docker_disabled: String = String::from("false");
         let mut docker_top_dir: String = String::from("");
         let mut docker_work_dir: String = String::from("");
+        let mut docker_hermetic: String = String::from("false");
+        let mut cache_sstate_mirrors: Vec<String> = vec![];
+        let mut cache_premirrors: Vec<String> = vec![];
+        let mut cache_hashserve: String = String::from("");
+        let mut cache_signature_handler: String = String::from("");
+        let mut cache_arch_layout: String = String::from("false");
+        let mut services_hashserv: String = String::from("false");
+        let mut services_hashserv_bind: String = String::from("");
+        let mut services_prserv: String = String::from("false");
+        let mut services_prserv_port: String = String::from("8585");
+        let mut logs_capture: String = String::from("true");
+        let mut artifacts_layout: String = String::from("");
+        let mut artifacts_latest: String = String::from("true");
+        let mut artifacts_keep: String = String::from("0");
+        let mut artifacts_max_age: String = String::from("0");
+        let mut artifacts_checksums: String = String::from("false");
 
         match Self::get_value("workspace", &data) {
             Ok(ws_data) => {
@@ -136,10 +173,74 @@ impl WsSettings {
                     Self::get_str_value("topdir", docker_data, Some(String::from("")))?;
                 docker_work_dir =
                     Self::get_str_value("workdir", docker_data, Some(String::from("")))?;
+                docker_hermetic =
+                    Self::get_str_value("hermetic", docker_data, Some(String::from("false")))?;
             }
             Err(_err) => {}
         }
 
+        match Self::get_value("cache", &data) {
+            Ok(cache_data) => {
+                cache_sstate_mirrors =
+                    Self::get_array_value("sstatemirrors", cache_data, Some(vec![]))?;
+                cache_premirrors = Self::get_array_value("premirrors", cache_data, Some(vec![]))?;
+                cache_hashserve =
+                    Self::get_str_value("hashserve", cache_data, Some(String::from("")))?;
+                cache_signature_handler =
+                    Self::get_str_value("signaturehandler", cache_data, Some(String::from("")))?;
+                cache_arch_layout =
+                    Self::get_str_value("archlayout", cache_data, Some(String::from("false")))?;
+            }
+            Err(_err) => {}
+        }
+
+        match Self::get_value("services", &data) {
+            Ok(services_data) => {
+                services_hashserv =
+                    Self::get_str_value("hashserv", services_data, Some(String::from("false")))?;
+                services_hashserv_bind =
+                    Self::get_str_value("hashservbind", services_data, Some(String::from("")))?;
+                services_prserv =
+                    Self::get_str_value("prserv", services_data, Some(String::from("false")))?;
+                services_prserv_port =
+                    Self::get_str_value("prservport", services_data, Some(String::from("8585")))?;
+            }
+            Err(_err) => {}
+        }
+
+        match Self::get_value("logs", &data) {
+            Ok(logs_data) => {
+                logs_capture =
+                    Self::get_str_value("capture", logs_data, Some(String::from("true")))?;
+            }
+            Err(_err) => {}
+        }
+
+        match Self::get_value("artifacts", &data) {
+            Ok(artifacts_data) => {
+                artifacts_layout =
+                    Self::get_str_value("layout", artifacts_data, Some(String::from("")))?;
+                artifacts_latest =
+                    Self::get_str_value("latest", artifacts_data, Some(String::from("true")))?;
+                artifacts_keep =
+                    Self::get_str_value("keep", artifacts_data, Some(String::from("0")))?;
+                artifacts_max_age =
+                    Self::get_str_value("maxage", artifacts_data, Some(String::from("0")))?;
+                artifacts_checksums =
+                    Self::get_str_value("checksums", artifacts_data, Some(String::from("false")))?;
+            }
+            Err(_err) => {}
+        }
+
+        for (key, value) in [("keep", &artifacts_keep), ("maxage", &artifacts_max_age)] {
+            if value.parse::<u64>().is_err() {
+                return Err(BError::WsError(format!(
+                    "Invalid artifacts {} '{}', expecting a positive number",
+                    key, value
+                )));
+            }
+        }
+
         Ok(WsSettings {
             version,
             mode,
@@ -159,6 +260,23 @@ impl WsSettings {
             docker_disabled,
             docker_top_dir,
             docker_work_dir,
+            docker_hermetic,
+            cache_sstate_mirrors,
+            cache_premirrors,
+            cache_hashserve,
+            cache_signature_handler,
+            cache_arch_layout,
+            services_hashserv,
+            services_hashserv_bind,
+            services_prserv,
+            services_prserv_port,
+            logs_capture,
+            artifacts_layout_unexpanded: artifacts_layout.clone(),
+            artifacts_layout,
+            artifacts_latest,
+            artifacts_keep,
+            artifacts_max_age,
+            artifacts_checksums,
         })
     }
 
@@ -175,6 +293,16 @@ impl WsSettings {
         self.docker_registry = ctx.expand_str(&self.docker_registry)?;
         self.docker_top_dir = ctx.expand_str(&self.docker_top_dir)?;
         self.docker_work_dir = ctx.expand_str(&self.docker_work_dir)?;
+        for mirror in self.cache_sstate_mirrors.iter_mut() {
+            *mirror = ctx.expand_str(mirror)?;
+        }
+        for mirror in self.cache_premirrors.iter_mut() {
+            *mirror = ctx.expand_str(mirror)?;
+        }
+        self.cache_hashserve = ctx.expand_str(&self.cache_hashserve)?;
+        self.cache_signature_handler = ctx.expand_str(&self.cache_signature_handler)?;
+        self.services_hashserv_bind = ctx.expand_str(&self.services_hashserv_bind)?;
+        self.artifacts_layout = ctx.expand_str(&self.artifacts_layout)?;
         Ok(())
     }
 
@@ -191,6 +319,23 @@ impl WsSettings {
         merge_field!(self, data, docker_registry);
         merge_field!(self, data, docker_top_dir);
         merge_field!(self, data, docker_work_dir);
+        merge_field!(self, data, docker_hermetic);
+        merge_field!(self, data, cache_sstate_mirrors);
+        merge_field!(self, data, cache_premirrors);
+        merge_field!(self, data, cache_hashserve);
+        merge_field!(self, data, cache_signature_handler);
+        merge_field!(self, data, cache_arch_layout);
+        merge_field!(self, data, services_hashserv);
+        merge_field!(self, data, services_hashserv_bind);
+        merge_field!(self, data, services_prserv);
+        merge_field!(self, data, services_prserv_port);
+        merge_field!(self, data, logs_capture);
+        merge_field!(self, data, artifacts_layout);
+        merge_field!(self, data, artifacts_layout_unexpanded);
+        merge_field!(self, data, artifacts_latest);
+        merge_field!(self, data, artifacts_keep);
+        merge_field!(self, data, artifacts_max_age);
+        merge_field!(self, data, artifacts_checksums);
         self.docker_args
             .extend(std::mem::take(&mut data.docker_args));
     }
@@ -200,6 +345,7 @@ impl WsSettings {
 mod tests {
     use crate::configs::{Context, WsSettings};
     use crate::constants::BkryConstants;
+    use crate::error::BError;
     use crate::helper::Helper;
     use indexmap::{indexmap, IndexMap};
 
@@ -231,6 +377,130 @@ mod tests {
         assert_eq!(&settings.cache_dir, "cache_test");
     }
 
+    #[test]
+    fn test_settings_config_cache() {
+        let json_test_str = r#"
+        {
+            "version": "6",
+            "cache": {
+              "sstatemirrors": [
+                "file:///nfs/sstate-cache",
+                "https://sstate.example.com/sstate-cache"
+              ],
+              "premirrors": [
+                "file:///nfs/downloads"
+              ],
+              "hashserve": "ws://hashserv.example.com:8686",
+              "signaturehandler": "OEEquivHash",
+              "archlayout": "true"
+            }
+        }"#;
+        let settings = Helper::setup_ws_settings(json_test_str);
+        assert_eq!(
+            settings.cache_sstate_mirrors,
+            vec![
+                String::from("file:///nfs/sstate-cache"),
+                String::from("https://sstate.example.com/sstate-cache")
+            ]
+        );
+        assert_eq!(
+            settings.cache_premirrors,
+            vec![String::from("file:///nfs/downloads")]
+        );
+        assert_eq!(&settings.cache_hashserve, "ws://hashserv.example.com:8686");
+        assert_eq!(&settings.cache_signature_handler, "OEEquivHash");
+        assert_eq!(&settings.cache_arch_layout, "true");
+    }
+
+    #[test]
+    fn test_settings_config_services() {
+        let json_test_str = r#"
+        {
+            "version": "6",
+            "services": {
+              "hashserv": "true",
+              "hashservbind": "localhost:8686",
+              "prserv": "true",
+              "prservport": "8586"
+            }
+        }"#;
+        let settings = Helper::setup_ws_settings(json_test_str);
+        assert_eq!(&settings.services_hashserv, "true");
+        assert_eq!(&settings.services_hashserv_bind, "localhost:8686");
+        assert_eq!(&settings.services_prserv, "true");
+        assert_eq!(&settings.services_prserv_port, "8586");
+    }
+
+    #[test]
+    fn test_settings_config_logs() {
+        let json_test_str = r#"
+        {
+            "version": "6",
+            "logs": {
+              "capture": "false"
+            }
+        }"#;
+        let settings = Helper::setup_ws_settings(json_test_str);
+        assert_eq!(&settings.logs_capture, "false");
+    }
+
+    #[test]
+    fn test_settings_config_logs_default() {
+        let json_test_str = r#"
+        {
+            "version": "6"
+        }"#;
+        let settings = Helper::setup_ws_settings(json_test_str);
+        assert_eq!(&settings.logs_capture, "true");
+    }
+
+    #[test]
+    fn test_settings_config_artifacts() {
+        let json_test_str = r#"
+        {
+            "version": "6",
+            "artifacts": {
+              "layout": "$#[BKRY_NAME]/$#[BKRY_PLATFORM_RELEASE]",
+              "latest": "false",
+              "keep": "5",
+              "maxage": "30",
+              "checksums": "true"
+            }
+        }"#;
+        let settings = Helper::setup_ws_settings(json_test_str);
+        assert_eq!(
+            &settings.artifacts_layout,
+            "$#[BKRY_NAME]/$#[BKRY_PLATFORM_RELEASE]"
+        );
+        assert_eq!(&settings.artifacts_latest, "false");
+        assert_eq!(&settings.artifacts_keep, "5");
+        assert_eq!(&settings.artifacts_max_age, "30");
+        assert_eq!(&settings.artifacts_checksums, "true");
+    }
+
+    #[test]
+    fn test_settings_config_artifacts_invalid_keep() {
+        let json_test_str = r#"
+        {
+            "version": "6",
+            "artifacts": {
+              "keep": "five"
+            }
+        }"#;
+        let result: Result<WsSettings, BError> = WsSettings::from_str(json_test_str);
+        match result {
+            Ok(_settings) => {
+                panic!("Expected an error for an invalid keep value");
+            }
+            Err(e) => {
+                assert_eq!(
+                    e.to_string(),
+                    "Invalid artifacts keep 'five', expecting a positive number"
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_settings_config_default_workspace_dirs() {
         let json_test_str = r#"
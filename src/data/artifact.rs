@@ -1,4 +1,6 @@
+use indexmap::IndexMap;
 use serde_json::Value;
+use std::fmt;
 
 use crate::configs::Config;
 use crate::configs::Context;
@@ -12,6 +14,32 @@ pub enum AType {
     Manifest,
     Link,
     Conditional,
+    Cve,
+    Sbom,
+    LicenseBundle,
+    Template,
+    Extract,
+    DiskImage,
+}
+
+impl fmt::Display for AType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ttype: &str = match self {
+            AType::File => "file",
+            AType::Directory => "directory",
+            AType::Archive => "archive",
+            AType::Manifest => "manifest",
+            AType::Link => "link",
+            AType::Conditional => "conditional",
+            AType::Cve => "cve",
+            AType::Sbom => "sbom",
+            AType::LicenseBundle => "license-bundle",
+            AType::Template => "template",
+            AType::Extract => "extract",
+            AType::DiskImage => "disk-image",
+        };
+        write!(f, "{}", ttype)
+    }
 }
 
 // TODO: we should consider using IndexSet instead of vector to make sure we
@@ -23,6 +51,22 @@ pub struct WsArtifactData {
     pub dest: String, // The dest is optional
     pub manifest: String, // The manifest content will be a json string that can be put in a file. The manifest can then be used by the CI to collect information from the build
     pub condition: String, // The condition is only used if the type is conditional
+    pub level: String, // The compression level is only used if the type is archive or license-bundle
+    pub reproducible: String, // Only used if the type is archive or license-bundle
+    pub source_date_epoch: String, // The mtime used for all files in a reproducible archive
+    pub mode: String, // The mode is only used if the type is file and is defining how the files are collected
+    pub exclude: Vec<String>, // Glob patterns for files to exclude, only used if the type is file
+    pub optional: String, // If true it is not an error if no file is matching the source
+    pub min: String,  // The minimum number of files the source must match
+    pub max: String,  // The maximum number of files the source can match
+    pub rename: Vec<(String, String)>, // Regex rules used to rename the collected files
+    pub content: Vec<String>, // The inline lines of a template, only used if the type is template
+    pub context: IndexMap<String, String>, // The context used to expand a template source file or included in a manifest
+    pub include_collected: String, // If true the collected artifacts and the context are included in the manifest
+    pub include: Vec<String>, // Glob patterns for the paths to extract, only used if the type is extract
+    pub strip_components: String, // The number of leading path components to remove when extracting
+    pub compression: String, // The compression of the copied image, only used if the type is disk-image
+    pub summary: String, // The name of the file the summary is written to, only used if the type is cve
 }
 
 impl Config for WsArtifactData {}
@@ -40,10 +84,62 @@ impl WsArtifactData {
     pub fn new(data: &Value) -> Result<Self, BError> {
         let ttype: String = Self::get_str_value("type", &data, Some(String::from("file")))?;
         let name: String = Self::get_str_value("name", &data, Some(String::from("")))?;
-        let source: String = Self::get_str_value("source", &data, Some(String::from("")))?;
+        let mut source: String = Self::get_str_value("source", &data, Some(String::from("")))?;
         let dest: String = Self::get_str_value("dest", &data, Some(String::from("")))?;
-        let manifest: String = Self::get_str_manifest("content", &data, Some(String::from("{}")))?;
+        let manifest: String;
+        let mut content: Vec<String> = vec![];
+        /*
+         * The content of a template is a list of lines while the content
+         * of a manifest is a json object
+         */
+        if ttype == "template" {
+            manifest = String::from("{}");
+            content = Self::get_array_value("content", &data, Some(vec![]))?;
+        } else {
+            manifest = Self::get_str_manifest("content", &data, Some(String::from("{}")))?;
+        }
         let condition: String = Self::get_str_value("condition", &data, Some(String::from("")))?;
+        let level: String = Self::get_str_value("level", &data, Some(String::from("")))?;
+        let reproducible: String =
+            Self::get_str_value("reproducible", &data, Some(String::from("")))?;
+        let source_date_epoch: String =
+            Self::get_str_value("source_date_epoch", &data, Some(String::from("")))?;
+        let mode: String = Self::get_str_value("mode", &data, Some(String::from("copy")))?;
+        let exclude: Vec<String> = Self::get_array_value("exclude", &data, Some(vec![]))?;
+        /*
+         * The optional and include_collected attributes can be either a json
+         * boolean or a string so that they can be set using a context variable
+         */
+        let optional: String = match data.get("optional") {
+            Some(Value::Bool(b)) => b.to_string(),
+            _ => Self::get_str_value("optional", &data, Some(String::from("")))?,
+        };
+        let min: String = Self::get_str_value("min", &data, Some(String::from("")))?;
+        let max: String = Self::get_str_value("max", &data, Some(String::from("")))?;
+        let include_collected: String = match data.get("include_collected") {
+            Some(Value::Bool(b)) => b.to_string(),
+            _ => Self::get_str_value("include_collected", &data, Some(String::from("")))?,
+        };
+        let include: Vec<String> = Self::get_array_value("include", &data, Some(vec![]))?;
+        let strip_components: String =
+            Self::get_str_value("strip_components", &data, Some(String::from("")))?;
+        let compression: String =
+            Self::get_str_value("compression", &data, Some(String::from("")))?;
+        let summary: String = Self::get_str_value("summary", &data, Some(String::from("")))?;
+        let mut rename: Vec<(String, String)> = vec![];
+        if let Some(rules) = data.get("rename") {
+            for rule in rules.as_array().unwrap_or(&vec![]) {
+                let pattern: String = Self::get_str_value("pattern", rule, None)?;
+                let replace: String = Self::get_str_value("replace", rule, Some(String::from("")))?;
+                if let Err(err) = regex::Regex::new(&pattern) {
+                    return Err(BError::ParseArtifactsError(format!(
+                        "Invalid rename pattern '{}', {}",
+                        pattern, err
+                    )));
+                }
+                rename.push((pattern, replace));
+            }
+        }
 
         if ttype != "file"
             && ttype != "directory"
@@ -51,12 +147,24 @@ impl WsArtifactData {
             && ttype != "manifest"
             && ttype != "link"
             && ttype != "conditional"
+            && ttype != "cve"
+            && ttype != "sbom"
+            && ttype != "license-bundle"
+            && ttype != "template"
+            && ttype != "extract"
+            && ttype != "disk-image"
         {
             return Err(BError::ParseArtifactsError(format!(
                 "Invalid type '{}'",
                 ttype
             )));
         }
+        if mode != "copy" && mode != "hardlink" && mode != "reflink" && mode != "preserve" {
+            return Err(BError::ParseArtifactsError(format!(
+                "Invalid mode '{}'",
+                mode
+            )));
+        }
         if ttype == "file" && source.is_empty() {
             return Err(BError::ParseArtifactsError(format!(
                 "The 'file' type requires a 'source'"
@@ -82,12 +190,52 @@ impl WsArtifactData {
                 "The 'link' type requires a 'name' and 'source'"
             )));
         }
+        if ttype == "license-bundle" && name.is_empty() {
+            return Err(BError::ParseArtifactsError(format!(
+                "The 'license-bundle' type requires a 'name'"
+            )));
+        }
+        if ttype == "extract" && source.is_empty() {
+            return Err(BError::ParseArtifactsError(format!(
+                "The 'extract' type requires a 'source'"
+            )));
+        }
+        if ttype == "disk-image" && source.is_empty() {
+            return Err(BError::ParseArtifactsError(format!(
+                "The 'disk-image' type requires a 'source'"
+            )));
+        }
+        if ttype == "template" && name.is_empty() {
+            return Err(BError::ParseArtifactsError(format!(
+                "The 'template' type requires a 'name'"
+            )));
+        }
+        if ttype == "template" && source.is_empty() == content.is_empty() {
+            return Err(BError::ParseArtifactsError(format!(
+                "The 'template' type requires either a 'source' or 'content'"
+            )));
+        }
         if ttype == "conditional" && condition.is_empty() {
             return Err(BError::ParseArtifactsError(format!(
                 "The 'conditional' type requires a 'condition'"
             )));
         }
 
+        /*
+         * The cve-check and create-spdx classes are writing the reports for
+         * an image next to the image in the deploy dir so if no source is
+         * specified the reports are collected from there.
+         */
+        if ttype == "cve" && source.is_empty() {
+            source = String::from("$#[BKRY_BB_DEPLOY_DIR]/images/*/*.json");
+        }
+        if ttype == "sbom" && source.is_empty() {
+            source = String::from("$#[BKRY_BB_DEPLOY_DIR]/images/*/*.spdx*");
+        }
+        if ttype == "license-bundle" && source.is_empty() {
+            source = String::from("$#[BKRY_BB_DEPLOY_DIR]");
+        }
+
         let enum_ttype: AType;
         match ttype.as_str() {
             "file" => {
@@ -108,6 +256,24 @@ impl WsArtifactData {
             "conditional" => {
                 enum_ttype = AType::Conditional;
             }
+            "cve" => {
+                enum_ttype = AType::Cve;
+            }
+            "sbom" => {
+                enum_ttype = AType::Sbom;
+            }
+            "license-bundle" => {
+                enum_ttype = AType::LicenseBundle;
+            }
+            "template" => {
+                enum_ttype = AType::Template;
+            }
+            "extract" => {
+                enum_ttype = AType::Extract;
+            }
+            "disk-image" => {
+                enum_ttype = AType::DiskImage;
+            }
             _ => {
                 return Err(BError::ParseArtifactsError(format!(
                     "Invalid type '{}'",
@@ -126,6 +292,22 @@ impl WsArtifactData {
             dest,
             manifest,
             condition,
+            level,
+            reproducible,
+            source_date_epoch,
+            mode,
+            exclude,
+            optional,
+            min,
+            max,
+            rename,
+            content,
+            context: IndexMap::new(),
+            include_collected,
+            include,
+            strip_components,
+            compression,
+            summary,
         })
     }
 
@@ -135,16 +317,30 @@ impl WsArtifactData {
                 self.name = ctx.expand_str(&self.name)?;
                 self.source = ctx.expand_str(&self.source)?;
                 self.dest = ctx.expand_str(&self.dest)?;
+                self.optional = ctx.expand_str(&self.optional)?;
+                self.min = ctx.expand_str(&self.min)?;
+                self.max = ctx.expand_str(&self.max)?;
+                for e in self.exclude.iter_mut() {
+                    *e = ctx.expand_str(e)?;
+                }
+                for (_pattern, replace) in self.rename.iter_mut() {
+                    *replace = ctx.expand_str(replace)?;
+                }
             }
             AType::Directory => {
                 self.name = ctx.expand_str(&self.name)?;
             }
             AType::Archive => {
                 self.name = ctx.expand_str(&self.name)?;
+                self.level = ctx.expand_str(&self.level)?;
+                self.reproducible = ctx.expand_str(&self.reproducible)?;
+                self.source_date_epoch = ctx.expand_str(&self.source_date_epoch)?;
             }
             AType::Manifest => {
                 self.name = ctx.expand_str(&self.name)?;
                 self.manifest = ctx.expand_str(&self.manifest)?;
+                self.include_collected = ctx.expand_str(&self.include_collected)?;
+                self.context = ctx.variables().clone();
             }
             AType::Link => {
                 self.name = ctx.expand_str(&self.name)?;
@@ -153,6 +349,45 @@ impl WsArtifactData {
             AType::Conditional => {
                 self.condition = ctx.expand_str(&self.condition)?;
             }
+            AType::Cve | AType::Sbom | AType::LicenseBundle => {
+                self.name = ctx.expand_str(&self.name)?;
+                self.summary = ctx.expand_str(&self.summary)?;
+                self.source = ctx.expand_str(&self.source)?;
+                self.dest = ctx.expand_str(&self.dest)?;
+                self.level = ctx.expand_str(&self.level)?;
+                self.reproducible = ctx.expand_str(&self.reproducible)?;
+                self.source_date_epoch = ctx.expand_str(&self.source_date_epoch)?;
+            }
+            AType::Template => {
+                self.name = ctx.expand_str(&self.name)?;
+                self.source = ctx.expand_str(&self.source)?;
+                self.dest = ctx.expand_str(&self.dest)?;
+                for line in self.content.iter_mut() {
+                    *line = ctx.expand_str(line)?;
+                }
+                /*
+                 * The template source file is not read until the artifacts
+                 * are collected so the context is kept to expand it then
+                 */
+                self.context = ctx.variables().clone();
+            }
+            AType::Extract => {
+                self.source = ctx.expand_str(&self.source)?;
+                self.dest = ctx.expand_str(&self.dest)?;
+                self.strip_components = ctx.expand_str(&self.strip_components)?;
+                for i in self.include.iter_mut() {
+                    *i = ctx.expand_str(i)?;
+                }
+                for e in self.exclude.iter_mut() {
+                    *e = ctx.expand_str(e)?;
+                }
+            }
+            AType::DiskImage => {
+                self.source = ctx.expand_str(&self.source)?;
+                self.dest = ctx.expand_str(&self.dest)?;
+                self.compression = ctx.expand_str(&self.compression)?;
+                self.level = ctx.expand_str(&self.level)?;
+            }
             _ => {
                 panic!(
                     "Invalid 'artifact' format in build config. Invalid type '{:?}'",
@@ -179,10 +414,119 @@ impl WsArtifactData {
         &self.dest
     }
 
+    pub fn exclude(&self) -> &Vec<String> {
+        &self.exclude
+    }
+
+    pub fn optional(&self) -> bool {
+        match self.optional.as_str() {
+            "1" | "yes" | "y" | "Y" | "true" | "YES" | "TRUE" | "True" | "Yes" => return true,
+            _ => false,
+        }
+    }
+
+    fn count(name: &str, value: &str) -> Result<Option<usize>, BError> {
+        if value.is_empty() {
+            return Ok(None);
+        }
+
+        match value.parse::<usize>() {
+            Ok(count) => Ok(Some(count)),
+            Err(_err) => Err(BError::ValueError(format!(
+                "Invalid '{}' value '{}'",
+                name, value
+            ))),
+        }
+    }
+
+    pub fn min(&self) -> Result<Option<usize>, BError> {
+        Self::count("min", &self.min)
+    }
+
+    pub fn max(&self) -> Result<Option<usize>, BError> {
+        Self::count("max", &self.max)
+    }
+
+    pub fn compression(&self) -> &str {
+        &self.compression
+    }
+
+    pub fn summary(&self) -> &str {
+        &self.summary
+    }
+
+    pub fn include(&self) -> &Vec<String> {
+        &self.include
+    }
+
+    pub fn strip_components(&self) -> Result<usize, BError> {
+        Ok(Self::count("strip_components", &self.strip_components)?.unwrap_or(0))
+    }
+
+    pub fn rename(&self) -> &Vec<(String, String)> {
+        &self.rename
+    }
+
+    pub fn mode(&self) -> &str {
+        &self.mode
+    }
+
+    pub fn content(&self) -> &Vec<String> {
+        &self.content
+    }
+
+    pub fn context(&self) -> &IndexMap<String, String> {
+        &self.context
+    }
+
+    pub fn include_collected(&self) -> bool {
+        match self.include_collected.as_str() {
+            "1" | "yes" | "y" | "Y" | "true" | "YES" | "TRUE" | "True" | "Yes" => return true,
+            _ => false,
+        }
+    }
+
     pub fn manifest(&self) -> &str {
         &self.manifest
     }
 
+    pub fn level(&self) -> Result<Option<u32>, BError> {
+        if self.level.is_empty() {
+            return Ok(None);
+        }
+
+        match self.level.parse::<u32>() {
+            Ok(level) => Ok(Some(level)),
+            Err(_err) => Err(BError::ValueError(format!(
+                "Invalid compression level '{}'",
+                self.level
+            ))),
+        }
+    }
+
+    /*
+     * A reproducible archive is using the source date epoch as mtime for
+     * all the files. If no source date epoch is specified 0 is used.
+     */
+    pub fn reproducible(&self) -> Result<Option<u64>, BError> {
+        match self.reproducible.as_str() {
+            "1" | "yes" | "y" | "Y" | "true" | "YES" | "TRUE" | "True" | "Yes" => {}
+            _ => return Ok(None),
+        }
+
+        if self.source_date_epoch.is_empty() {
+            return Ok(Some(0));
+        }
+
+        match self.source_date_epoch.parse::<u64>() {
+            Ok(epoch) => Ok(Some(epoch)),
+            Err(_err) => Err(BError::ValueError(format!(
+                "Invalid source date epoch '{}'",
+                self.source_date_epoch
+            ))),
+        }
+    }
+
     pub fn condition(&self) -> bool {
         match self.condition.as_str() {
             "1" | "yes" | "y" | "Y" | "true" | "YES" | "TRUE" | "True" | "Yes" => return true,
@@ -595,4 +939,173 @@ mod tests {
         assert_eq!(data.atype(), &AType::Conditional);
         assert!(!data.condition());
     }
+
+    #[test]
+    fn test_ws_artifact_data_cve_sbom_default_source() {
+        let ctx_variables: IndexMap<String, String> = indexmap! {
+            "BKRY_BB_DEPLOY_DIR".to_string() => "/builds/default/tmp/deploy".to_string()
+        };
+        let context: Context = Context::new(&ctx_variables);
+        let value: Value =
+            Helper::parse(r#"{ "type": "cve" }"#).expect("Failed to parse artifact config");
+        let mut data: WsArtifactData =
+            WsArtifactData::new(&value).expect("Failed to parse artifact data");
+        data.expand_ctx(&context).unwrap();
+        assert_eq!(data.atype(), &AType::Cve);
+        assert_eq!(data.source(), "/builds/default/tmp/deploy/images/*/*.json");
+        let value: Value = Helper::parse(r#"{ "type": "sbom", "source": "spdx/*" }"#)
+            .expect("Failed to parse artifact config");
+        let data: WsArtifactData =
+            WsArtifactData::new(&value).expect("Failed to parse artifact data");
+        assert_eq!(data.atype(), &AType::Sbom);
+        assert_eq!(data.source(), "spdx/*");
+    }
+
+    #[test]
+    fn test_ws_artifact_data_reproducible() {
+        let ctx_variables: IndexMap<String, String> = indexmap! {
+            "SOURCE_DATE_EPOCH".to_string() => "1700000000".to_string()
+        };
+        let context: Context = Context::new(&ctx_variables);
+        let json_artifact_config: &str = r#"
+        {
+            "type": "archive",
+            "name": "test.tar.gz",
+            "reproducible": "true",
+            "source_date_epoch": "$#[SOURCE_DATE_EPOCH]"
+        }
+        "#;
+        let value: Value =
+            Helper::parse(json_artifact_config).expect("Failed to parse artifact config");
+        let mut data: WsArtifactData =
+            WsArtifactData::new(&value).expect("Failed to parse artifact data");
+        data.expand_ctx(&context).unwrap();
+        assert_eq!(data.reproducible().unwrap(), Some(1700000000));
+        let value: Value = Helper::parse(
+            r#"{ "type": "archive", "name": "test.tar.gz", "reproducible": "true" }"#,
+        )
+        .expect("Failed to parse artifact config");
+        let data: WsArtifactData =
+            WsArtifactData::new(&value).expect("Failed to parse artifact data");
+        assert_eq!(data.reproducible().unwrap(), Some(0));
+        let value: Value = Helper::parse(r#"{ "type": "archive", "name": "test.tar.gz" }"#)
+            .expect("Failed to parse artifact config");
+        let data: WsArtifactData =
+            WsArtifactData::new(&value).expect("Failed to parse artifact data");
+        assert_eq!(data.reproducible().unwrap(), None);
+    }
+
+    #[test]
+    fn test_ws_artifact_data_mode() {
+        let value: Value =
+            Helper::parse(r#"{ "source": "file1.txt" }"#).expect("Failed to parse artifact config");
+        let data: WsArtifactData =
+            WsArtifactData::new(&value).expect("Failed to parse artifact data");
+        assert_eq!(data.mode(), "copy");
+        let value: Value = Helper::parse(r#"{ "source": "file1.txt", "mode": "hardlink" }"#)
+            .expect("Failed to parse artifact config");
+        let data: WsArtifactData =
+            WsArtifactData::new(&value).expect("Failed to parse artifact data");
+        assert_eq!(data.mode(), "hardlink");
+        let value: Value = Helper::parse(r#"{ "source": "file1.txt", "mode": "move" }"#)
+            .expect("Failed to parse artifact config");
+        let result: Result<WsArtifactData, BError> = WsArtifactData::new(&value);
+        match result {
+            Ok(_data) => {
+                panic!("We should have recived an error because the mode is invalid!");
+            }
+            Err(e) => {
+                assert_eq!(
+                    e.to_string(),
+                    String::from("Invalid 'artifact' node in build config. Invalid mode 'move'")
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_ws_artifact_data_file_selection() {
+        let json_artifact_config: &str = r#"
+        {
+            "source": "images/**/*.dtb",
+            "exclude": [ "*-test.dtb" ],
+            "optional": "true",
+            "min": "1",
+            "max": "10",
+            "rename": [
+                {
+                    "pattern": "-[0-9]{14}",
+                    "replace": ""
+                }
+            ]
+        }
+        "#;
+        let value: Value =
+            Helper::parse(json_artifact_config).expect("Failed to parse artifact config");
+        let data: WsArtifactData =
+            WsArtifactData::new(&value).expect("Failed to parse artifact data");
+        assert_eq!(data.exclude(), &vec![String::from("*-test.dtb")]);
+        assert!(data.optional());
+        assert_eq!(data.min().unwrap(), Some(1));
+        assert_eq!(data.max().unwrap(), Some(10));
+        assert_eq!(
+            data.rename(),
+            &vec![(String::from("-[0-9]{14}"), String::from(""))]
+        );
+        let value: Value = Helper::parse(r#"{ "source": "file.txt", "min": "one" }"#)
+            .expect("Failed to parse artifact config");
+        let data: WsArtifactData =
+            WsArtifactData::new(&value).expect("Failed to parse artifact data");
+        assert_eq!(
+            data.min().unwrap_err().to_string(),
+            "Invalid 'min' value 'one'"
+        );
+        let value: Value = Helper::parse(
+            r#"{ "source": "file.txt", "rename": [ { "pattern": "(", "replace": "" } ] }"#,
+        )
+        .expect("Failed to parse artifact config");
+        assert!(WsArtifactData::new(&value).is_err());
+    }
+
+    #[test]
+    fn test_ws_artifact_data_template() {
+        let json_artifact_config: &str = r#"
+        {
+            "type": "template",
+            "name": "$#[IMAGE]-build-info",
+            "content": [
+                "ID=$#[IMAGE]"
+            ]
+        }
+        "#;
+        let value: Value =
+            Helper::parse(json_artifact_config).expect("Failed to parse artifact config");
+        let mut data: WsArtifactData =
+            WsArtifactData::new(&value).expect("Failed to parse artifact config");
+        let variables: IndexMap<String, String> = indexmap! {
+            "IMAGE".to_string() => "core-image-minimal".to_string(),
+        };
+        data.expand_ctx(&Context::new(&variables)).unwrap();
+        assert_eq!(data.atype(), &AType::Template);
+        assert_eq!(data.name(), "core-image-minimal-build-info");
+        assert_eq!(data.content(), &vec![String::from("ID=core-image-minimal")]);
+        assert_eq!(data.context().get("image").unwrap(), "core-image-minimal");
+        let json_artifact_config: &str = r#"
+        {
+            "type": "template",
+            "name": "build-info"
+        }
+        "#;
+        let value: Value =
+            Helper::parse(json_artifact_config).expect("Failed to parse artifact config");
+        let result: Result<WsArtifactData, BError> = WsArtifactData::new(&value);
+        match result {
+            Ok(_data) => {
+                panic!("We should have recived an error because there is no source or content!");
+            }
+            Err(e) => {
+                assert_eq!(e.to_string(), String::from("Invalid 'artifact' node in build config. The 'template' type requires either a 'source' or 'content'"));
+            }
+        }
+    }
 }
@@ -1,5 +1,6 @@
+use indexmap::IndexMap;
 use serde_json::Value;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 
 use crate::configs::Config;
 use crate::configs::Context;
@@ -17,6 +18,13 @@ pub struct WsBitbakeData {
     bblayers_conf: Vec<String>, // Optional but if there is a task with type bitbake defined it will fail without a bblayers.conf
     local_conf: Vec<String>, // Optional but if there is a task with type bitbake defined it will fail without a local.conf
     init_env: String, // Optional but if not set the default oe-init-build-env file will be used
+    site_conf: Vec<String>, // Optional if set a site.conf will be generated in the build conf dir
+    auto_conf: Vec<String>, // Optional if set an auto.conf will be generated in the build conf dir
+    localconf_files: Vec<String>, // Optional list of workspace relative files that will be required by the local.conf
+    multiconfig: IndexMap<String, Vec<String>>, // Optional if set a conf/multiconfig/<name>.conf is generated for each entry
+    local_conf_user: String, // Optional developer owned conf included by the local.conf. Default is conf/local.conf.user in the build dir
+    build_dir: String, // Optional template for the build dir relative to the builds dir. Default is the product name
+    tmp_dir: String, // Optional if set TMPDIR will be set in the local.conf. Default is an empty string keeping TMPDIR in the build dir
     settings: WsSettingsHandler,
 }
 
@@ -58,6 +66,19 @@ impl WsBitbakeData {
             bb_data,
             Some(String::from("layers/poky/oe-init-build-env")),
         )?;
+        let site_conf: Vec<String> = Self::get_array_value("siteconf", bb_data, Some(vec![]))?;
+        let auto_conf: Vec<String> = Self::get_array_value("autoconf", bb_data, Some(vec![]))?;
+        let localconf_files: Vec<String> =
+            Self::get_array_value("localconf_files", bb_data, Some(vec![]))?;
+        let multiconfig: IndexMap<String, Vec<String>> = Self::get_multiconfig(bb_data)?;
+        let local_conf_user: String = Self::get_str_value(
+            "localconf_user",
+            bb_data,
+            Some(String::from("conf/local.conf.user")),
+        )?;
+        let build_dir: String = Self::get_str_value("builddir", bb_data, Some(product.clone()))?;
+        Self::verify_build_dir(&build_dir)?;
+        let tmp_dir: String = Self::get_str_value("tmpdir", bb_data, Some(String::new()))?;
 
         Ok(WsBitbakeData {
             data: bb_data.clone(),
@@ -71,9 +92,37 @@ impl WsBitbakeData {
             local_conf,
             settings: settings.clone(),
             init_env,
+            site_conf,
+            auto_conf,
+            localconf_files,
+            multiconfig,
+            local_conf_user,
+            build_dir,
+            tmp_dir,
         })
     }
 
+    fn get_multiconfig(bb_data: &Value) -> Result<IndexMap<String, Vec<String>>, BError> {
+        let mut multiconfig: IndexMap<String, Vec<String>> = IndexMap::new();
+        match bb_data.get("multiconfig") {
+            Some(value) => match value.as_object() {
+                Some(object) => {
+                    for name in object.keys() {
+                        let conf: Vec<String> = Self::get_array_value(name, value, None)?;
+                        multiconfig.insert(name.clone(), conf);
+                    }
+                }
+                None => {
+                    return Err(BError::ParseError(String::from(
+                        "Failed to parse multiconfig. Error when reading object 'multiconfig'",
+                    )));
+                }
+            },
+            None => {}
+        }
+        Ok(multiconfig)
+    }
+
     pub fn expand_conf(&self, ctx: &Context, conf: &Vec<String>) -> Result<Vec<String>, BError> {
         let mut config: Vec<String> = Vec::new();
         for line in conf.clone() {
@@ -85,6 +134,7 @@ impl WsBitbakeData {
     }
 
     pub fn expand_ctx(&mut self, ctx: &Context) -> Result<(), BError> {
+        self.settings.expand_ctx(ctx)?;
         self.machine = ctx.expand_str(&self.machine)?;
         self.distro = ctx.expand_str(&self.distro)?;
         self.docker = ctx.expand_str(&self.docker)?;
@@ -92,6 +142,40 @@ impl WsBitbakeData {
         self.init_env = ctx.expand_str(&self.init_env)?;
         self.bblayers_conf = self.expand_conf(ctx, &self.bblayers_conf)?;
         self.local_conf = self.expand_conf(ctx, &self.local_conf)?;
+        self.site_conf = self.expand_conf(ctx, &self.site_conf)?;
+        self.auto_conf = self.expand_conf(ctx, &self.auto_conf)?;
+        self.localconf_files = self.expand_conf(ctx, &self.localconf_files)?;
+        let mut multiconfig: IndexMap<String, Vec<String>> = IndexMap::new();
+        for (name, conf) in self.multiconfig.iter() {
+            multiconfig.insert(ctx.expand_str(name)?, self.expand_conf(ctx, conf)?);
+        }
+        self.multiconfig = multiconfig;
+        self.local_conf_user = ctx.expand_str(&self.local_conf_user)?;
+        self.build_dir = ctx.expand_str(&self.build_dir)?;
+        Self::verify_build_dir(&self.build_dir)?;
+        self.tmp_dir = ctx.expand_str(&self.tmp_dir)?;
+        Ok(())
+    }
+
+    /*
+     * The build dir is removed by the clean command so it has to be a
+     * dir below the builds dir. An absolute path would replace the builds
+     * dir and an empty path or '..' would resolve to the builds dir or
+     * somewhere outside of it.
+     */
+    fn verify_build_dir(build_dir: &str) -> Result<(), BError> {
+        let path: &Path = Path::new(build_dir);
+        let valid: bool = !path.is_absolute()
+            && path
+                .components()
+                .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+            && path.components().any(|c| matches!(c, Component::Normal(_)));
+        if !valid {
+            return Err(BError::ValueError(format!(
+                "Invalid bitbake build dir '{}', it has to be a relative path below the builds dir",
+                build_dir
+            )));
+        }
         Ok(())
     }
 
@@ -108,6 +192,13 @@ impl WsBitbakeData {
         for line in self.local_conf.clone() {
             conf_str.push_str(format!("{}\n", line).as_str());
         }
+        /*
+         * Shared fragments are pulled in using require so bitbake
+         * will fail if any of them is missing in the workspace
+         */
+        for file in self.localconf_files() {
+            conf_str.push_str(&format!("require {}\n", file.display()));
+        }
         conf_str.push_str(&format!("MACHINE ?= \"{}\"\n", self.machine()));
         // TODO: we should define a method product_name() call that instead
         conf_str.push_str(&format!("PRODUCT_NAME ?= \"{}\"\n", self.product));
@@ -120,9 +211,148 @@ impl WsBitbakeData {
             "DL_DIR ?= \"{}\"\n",
             self.dl_dir().to_str().unwrap()
         ));
+        conf_str.push_str(&self.cache_conf());
+        if !self.tmp_dir.is_empty() {
+            conf_str.push_str(&format!(
+                "TMPDIR ?= \"{}\"\n",
+                self.tmp_dir().to_str().unwrap()
+            ));
+        }
+        if !self.multiconfig.is_empty() {
+            let names: Vec<String> = self.multiconfig.keys().cloned().collect();
+            conf_str.push_str(&format!("BBMULTICONFIG ?= \"{}\"\n", names.join(" ")));
+        }
+        conf_str
+    }
+
+    /*
+     * The shared caches are defined in the cache segment of the workspace.json
+     * and are the same for all build configs in the workspace
+     */
+    pub fn cache_conf(&self) -> String {
+        let mut conf_str: String = String::new();
+        let sstate_mirrors: Vec<String> = self.sstate_mirrors();
+        if !sstate_mirrors.is_empty() {
+            conf_str.push_str("SSTATE_MIRRORS ?= \"\\\n");
+            for mirror in sstate_mirrors {
+                if mirror.starts_with("file://") {
+                    conf_str.push_str(&format!("file://.* {}/PATH \\\n", mirror));
+                } else {
+                    conf_str.push_str(&format!(
+                        "file://.* {}/PATH;downloadfilename=PATH \\\n",
+                        mirror
+                    ));
+                }
+            }
+            conf_str.push_str("\"\n");
+        }
+        let premirrors: &Vec<String> = self.settings.cache_premirrors();
+        if !premirrors.is_empty() {
+            conf_str.push_str("PREMIRRORS:prepend = \"\\\n");
+            for mirror in premirrors {
+                let mirror: &str = mirror.trim_end_matches('/');
+                for scheme in ["git", "gitsm", "ftp", "http", "https"] {
+                    conf_str.push_str(&format!("{}://.*/.* {}/ \\\n", scheme, mirror));
+                }
+            }
+            conf_str.push_str("\"\n");
+        }
+        if !self.hashserve().is_empty() {
+            conf_str.push_str(&format!("BB_HASHSERVE ?= \"{}\"\n", self.hashserve()));
+        }
+        if self.settings.services_prserv() {
+            conf_str.push_str(&format!(
+                "PRSERV_HOST ?= \"{}\"\n",
+                self.settings.prserv_host()
+            ));
+        }
+        if !self.settings.cache_signature_handler().is_empty() {
+            conf_str.push_str(&format!(
+                "BB_SIGNATURE_HANDLER ?= \"{}\"\n",
+                self.settings.cache_signature_handler()
+            ));
+        }
+        conf_str
+    }
+
+    /*
+     * If the workspace is running a local hash equivalence server it
+     * is used by bitbake and the shared server from the cache segment
+     * is instead used as upstream by the local server
+     */
+    pub fn hashserve(&self) -> String {
+        if self.settings.services_hashserv() {
+            return self.settings.hashserv_bind();
+        }
+        self.settings.cache_hashserve().to_string()
+    }
+
+    pub fn sstate_mirrors(&self) -> Vec<String> {
+        self.settings
+            .cache_sstate_mirrors()
+            .iter()
+            .map(|mirror| {
+                let mirror: &str = mirror.trim_end_matches('/');
+                if self.settings.cache_arch_layout() {
+                    // Same layout as the local sstate cache <cache dir>/<arch>/sstate-cache
+                    return format!("{}/{}/sstate-cache", mirror, self.arch);
+                }
+                mirror.to_string()
+            })
+            .collect()
+    }
+
+    pub fn caches(&self) -> Vec<String> {
+        let mut caches: Vec<String> = vec![
+            format!("sstate cache '{}'", self.sstate_dir().display()),
+            format!("download cache '{}'", self.dl_dir().display()),
+        ];
+        for mirror in self.sstate_mirrors() {
+            caches.push(format!("sstate mirror '{}'", mirror));
+        }
+        for mirror in self.settings.cache_premirrors() {
+            caches.push(format!("download mirror '{}'", mirror));
+        }
+        if !self.hashserve().is_empty() {
+            caches.push(format!("hash equivalence server '{}'", self.hashserve()));
+        }
+        if self.settings.services_prserv() {
+            caches.push(format!("PR server '{}'", self.settings.prserv_host()));
+        }
+        caches
+    }
+
+    fn conf_to_string(conf: &Vec<String>) -> String {
+        let mut conf_str: String = String::new();
+        for line in conf {
+            conf_str.push_str(format!("{}\n", line).as_str());
+        }
         conf_str
     }
 
+    pub fn site_conf(&self) -> String {
+        Self::conf_to_string(&self.site_conf)
+    }
+
+    pub fn auto_conf(&self) -> String {
+        Self::conf_to_string(&self.auto_conf)
+    }
+
+    pub fn multiconfig(&self) -> IndexMap<String, String> {
+        let mut multiconfig: IndexMap<String, String> = IndexMap::new();
+        for (name, conf) in self.multiconfig.iter() {
+            multiconfig.insert(name.clone(), Self::conf_to_string(conf));
+        }
+        multiconfig
+    }
+
+    pub fn localconf_files(&self) -> Vec<PathBuf> {
+        self.localconf_files
+            .iter()
+            .map(|f| self.settings.work_dir().join(f))
+            .collect()
+    }
+
     pub fn to_string(&self) -> String {
         let bb_str: String = format!("\"bb\": {}", self.data.to_string());
         bb_str.clone()
@@ -136,11 +366,46 @@ impl WsBitbakeData {
         &self.distro
     }
 
+    pub fn builds_dir(&self) -> PathBuf {
+        self.settings.builds_dir()
+    }
+
     pub fn build_dir(&self) -> PathBuf {
         self.settings
             .builds_dir()
             .clone()
-            .join(PathBuf::from(self.product.clone()))
+            .join(PathBuf::from(self.build_dir.clone()))
+    }
+
+    pub fn tmp_dir(&self) -> PathBuf {
+        if self.tmp_dir.is_empty() {
+            return self.build_dir().join("tmp");
+        }
+        self.build_dir().join(PathBuf::from(self.tmp_dir.clone()))
+    }
+
+    pub fn tmp_dir_outside_build_dir(&self) -> bool {
+        !self.tmp_dir().starts_with(self.build_dir())
+    }
+
+    /*
+     * A marker is written to a TMPDIR outside of the build dir when the
+     * bitbake configs are generated. It contains the build dir so that a
+     * TMPDIR is only removed by the clean command if it was created by
+     * bakery for this build.
+     */
+    pub fn tmp_dir_marker_path(&self) -> PathBuf {
+        self.tmp_dir().join(".bakery-tmpdir")
+    }
+
+    pub fn tmp_dir_owned(&self) -> bool {
+        if !self.tmp_dir_outside_build_dir() {
+            return true;
+        }
+        match std::fs::read_to_string(self.tmp_dir_marker_path()) {
+            Ok(content) => content.trim() == self.build_dir().to_string_lossy(),
+            Err(_err) => false,
+        }
     }
 
     pub fn docker_image(&self) -> &str {
@@ -159,11 +424,53 @@ impl WsBitbakeData {
         self.build_config_dir().join("bblayers.conf")
     }
 
+    pub fn local_conf_user_path(&self) -> Option<PathBuf> {
+        if self.local_conf_user.is_empty() {
+            return None;
+        }
+        // If the path is absolute the join will replace the build dir
+        Some(self.build_dir().join(&self.local_conf_user))
+    }
+
+    pub fn site_conf_path(&self) -> PathBuf {
+        self.build_config_dir().join("site.conf")
+    }
+
+    pub fn auto_conf_path(&self) -> PathBuf {
+        self.build_config_dir().join("auto.conf")
+    }
+
+    pub fn multiconfig_dir(&self) -> PathBuf {
+        self.build_config_dir().join("multiconfig")
+    }
+
+    pub fn multiconfig_conf_path(&self, name: &str) -> PathBuf {
+        self.multiconfig_dir().join(format!("{}.conf", name))
+    }
+
     pub fn deploy_dir(&self) -> PathBuf {
+        /*
+         * The default deploy dir is located under TMPDIR so if
+         * the TMPDIR has been moved the deploy dir should follow
+         */
+        match self.deploy_dir.strip_prefix("tmp/") {
+            Some(deploy_dir) if !self.tmp_dir.is_empty() => {
+                return self.tmp_dir().join(PathBuf::from(deploy_dir));
+            }
+            _ => {}
+        }
         self.build_dir()
             .join(PathBuf::from(self.deploy_dir.clone()))
     }
 
+    /*
+     * The buildhistory class is storing the history in TOPDIR which
+     * is the bitbake build dir
+     */
+    pub fn buildhistory_dir(&self) -> PathBuf {
+        self.build_dir().join("buildhistory")
+    }
+
     pub fn sstate_dir(&self) -> PathBuf {
         self.settings
             .cache_dir()
@@ -193,6 +500,7 @@ mod tests {
 
     use crate::configs::Context;
     use crate::data::WsBitbakeData;
+    use crate::error::BError;
     use crate::workspace::WsSettingsHandler;
 
     #[test]
@@ -424,4 +732,253 @@ mod tests {
             .expect("Failed to parse product data");
         assert_eq!(data.to_string(), json_bb);
     }
+
+    #[test]
+    fn test_ws_bitbake_data_multiconfig() {
+        let json_settings: &str = r#"
+        {
+            "version": "6"
+        }"#;
+        let json_build_config = r#"
+        {
+            "version": "6",
+            "name": "test-name",
+            "arch": "test-arch",
+            "bb": {
+                "machine": "test-machine",
+                "distro": "test-distro",
+                "localconf": [
+                    "BB_LOCAL_CONF_TEST_LINE_1"
+                ],
+                "localconf_files": [
+                    "layers/meta-test/conf/shared.inc"
+                ],
+                "siteconf": [
+                    "SCONF_VERSION = \"1\""
+                ],
+                "autoconf": [
+                    "BB_AUTO_CONF_TEST_LINE_1"
+                ],
+                "multiconfig": {
+                    "firmware": [
+                        "MACHINE = \"$#[FW_MACHINE]\"",
+                        "TMPDIR = \"${TOPDIR}/tmp-firmware\""
+                    ],
+                    "linux": [
+                        "MACHINE = \"test-machine\""
+                    ]
+                }
+            }
+        }"#;
+        let work_dir: PathBuf = PathBuf::from("/workspace");
+        let settings: WsSettingsHandler =
+            WsSettingsHandler::from_str(&work_dir, json_settings, None)
+                .expect("Failed to parse settings");
+        let mut data: WsBitbakeData = WsBitbakeData::from_str(json_build_config, &settings)
+            .expect("Failed to parse product data");
+        let variables: IndexMap<String, String> = indexmap! {
+            "FW_MACHINE".to_string() => "test-mcu".to_string()
+        };
+        let context: Context = Context::new(&variables);
+        data.expand_ctx(&context).unwrap();
+        let mut conf_str: String = String::new();
+        conf_str.push_str("BB_LOCAL_CONF_TEST_LINE_1\n");
+        conf_str.push_str("require /workspace/layers/meta-test/conf/shared.inc\n");
+        conf_str.push_str("MACHINE ?= \"test-machine\"\n");
+        conf_str.push_str("PRODUCT_NAME ?= \"test-name\"\n");
+        conf_str.push_str("DISTRO ?= \"test-distro\"\n");
+        conf_str.push_str("SSTATE_DIR ?= \"/workspace/.cache/test-arch/sstate-cache\"\n");
+        conf_str.push_str("DL_DIR ?= \"/workspace/.cache/download\"\n");
+        conf_str.push_str("BBMULTICONFIG ?= \"firmware linux\"\n");
+        assert_eq!(data.local_conf(), conf_str);
+        assert_eq!(data.site_conf(), "SCONF_VERSION = \"1\"\n");
+        assert_eq!(data.auto_conf(), "BB_AUTO_CONF_TEST_LINE_1\n");
+        assert_eq!(
+            data.multiconfig().get("firmware").unwrap(),
+            "MACHINE = \"test-mcu\"\nTMPDIR = \"${TOPDIR}/tmp-firmware\"\n"
+        );
+        assert_eq!(
+            data.multiconfig().get("linux").unwrap(),
+            "MACHINE = \"test-machine\"\n"
+        );
+        assert_eq!(
+            data.site_conf_path(),
+            PathBuf::from("/workspace/builds/test-name/conf/site.conf")
+        );
+        assert_eq!(
+            data.auto_conf_path(),
+            PathBuf::from("/workspace/builds/test-name/conf/auto.conf")
+        );
+        assert_eq!(
+            data.multiconfig_conf_path("firmware"),
+            PathBuf::from("/workspace/builds/test-name/conf/multiconfig/firmware.conf")
+        );
+    }
+
+    #[test]
+    fn test_ws_bitbake_data_build_dir_tmp_dir() {
+        let json_settings: &str = r#"
+        {
+            "version": "6"
+        }"#;
+        let json_build_config = r#"
+        {
+            "version": "6",
+            "name": "test-name",
+            "arch": "test-arch",
+            "bb": {
+                "machine": "test-machine",
+                "distro": "test-distro",
+                "builddir": "$#[BKRY_NAME]-$#[BKRY_BUILD_VARIANT]",
+                "tmpdir": "/fast-disk/tmp"
+            }
+        }"#;
+        let work_dir: PathBuf = PathBuf::from("/workspace");
+        let settings: WsSettingsHandler =
+            WsSettingsHandler::from_str(&work_dir, json_settings, None)
+                .expect("Failed to parse settings");
+        let mut data: WsBitbakeData = WsBitbakeData::from_str(json_build_config, &settings)
+            .expect("Failed to parse product data");
+        let variables: IndexMap<String, String> = indexmap! {
+            "BKRY_NAME".to_string() => "test-name".to_string(),
+            "BKRY_BUILD_VARIANT".to_string() => "release".to_string()
+        };
+        let context: Context = Context::new(&variables);
+        data.expand_ctx(&context).unwrap();
+        assert_eq!(
+            data.build_dir(),
+            PathBuf::from("/workspace/builds/test-name-release")
+        );
+        assert_eq!(
+            data.local_conf_path(),
+            PathBuf::from("/workspace/builds/test-name-release/conf/local.conf")
+        );
+        assert_eq!(data.tmp_dir(), PathBuf::from("/fast-disk/tmp"));
+        assert!(data.tmp_dir_outside_build_dir());
+        assert!(!data.tmp_dir_owned());
+        assert_eq!(
+            data.deploy_dir(),
+            PathBuf::from("/fast-disk/tmp/deploy/images")
+        );
+        assert!(data
+            .local_conf()
+            .ends_with("TMPDIR ?= \"/fast-disk/tmp\"\n"));
+    }
+
+    #[test]
+    fn test_ws_bitbake_data_invalid_build_dir() {
+        let json_settings: &str = r#"
+        {
+            "version": "6"
+        }"#;
+        let work_dir: PathBuf = PathBuf::from("/workspace");
+        let settings: WsSettingsHandler =
+            WsSettingsHandler::from_str(&work_dir, json_settings, None)
+                .expect("Failed to parse settings");
+        for build_dir in ["", ".", "..", "../other", "test/../..", "/builds/test"] {
+            let json_build_config: String = format!(
+                r#"{{ "version": "6", "name": "test-name", "bb": {{ "builddir": "{}" }} }}"#,
+                build_dir
+            );
+            let result: Result<WsBitbakeData, BError> =
+                WsBitbakeData::from_str(&json_build_config, &settings);
+            match result {
+                Ok(_data) => {
+                    panic!("Expected an error for the build dir '{}'", build_dir);
+                }
+                Err(e) => {
+                    assert_eq!(
+                        e.to_string(),
+                        format!(
+                            "Invalid bitbake build dir '{}', it has to be a relative path below the builds dir",
+                            build_dir
+                        )
+                    );
+                }
+            }
+        }
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "test-name",
+            "bb": {
+                "builddir": "$#[BUILD_DIR]"
+            }
+        }"#;
+        let mut data: WsBitbakeData = WsBitbakeData::from_str(json_build_config, &settings)
+            .expect("Failed to parse product data");
+        let variables: IndexMap<String, String> = indexmap! {
+            "BUILD_DIR".to_string() => "/".to_string()
+        };
+        assert!(data.expand_ctx(&Context::new(&variables)).is_err());
+    }
+
+    #[test]
+    fn test_ws_bitbake_data_cache() {
+        let json_settings: &str = r#"
+        {
+            "version": "6",
+            "cache": {
+                "sstatemirrors": [
+                    "file:///nfs/cache",
+                    "https://sstate.example.com/cache/"
+                ],
+                "premirrors": [
+                    "file:///nfs/downloads"
+                ],
+                "hashserve": "ws://hashserv.example.com:8686",
+                "signaturehandler": "OEEquivHash",
+                "archlayout": "true"
+            }
+        }"#;
+        let json_build_config = r#"
+        {
+            "version": "6",
+            "name": "test-name",
+            "arch": "test-arch",
+            "bb": {
+                "machine": "test-machine",
+                "distro": "test-distro"
+            }
+        }"#;
+        let work_dir: PathBuf = PathBuf::from("/workspace");
+        let settings: WsSettingsHandler =
+            WsSettingsHandler::from_str(&work_dir, json_settings, None)
+                .expect("Failed to parse settings");
+        let data: WsBitbakeData = WsBitbakeData::from_str(json_build_config, &settings)
+            .expect("Failed to parse product data");
+        let mut conf_str: String = String::new();
+        conf_str.push_str("MACHINE ?= \"test-machine\"\n");
+        conf_str.push_str("PRODUCT_NAME ?= \"test-name\"\n");
+        conf_str.push_str("DISTRO ?= \"test-distro\"\n");
+        conf_str.push_str("SSTATE_DIR ?= \"/workspace/.cache/test-arch/sstate-cache\"\n");
+        conf_str.push_str("DL_DIR ?= \"/workspace/.cache/download\"\n");
+        conf_str.push_str("SSTATE_MIRRORS ?= \"\\\n");
+        conf_str.push_str("file://.* file:///nfs/cache/test-arch/sstate-cache/PATH \\\n");
+        conf_str.push_str("file://.* https://sstate.example.com/cache/test-arch/sstate-cache/PATH;downloadfilename=PATH \\\n");
+        conf_str.push_str("\"\n");
+        conf_str.push_str("PREMIRRORS:prepend = \"\\\n");
+        conf_str.push_str("git://.*/.* file:///nfs/downloads/ \\\n");
+        conf_str.push_str("gitsm://.*/.* file:///nfs/downloads/ \\\n");
+        conf_str.push_str("ftp://.*/.* file:///nfs/downloads/ \\\n");
+        conf_str.push_str("http://.*/.* file:///nfs/downloads/ \\\n");
+        conf_str.push_str("https://.*/.* file:///nfs/downloads/ \\\n");
+        conf_str.push_str("\"\n");
+        conf_str.push_str("BB_HASHSERVE ?= \"ws://hashserv.example.com:8686\"\n");
+        conf_str.push_str("BB_SIGNATURE_HANDLER ?= \"OEEquivHash\"\n");
+        assert_eq!(data.local_conf(), conf_str);
+        assert_eq!(
+            data.caches(),
+            vec![
+                String::from("sstate cache '/workspace/.cache/test-arch/sstate-cache'"),
+                String::from("download cache '/workspace/.cache/download'"),
+                String::from("sstate mirror 'file:///nfs/cache/test-arch/sstate-cache'"),
+                String::from(
+                    "sstate mirror 'https://sstate.example.com/cache/test-arch/sstate-cache'"
+                ),
+                String::from("download mirror 'file:///nfs/downloads'"),
+                String::from("hash equivalence server 'ws://hashserv.example.com:8686'"),
+            ]
+        );
+    }
 }
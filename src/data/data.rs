@@ -85,13 +85,8 @@ impl WsBuildData {
 
         context.update(&ctx_built_in_variables);
         // Update the "built-in" bitbake paths in the context variables
-        let bb_build_dir: PathBuf = settings
-            .builds_dir()
-            .clone()
-            .join(PathBuf::from(product.name().to_string()));
-        let bb_deploy_dir: PathBuf = bb_build_dir
-            .clone()
-            .join(PathBuf::from(bitbake.deploy_dir().clone()));
+        let bb_build_dir: PathBuf = bitbake.build_dir();
+        let bb_deploy_dir: PathBuf = bitbake.deploy_dir();
         let ctx_bitbake_variables: IndexMap<String, String> = indexmap! {
             context::CTX_KEY_BB_BUILD_DIR.to_string() => bb_build_dir.to_string_lossy().to_string(),
             context::CTX_KEY_BB_DEPLOY_DIR.to_string() => bb_deploy_dir.to_string_lossy().to_string(),
@@ -216,6 +211,21 @@ impl WsBuildData {
         self.config.version()
     }
 
+    pub fn logs_dir(&self) -> PathBuf {
+        self.settings.builds_dir().join(self.name()).join("logs")
+    }
+
+    /*
+     * The checksums of the artifacts collected by each task are recorded
+     * in this directory so the artifacts can be verified after the build.
+     */
+    pub fn collected_dir(&self) -> PathBuf {
+        self.settings
+            .builds_dir()
+            .join(self.name())
+            .join("collected")
+    }
+
     pub fn valid(&self) -> bool {
         return self.config.version() != "NA"
             && self.product().name() != "NA"
@@ -19,4 +19,4 @@ pub use customsubcmd::WsCustomSubCmdData;
 pub use data::WsBuildData;
 pub use include::WsIncludeData;
 pub use product::WsProductData;
-pub use task::{TType, WsTaskData};
+pub use task::{TType, WarningsPolicy, WsTaskData};
@@ -13,6 +13,13 @@ pub enum TType {
     NonBitbake,
 }
 
+#[derive(Clone, PartialEq, Debug)]
+pub enum WarningsPolicy {
+    Allow,
+    Fail,
+    Max(u32),
+}
+
 pub struct WsTaskData {
     index: u32,
     name: String,
@@ -26,6 +33,7 @@ pub struct WsTaskData {
     recipes: Vec<String>, // The list of recipes will be empty if the type for the task is 'non-bitbake'
     description: String,
     env: IndexMap<String, String>,
+    warnings: String, // Optional if not set for the task the default value 'allow' is used
 }
 
 impl Config for WsTaskData {}
@@ -46,6 +54,20 @@ impl WsTaskData {
         work_dir.join(PathBuf::from(task_build_dir))
     }
 
+    fn warnings_policy(warnings: &str) -> Result<WarningsPolicy, BError> {
+        match warnings {
+            "allow" => Ok(WarningsPolicy::Allow),
+            "fail" => Ok(WarningsPolicy::Fail),
+            _ => match warnings.parse::<u32>() {
+                Ok(max) => Ok(WarningsPolicy::Max(max)),
+                Err(_err) => Err(BError::ParseTasksError(format!(
+                    "Invalid warnings '{}' expected 'allow', 'fail' or a maximum number of warnings",
+                    warnings
+                ))),
+            },
+        }
+    }
+
     pub fn from_str(json_string: &str, build_data: &WsBuildData) -> Result<Self, BError> {
         let data: Value = Self::parse(json_string)?;
         Self::from_value(&data, build_data)
@@ -73,6 +95,7 @@ impl WsTaskData {
             Self::get_str_value("description", &data, Some(String::from("NA")))?;
         let env: IndexMap<String, String> = Self::get_hashmap_value("env", &data)?;
         let recipes: Vec<String> = Self::get_array_value("recipes", &data, Some(vec![]))?;
+        let warnings: String = Self::get_str_value("warnings", &data, Some(String::from("allow")))?;
 
         let enum_ttype: TType;
         match ttype.as_str() {
@@ -87,6 +110,11 @@ impl WsTaskData {
             }
         }
 
+        // The warnings can be expanded from the context in which case it is validated after expansion
+        if !warnings.contains("$#[") {
+            Self::warnings_policy(&warnings)?;
+        }
+
         let task_build_dir: PathBuf =
             Self::determine_build_dir(enum_ttype.clone(), &build_dir, bb_build_dir, work_dir);
 
@@ -110,6 +138,7 @@ impl WsTaskData {
             recipes,
             description,
             env,
+            warnings,
         })
     }
 
@@ -121,6 +150,8 @@ impl WsTaskData {
         self.condition = ctx.expand_str(&self.condition)?;
         self.disabled = ctx.expand_str(&self.disabled)?;
         self.description = ctx.expand_str(&self.description)?;
+        self.warnings = ctx.expand_str(&self.warnings)?;
+        Self::warnings_policy(&self.warnings)?;
         for r in self.recipes.iter_mut() {
             *r = ctx.expand_str(r)?;
         }
@@ -189,6 +220,10 @@ impl WsTaskData {
     pub fn env(&self) -> &IndexMap<String, String> {
         &self.env
     }
+
+    pub fn warnings(&self) -> WarningsPolicy {
+        Self::warnings_policy(&self.warnings).unwrap_or(WarningsPolicy::Allow)
+    }
 }
 
 #[cfg(test)]
@@ -198,7 +233,7 @@ mod tests {
     use std::path::PathBuf;
 
     use crate::configs::Context;
-    use crate::data::{TType, WsTaskData};
+    use crate::data::{TType, WarningsPolicy, WsTaskData};
     use crate::error::BError;
     use crate::helper::Helper;
 
@@ -266,6 +301,48 @@ mod tests {
             task.recipes(),
             &vec![String::from("test-image"), String::from("test-image:sdk")]
         );
+        assert_eq!(task.warnings(), WarningsPolicy::Allow);
+    }
+
+    #[test]
+    fn test_ws_task_data_warnings() {
+        let work_dir: PathBuf = PathBuf::from("/workspace");
+        let bb_build_dir: PathBuf = work_dir.clone().join(String::from("builds/test-name"));
+        for (warnings, policy) in [
+            ("fail", WarningsPolicy::Fail),
+            ("allow", WarningsPolicy::Allow),
+            ("10", WarningsPolicy::Max(10)),
+        ] {
+            let json_task_config: String = format!(
+                r#"
+            {{
+                "index": "0",
+                "name": "task1-name",
+                "warnings": "{}",
+                "recipes": [
+                    "test-image"
+                ]
+            }}"#,
+                warnings
+            );
+            let data: Value =
+                Helper::parse(&json_task_config).expect("Failed to parse task config");
+            let task: WsTaskData =
+                WsTaskData::new(&data, &work_dir, &bb_build_dir).expect("Failed parsing task data");
+            assert_eq!(task.warnings(), policy);
+        }
+        let json_task_config: &str = r#"
+        {
+            "index": "0",
+            "name": "task1-name",
+            "warnings": "invalid",
+            "recipes": [
+                "test-image"
+            ]
+        }"#;
+        let data: Value = Helper::parse(json_task_config).expect("Failed to parse task config");
+        let result: Result<WsTaskData, BError> = WsTaskData::new(&data, &work_dir, &bb_build_dir);
+        assert!(result.is_err());
     }
 
     #[test]
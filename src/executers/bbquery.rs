@@ -0,0 +1,374 @@
+use crate::cli::Cli;
+use crate::data::WsBitbakeData;
+use crate::error::BError;
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/*
+ * Answer common questions about the bitbake environment of a build config
+ * by running the bitbake tools and turning their output into json. The
+ * tools are executed in the same bitbake environment as a bitbake task
+ * so the answers are the same as what bitbake would use during a build.
+ */
+pub struct BBQuery<'a> {
+    cli: &'a Cli,
+    bb_data: &'a WsBitbakeData,
+}
+
+impl<'a> BBQuery<'a> {
+    fn env(&self) -> Result<HashMap<String, String>, BError> {
+        let mut env: HashMap<String, String> = self
+            .cli
+            .source_init_env(&self.bb_data.init_env_file(), &self.bb_data.build_dir())?;
+        env.insert(
+            String::from("BB_ENV_PASSTHROUGH_ADDITIONS"),
+            String::from("SSTATE_DIR DL_DIR TMPDIR"),
+        );
+        Ok(env)
+    }
+
+    fn run(&self, cmd: Vec<String>) -> Result<String, BError> {
+        /*
+         * The bitbake configs needs to be in place before any of the bitbake
+         * tools can be used. They are generated by the build using the
+         * variables from the build args so they are never generated here
+         * since that would change the configs used by the build.
+         */
+        for conf in [
+            self.bb_data.local_conf_path(),
+            self.bb_data.bblayers_conf_path(),
+        ] {
+            if !conf.exists() {
+                return Err(BError::ValueError(format!(
+                    "Missing '{}', the build config needs to be built before it can be queried",
+                    conf.display()
+                )));
+            }
+        }
+
+        let mut cmd_line: Vec<String> = vec![
+            "cd".to_string(),
+            self.bb_data.build_dir().to_string_lossy().to_string(),
+            "&&".to_string(),
+        ];
+        cmd_line.extend(cmd);
+        self.cli.check_output(&cmd_line, &self.env()?, true)
+    }
+
+    fn getvar_cmd(variable: &str, recipe: &str, value_only: bool) -> Vec<String> {
+        let mut cmd: Vec<String> = vec!["bitbake-getvar".to_string()];
+        if !recipe.is_empty() {
+            cmd.append(&mut vec!["-r".to_string(), recipe.to_string()]);
+        }
+        if value_only {
+            cmd.push("--value".to_string());
+        }
+        cmd.push(variable.to_string());
+        cmd
+    }
+
+    fn last_line(output: &str) -> String {
+        output
+            .lines()
+            .rfind(|l| !l.trim().is_empty())
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    }
+
+    /*
+     * Parse the output from bitbake-getvar. The history of the variable is
+     * printed as comments followed by the value of the variable. A value
+     * spanning multiple lines is using a backslash at the end of each line.
+     */
+    pub fn parse_getvar(variable: &str, output: &str) -> (Option<String>, Vec<String>) {
+        let mut history: Vec<String> = vec![];
+        let mut value: Option<String> = None;
+        let mut lines: std::str::Lines = output.lines();
+        let assignment: String = format!("{}=", variable);
+
+        while let Some(line) = lines.next() {
+            if let Some(comment) = line.strip_prefix('#') {
+                let comment: &str = comment.trim();
+                if !comment.is_empty() {
+                    history.push(comment.to_string());
+                }
+                continue;
+            }
+
+            let line: &str = line.strip_prefix("export ").unwrap_or(line);
+            if let Some(raw) = line.strip_prefix(&assignment) {
+                let mut raw: String = raw.to_string();
+                while raw.ends_with('\\') {
+                    raw.pop();
+                    match lines.next() {
+                        Some(next) => raw.push_str(next.trim()),
+                        None => break,
+                    }
+                }
+                let raw: &str = raw.trim();
+                let raw: &str = raw
+                    .strip_prefix('"')
+                    .and_then(|r| r.strip_suffix('"'))
+                    .unwrap_or(raw);
+                value = Some(raw.trim().to_string());
+            }
+        }
+
+        (value, history)
+    }
+
+    /*
+     * Parse the table printed by 'bitbake-layers show-layers'. The rows of
+     * the table are listed after the line separating the header.
+     */
+    pub fn parse_layers(output: &str) -> Vec<Value> {
+        let mut layers: Vec<Value> = vec![];
+        let mut table: bool = false;
+
+        for line in output.lines() {
+            if line.starts_with("====") {
+                table = true;
+                continue;
+            }
+
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            if !table || columns.len() != 3 {
+                continue;
+            }
+
+            let priority: Value = match columns[2].parse::<i64>() {
+                Ok(priority) => json!(priority),
+                Err(_err) => json!(columns[2]),
+            };
+            layers.push(json!({
+                "name": columns[0],
+                "path": columns[1],
+                "priority": priority,
+            }));
+        }
+
+        layers
+    }
+
+    /*
+     * Parse the output from 'bitbake-layers show-recipes'. Each recipe is
+     * listed with the name followed by a ':' and then one indented line
+     * for each layer providing a version of the recipe.
+     */
+    pub fn parse_recipes(output: &str) -> Vec<Value> {
+        let mut recipes: Vec<Value> = vec![];
+        let mut recipe: String = String::new();
+
+        for line in output.lines() {
+            if line.starts_with("===") || line.starts_with("NOTE:") || line.trim().is_empty() {
+                continue;
+            }
+
+            if !line.starts_with(' ') {
+                if let Some(name) = line.trim_end().strip_suffix(':') {
+                    recipe = name.to_string();
+                }
+                continue;
+            }
+
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            if recipe.is_empty() || columns.len() < 2 {
+                continue;
+            }
+
+            recipes.push(json!({
+                "recipe": recipe,
+                "layer": columns[0],
+                "version": columns[1],
+                "skipped": line.contains("(skipped"),
+            }));
+        }
+
+        recipes
+    }
+
+    /*
+     * The image manifest is listing one package per line with the name,
+     * architecture and version of the package.
+     */
+    pub fn parse_manifest(content: &str) -> Vec<Value> {
+        content
+            .lines()
+            .map(|l| l.split_whitespace().collect::<Vec<&str>>())
+            .filter(|c| c.len() == 3)
+            .map(|c| {
+                json!({
+                    "name": c[0],
+                    "arch": c[1],
+                    "version": c[2],
+                })
+            })
+            .collect()
+    }
+
+    pub fn getvar(&self, variable: &str, recipe: &str) -> Result<Value, BError> {
+        let output: String = self.run(Self::getvar_cmd(variable, recipe, false))?;
+        let (value, history) = Self::parse_getvar(variable, &output);
+        Ok(json!({
+            "variable": variable,
+            "recipe": recipe,
+            "value": value,
+            "history": history,
+        }))
+    }
+
+    pub fn layers(&self) -> Result<Value, BError> {
+        let output: String = self.run(vec![
+            "bitbake-layers".to_string(),
+            "show-layers".to_string(),
+        ])?;
+        Ok(json!({ "layers": Self::parse_layers(&output) }))
+    }
+
+    /*
+     * The show-recipes command is only matching recipe names so a target
+     * like virtual/kernel is first resolved by bitbake using PROVIDES and
+     * the preferred providers. The environment for the target is the one
+     * of the recipe bitbake would build for it and it is also including
+     * the preferred provider from the global configuration.
+     */
+    pub fn providers(&self, target: &str) -> Result<Value, BError> {
+        let env: String = self.run(vec![
+            "bitbake".to_string(),
+            "-e".to_string(),
+            target.to_string(),
+        ])?;
+        let provider: String = Self::parse_getvar("PN", &env).0.unwrap_or_default();
+        let provides: String = Self::parse_getvar("PROVIDES", &env).0.unwrap_or_default();
+        let preferred: String = Self::parse_getvar(&format!("PREFERRED_PROVIDER_{}", target), &env)
+            .0
+            .unwrap_or_default();
+
+        let mut recipes: Vec<Value> = vec![];
+        if !provider.is_empty() {
+            let output: String = self.run(vec![
+                "bitbake-layers".to_string(),
+                "show-recipes".to_string(),
+                provider.clone(),
+            ])?;
+            recipes = Self::parse_recipes(&output);
+        }
+
+        Ok(json!({
+            "target": target,
+            "provider": provider,
+            "preferred_provider": preferred,
+            "provides": provides.split_whitespace().collect::<Vec<&str>>(),
+            "recipes": recipes,
+        }))
+    }
+
+    /*
+     * The packages are read from the manifest created by the image recipe
+     * in the deploy dir so the image needs to have been built first.
+     */
+    pub fn packages(&self, image: &str) -> Result<Value, BError> {
+        let deploy_dir: String =
+            Self::last_line(&self.run(Self::getvar_cmd("DEPLOY_DIR_IMAGE", image, true))?);
+        let link_name: String =
+            Self::last_line(&self.run(Self::getvar_cmd("IMAGE_LINK_NAME", image, true))?);
+        let manifest: PathBuf = PathBuf::from(deploy_dir).join(format!("{}.manifest", link_name));
+
+        if !manifest.exists() {
+            return Err(BError::CliError(format!(
+                "No manifest '{}' found for image '{}', make sure the image has been built",
+                manifest.display(),
+                image
+            )));
+        }
+
+        let content: String = std::fs::read_to_string(&manifest)?;
+        Ok(json!({
+            "image": image,
+            "manifest": manifest.to_string_lossy(),
+            "packages": Self::parse_manifest(&content),
+        }))
+    }
+
+    pub fn new(cli: &'a Cli, bb_data: &'a WsBitbakeData) -> Self {
+        BBQuery { cli, bb_data }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::executers::BBQuery;
+
+    #[test]
+    fn test_bbquery_parse_getvar() {
+        let output: &str = r#"#
+# $IMAGE_FSTYPES [2 operations]
+#   set /layers/poky/meta/conf/bitbake.conf:855
+#     "tar.gz"
+#   set /builds/default/conf/local.conf:12
+#     "ext4 \
+#     wic"
+# pre-expansion value:
+#   "ext4 wic"
+IMAGE_FSTYPES="ext4 \
+    wic"
+"#;
+        let (value, history) = BBQuery::parse_getvar("IMAGE_FSTYPES", output);
+        assert_eq!(value, Some(String::from("ext4 wic")));
+        assert_eq!(history[0], "$IMAGE_FSTYPES [2 operations]");
+        assert_eq!(history[1], "set /layers/poky/meta/conf/bitbake.conf:855");
+        assert_eq!(history.len(), 8);
+        let (value, _history) = BBQuery::parse_getvar("PATH", "export PATH=\"/usr/bin\"\n");
+        assert_eq!(value, Some(String::from("/usr/bin")));
+        let (value, _history) = BBQuery::parse_getvar("UNSET", "#\n# $UNSET\n");
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    fn test_bbquery_parse_layers() {
+        let output: &str = r#"NOTE: Starting bitbake server...
+layer                 path                                      priority
+==========================================================================
+meta                  /layers/poky/meta                         5
+meta-poky             /layers/poky/meta-poky                    5
+meta-test             /layers/meta-test                         10
+"#;
+        let layers: Vec<Value> = BBQuery::parse_layers(output);
+        assert_eq!(layers.len(), 3);
+        assert_eq!(layers[2]["name"], "meta-test");
+        assert_eq!(layers[2]["path"], "/layers/meta-test");
+        assert_eq!(layers[2]["priority"], 10);
+    }
+
+    #[test]
+    fn test_bbquery_parse_recipes() {
+        let output: &str = r#"NOTE: Starting bitbake server...
+=== Matching recipes: ===
+busybox:
+  meta-test            1.36.1
+  meta                 1.35.0 (skipped: incompatible with machine)
+"#;
+        let recipes: Vec<Value> = BBQuery::parse_recipes(output);
+        assert_eq!(recipes.len(), 2);
+        assert_eq!(recipes[0]["recipe"], "busybox");
+        assert_eq!(recipes[0]["layer"], "meta-test");
+        assert_eq!(recipes[0]["version"], "1.36.1");
+        assert_eq!(recipes[0]["skipped"], false);
+        assert_eq!(recipes[1]["skipped"], true);
+    }
+
+    #[test]
+    fn test_bbquery_parse_manifest() {
+        let content: &str = "base-files qemux86_64 3.0.14\nbusybox core2_64 1.36.1\n";
+        let packages: Vec<Value> = BBQuery::parse_manifest(content);
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[1]["name"], "busybox");
+        assert_eq!(packages[1]["arch"], "core2_64");
+        assert_eq!(packages[1]["version"], "1.36.1");
+    }
+}
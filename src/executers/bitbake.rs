@@ -36,25 +36,50 @@ impl<'a> TaskExecuter for BBCleanExecuter<'a> {
 
         /*
          * We are only allowed to remove the bitbake build dir. We make sure that is
-         * the case by checking that it is a subdir of the task build directory which
-         * should be set to the work directory when it is a bitbake task
+         * the case by checking that it is a subdir of the builds dir and not the
+         * builds dir itself which would remove the builds of all the products
          */
-        if self
-            .bb_data
-            .build_dir()
-            .starts_with(self.task_data.build_dir())
+        let builds_dir: std::path::PathBuf = self.bb_data.builds_dir();
+        if self.bb_data.build_dir().starts_with(&builds_dir)
+            && self.bb_data.build_dir() != builds_dir
         {
             self.cli.info(format!(
                 "Removing bitbake build dir '{}'",
                 self.bb_data.build_dir().display()
             ));
             self.cli.rmdir_all(&self.bb_data.build_dir())?;
+            /*
+             * The TMPDIR can be placed outside of the bitbake build dir. It is only
+             * removed if it was created by bakery for this build and not a parent of
+             * the task build dir or the sstate cache since that would remove a lot
+             * more then intended
+             */
+            let tmp_dir: std::path::PathBuf = self.bb_data.tmp_dir();
+            if self.bb_data.tmp_dir_outside_build_dir() {
+                if !self.bb_data.tmp_dir_owned() {
+                    self.cli._warn(format!(
+                        "Bitbake tmp dir '{}' was not created by bakery for this build, skipping. Remove it manually if it is no longer needed",
+                        tmp_dir.display()
+                    ));
+                } else if !self.task_data.build_dir().starts_with(&tmp_dir)
+                    && !self.bb_data.sstate_dir().starts_with(&tmp_dir)
+                {
+                    self.cli
+                        .info(format!("Removing bitbake tmp dir '{}'", tmp_dir.display()));
+                    self.cli.rmdir_all(&tmp_dir)?;
+                } else {
+                    self.cli.info(format!(
+                        "Bitbake tmp dir '{}' is a parent of the build dir or cache, skipping",
+                        tmp_dir.display()
+                    ));
+                }
+            }
             self.cli.stdout(note);
         } else {
             self.cli.info(format!(
-                "Bitbake build dir '{}' is outside of the task build dir '{}'",
+                "Bitbake build dir '{}' is not below the builds dir '{}'",
                 self.bb_data.build_dir().display(),
-                self.task_data.build_dir().display()
+                builds_dir.display()
             ))
         }
 
@@ -77,6 +102,7 @@ pub struct BBBuildExecuter<'a> {
     task_data: &'a WsTaskData,
     bb_variables: &'a Vec<String>,
     cli: &'a Cli,
+    fetch: bool,
 }
 
 impl<'a> TaskExecuter for BBBuildExecuter<'a> {
@@ -86,10 +112,17 @@ impl<'a> TaskExecuter for BBBuildExecuter<'a> {
         dry_run: bool,
         interactive: bool,
     ) -> Result<(), BError> {
-        self.cli.info(format!(
-            "execute bitbake build task '{}'",
-            self.task_data.name()
-        ));
+        if self.fetch {
+            self.cli.info(format!(
+                "execute bitbake fetch task '{}'",
+                self.task_data.name()
+            ));
+        } else {
+            self.cli.info(format!(
+                "execute bitbake build task '{}'",
+                self.task_data.name()
+            ));
+        }
         let force: bool = dry_run;
         let env: HashMap<String, String> = self.bb_build_env(args_env_variables)?;
         let exec_dir: std::path::PathBuf = self.bb_data.build_dir();
@@ -109,7 +142,10 @@ impl<'a> TaskExecuter for BBBuildExecuter<'a> {
             ]);
             if !docker_str.is_empty() {
                 let image: DockerImage = DockerImage::new(docker_str)?;
-                let docker: Docker = Docker::new(image, interactive);
+                let mut docker: Docker = Docker::new(image, interactive);
+                if self.cli.hermetic() {
+                    docker.set_network("none");
+                }
                 docker.run_cmd(&mut cmd_line, &env, &exec_dir, &self.cli)?;
             } else {
                 self.cli.check_call(&cmd_line, &env, true)?;
@@ -122,6 +158,9 @@ impl<'a> TaskExecuter for BBBuildExecuter<'a> {
             let mut cmd_line: Vec<String> = vec![];
             let recipe: Recipe = Recipe::new(r);
             let mut cmd: Vec<String> = recipe.bitbake_cmd();
+            if self.fetch {
+                cmd = recipe.bitbake_fetch_cmd();
+            }
 
             cmd_line.append(&mut vec![
                 "cd".to_string(),
@@ -152,7 +191,10 @@ impl<'a> TaskExecuter for BBBuildExecuter<'a> {
 
             if !docker_str.is_empty() {
                 let image: DockerImage = DockerImage::new(docker_str)?;
-                let docker: Docker = Docker::new(image, interactive);
+                let mut docker: Docker = Docker::new(image, interactive);
+                if self.cli.hermetic() {
+                    docker.set_network("none");
+                }
                 docker.run_cmd(&mut cmd_line, &env, &exec_dir, &self.cli)?;
             } else {
                 self.cli.check_call(&cmd_line, &env, true)?;
@@ -229,6 +271,26 @@ impl<'a> BBBuildExecuter<'a> {
             bb_data,
             task_data,
             bb_variables,
+            fetch: false,
+        }
+    }
+
+    /*
+     * Setup an executer that is only fetching the sources required to
+     * build the recipes of the task
+     */
+    pub fn fetch(
+        cli: &'a Cli,
+        task_data: &'a WsTaskData,
+        bb_data: &'a WsBitbakeData,
+        bb_variables: &'a Vec<String>,
+    ) -> Self {
+        BBBuildExecuter {
+            cli,
+            bb_data,
+            task_data,
+            bb_variables,
+            fetch: true,
         }
     }
 }
@@ -242,6 +304,7 @@ mod tests {
     use crate::cli::*;
     use crate::data::{WsBuildData, WsTaskData};
     use crate::executers::{BBBuildExecuter, BBCleanExecuter, TaskExecuter};
+    use crate::fs::BitbakeConf;
     use crate::helper::Helper;
 
     #[test]
@@ -1098,4 +1161,174 @@ mod tests {
             .exec(&env_variables, true, true)
             .expect("Failed to execute task");
     }
+
+    #[test]
+    fn test_bitbake_clean_executer_tmp_dir() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let env_variables: HashMap<String, String> = HashMap::new();
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {
+                "machine": "raspberrypi3",
+                "distro": "strix",
+                "builddir": "$#[BKRY_NAME]-$#[BKRY_BUILD_VARIANT]",
+                "tmpdir": "$#[BKRY_WORK_DIR]/fast-disk/$#[BKRY_NAME]/tmp"
+            }
+        }"#;
+        let json_task_config: &str = r#"
+        {
+            "index": "0",
+            "name": "task1-name",
+            "recipes": [
+                "test-image"
+            ]
+        }"#;
+        let mut data: WsBuildData =
+            Helper::setup_build_data(&work_dir, Some(json_build_config), None);
+        data.expand_ctx().expect("Failed to expand context");
+        let mut task_data: WsTaskData =
+            WsTaskData::from_str(json_task_config, &data).expect("Failed to parse task config");
+        task_data
+            .expand_ctx(data.context().ctx())
+            .expect("Failed to expand context");
+        let build_dir: PathBuf = work_dir.join("builds/default-dev");
+        let tmp_dir: PathBuf = work_dir.join("fast-disk/default/tmp");
+        assert_eq!(data.bitbake().build_dir(), build_dir);
+        assert_eq!(task_data.build_dir(), &build_dir);
+        assert_eq!(data.bitbake().tmp_dir(), tmp_dir);
+        assert!(!data.bitbake().tmp_dir_owned());
+        BitbakeConf::new(data.bitbake(), &vec![], false)
+            .create_tmp_dir_marker()
+            .expect("Failed to create tmp dir marker");
+        assert!(data.bitbake().tmp_dir_owned());
+        let mut mocked_system: MockSystem = MockSystem::new();
+        mocked_system
+            .expect_rmdir_all()
+            .with(mockall::predicate::eq(build_dir.clone()))
+            .once()
+            .returning(|_x| Ok(()));
+        mocked_system
+            .expect_rmdir_all()
+            .with(mockall::predicate::eq(tmp_dir.clone()))
+            .once()
+            .returning(|_x| Ok(()));
+        let mut mocked_logger: MockLogger = MockLogger::new();
+        mocked_logger
+            .expect_info()
+            .with(mockall::predicate::eq(format!(
+                "execute bitbake clean task '{}'",
+                task_data.name()
+            )))
+            .once()
+            .returning(|_x| ());
+        mocked_logger
+            .expect_info()
+            .with(mockall::predicate::eq(format!(
+                "Removing bitbake build dir '{}'",
+                build_dir.display()
+            )))
+            .once()
+            .returning(|_x| ());
+        mocked_logger
+            .expect_info()
+            .with(mockall::predicate::eq(format!(
+                "Removing bitbake tmp dir '{}'",
+                tmp_dir.display()
+            )))
+            .once()
+            .returning(|_x| ());
+        mocked_logger.expect_stdout().once().returning(|_x| ());
+        let cli: Cli = Cli::new(
+            Box::new(mocked_logger),
+            Box::new(mocked_system),
+            clap::Command::new("bakery"),
+            Some(vec!["bakery"]),
+        );
+        let executer: BBCleanExecuter = BBCleanExecuter::new(&cli, &task_data, data.bitbake());
+        executer
+            .exec(&env_variables, true, true)
+            .expect("Failed to execute task");
+    }
+
+    #[test]
+    fn test_bitbake_clean_executer_shared_tmp_dir() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let env_variables: HashMap<String, String> = HashMap::new();
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "description": "Test Description",
+            "arch": "test-arch",
+            "bb": {
+                "machine": "raspberrypi3",
+                "distro": "strix",
+                "tmpdir": "/fast-disk/tmp"
+            }
+        }"#;
+        let json_task_config: &str = r#"
+        {
+            "index": "0",
+            "name": "task1-name",
+            "recipes": [
+                "test-image"
+            ]
+        }"#;
+        let data: WsBuildData = Helper::setup_build_data(&work_dir, Some(json_build_config), None);
+        let task_data: WsTaskData =
+            WsTaskData::from_str(json_task_config, &data).expect("Failed to parse task config");
+        let build_dir: PathBuf = work_dir.join("builds/default");
+        let tmp_dir: PathBuf = PathBuf::from("/fast-disk/tmp");
+        assert_eq!(data.bitbake().tmp_dir(), tmp_dir);
+        let mut mocked_system: MockSystem = MockSystem::new();
+        mocked_system
+            .expect_rmdir_all()
+            .with(mockall::predicate::eq(build_dir.clone()))
+            .once()
+            .returning(|_x| Ok(()));
+        let mut mocked_logger: MockLogger = MockLogger::new();
+        mocked_logger
+            .expect_info()
+            .with(mockall::predicate::eq(format!(
+                "execute bitbake clean task '{}'",
+                task_data.name()
+            )))
+            .once()
+            .returning(|_x| ());
+        mocked_logger
+            .expect_info()
+            .with(mockall::predicate::eq(format!(
+                "Removing bitbake build dir '{}'",
+                build_dir.display()
+            )))
+            .once()
+            .returning(|_x| ());
+        mocked_logger
+            .expect_warn()
+            .with(mockall::predicate::eq(format!(
+                "Bitbake tmp dir '{}' was not created by bakery for this build, skipping. Remove it manually if it is no longer needed",
+                tmp_dir.display()
+            )))
+            .once()
+            .returning(|_x| ());
+        mocked_logger.expect_stdout().once().returning(|_x| ());
+        let cli: Cli = Cli::new(
+            Box::new(mocked_logger),
+            Box::new(mocked_system),
+            clap::Command::new("bakery"),
+            Some(vec!["bakery"]),
+        );
+        let executer: BBCleanExecuter = BBCleanExecuter::new(&cli, &task_data, data.bitbake());
+        executer
+            .exec(&env_variables, true, true)
+            .expect("Failed to execute task");
+    }
 }
@@ -13,6 +13,7 @@ use crate::error::BError;
 pub struct Docker {
     image: DockerImage,
     _interactive: bool,
+    network: String,
 }
 
 #[derive(Clone)]
@@ -182,6 +183,13 @@ impl Docker {
         v
     }
 
+    fn network(&self) -> Vec<String> {
+        if self.network.is_empty() {
+            return vec![];
+        }
+        vec![String::from("--network"), self.network.clone()]
+    }
+
     fn container_name(&self, name: &str) -> Vec<String> {
         vec![
             String::from("--name"),
@@ -211,9 +219,19 @@ impl Docker {
         Docker {
             image,
             _interactive: interactive,
+            network: String::new(),
         }
     }
 
+    /*
+     * Set the network the container should be connected to. Setting it
+     * to 'none' will start the container without any network access
+     * which is used for hermetic builds.
+     */
+    pub fn set_network(&mut self, network: &str) {
+        self.network = network.to_string();
+    }
+
     pub fn bootstrap_cmd_line(
         &self,
         cmd_line: &Vec<String>,
@@ -229,6 +247,7 @@ impl Docker {
             docker_cmd.push("-i".to_string());
         }
         docker_cmd.append(&mut self.group());
+        docker_cmd.append(&mut self.network());
         docker_cmd.append(&mut self.volumes(volumes));
         docker_cmd.append(&mut self.user());
         docker_cmd.append(&mut self.top_dir(docker_top_dir));
@@ -257,6 +276,7 @@ impl Docker {
             docker_cmd.push("-i".to_string());
         }
         docker_cmd.append(&mut self.group());
+        docker_cmd.append(&mut self.network());
         docker_cmd.append(&mut self.env_file(env_file));
         docker_cmd.push(format!("{}", self.image));
         docker_cmd.append(&mut cmd_line.clone());
@@ -633,4 +653,26 @@ TEST_KEY1=TEST_VALUE1
             Helper::docker_cmdline_string(interactive, &work_dir, &image, &test_cmd, &env_file);
         assert_eq!(result, cmd_line);
     }
+
+    #[test]
+    fn test_docker_cmdline_network_none() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = PathBuf::from(temp_dir.path());
+        let env_file: PathBuf = work_dir.clone().join("test-docker.env");
+        let test_cmd: Vec<String> = vec![String::from("test")];
+        let image: DockerImage =
+            DockerImage::new("test-registry/test-image:0.1").expect("Invalid docker image format");
+        let mut docker: Docker = Docker::new(image.clone(), false);
+        docker.set_network("none");
+        let result: Vec<String> = docker.cmd_line(&test_cmd, &env_file, &work_dir);
+        let network: usize = result
+            .iter()
+            .position(|arg| arg == "--network")
+            .expect("Missing --network in docker command line");
+        assert_eq!(result[network + 1], "none");
+        let bootstrap: Vec<String> =
+            docker.bootstrap_cmd_line(&test_cmd, &work_dir, &work_dir, &vec![], &vec![]);
+        assert!(bootstrap.contains(&String::from("--network")));
+    }
 }
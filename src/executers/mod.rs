@@ -1,15 +1,21 @@
+pub mod bbquery;
 pub mod bitbake;
 pub mod customsubcmd;
 pub mod docker;
 pub mod nonbitbake;
 pub mod recipe;
+pub mod services;
+pub mod sigdiff;
 
+pub use bbquery::BBQuery;
 pub use bitbake::{BBBuildExecuter, BBCleanExecuter};
 pub use customsubcmd::CustomSubCmdExecuter;
 pub use docker::Docker;
 pub use docker::DockerImage;
 pub use nonbitbake::{NonBBBuildExecuter, NonBBCleanExecuter};
 pub use recipe::Recipe;
+pub use services::{BBService, BBServices};
+pub use sigdiff::BBSigDiff;
 
 use crate::error::BError;
 
@@ -38,7 +38,10 @@ impl<'a> TaskExecuter for NonBBCleanExecuter<'a> {
 
         if !docker_str.is_empty() {
             let image: DockerImage = DockerImage::new(docker_str)?;
-            let docker: Docker = Docker::new(image, interactive);
+            let mut docker: Docker = Docker::new(image, interactive);
+            if self.cli.hermetic() {
+                docker.set_network("none");
+            }
             docker.run_cmd(
                 &mut cmd_line,
                 args_env_variables,
@@ -92,7 +95,10 @@ impl<'a> TaskExecuter for NonBBBuildExecuter<'a> {
 
         if !self.task_data.docker_image().is_empty() && self.task_data.docker_image() != "NA" {
             let image: DockerImage = DockerImage::new(self.task_data.docker_image())?;
-            let docker: Docker = Docker::new(image, interactive);
+            let mut docker: Docker = Docker::new(image, interactive);
+            if self.cli.hermetic() {
+                docker.set_network("none");
+            }
             docker.run_cmd(&mut cmd_line, env_variables, exec_dir, &self.cli)?;
         } else {
             self.cli.check_call(&cmd_line, env_variables, true)?;
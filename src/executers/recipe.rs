@@ -35,6 +35,16 @@ impl Recipe {
         }
         cmd
     }
+
+    /*
+     * Fetch the sources for all the recipes in the taskgraph of the recipe
+     * without building anything
+     */
+    pub fn bitbake_fetch_cmd(&self) -> Vec<String> {
+        let mut cmd: Vec<String> = self.bitbake_cmd();
+        cmd.push("--runall=fetch".to_string());
+        cmd
+    }
 }
 
 #[cfg(test)]
@@ -73,4 +83,19 @@ mod tests {
             vec!["bitbake", "test-image", "-c", "do_test"]
         );
     }
+
+    #[test]
+    fn test_recipe_fetch() {
+        let recipe: Recipe = Recipe::new("test-image:sdk");
+        assert_eq!(
+            recipe.bitbake_fetch_cmd(),
+            vec![
+                "bitbake",
+                "test-image",
+                "-c",
+                "do_populate_sdk",
+                "--runall=fetch"
+            ]
+        );
+    }
 }
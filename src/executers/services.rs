@@ -0,0 +1,422 @@
+use crate::cli::Cli;
+use crate::data::{WsBitbakeData, WsBuildData};
+use crate::error::BError;
+use crate::workspace::WsSettingsHandler;
+
+use std::collections::HashMap;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BBService {
+    HashServ,
+    PRServ,
+}
+
+impl BBService {
+    pub fn name(&self) -> &str {
+        match self {
+            BBService::HashServ => "bitbake-hashserv",
+            BBService::PRServ => "bitbake-prserv",
+        }
+    }
+}
+
+/*
+ * Manage the bitbake hash equivalence server and PR server for the
+ * workspace. The servers are shared by all build configs in the workspace
+ * and the databases are kept in the cache dir. The servers are started
+ * in the background and the logs and the hash equivalence server pid
+ * file are kept in the services dir under the cache dir.
+ */
+pub struct BBServices<'a> {
+    cli: &'a Cli,
+    bb_data: &'a WsBitbakeData,
+    settings: &'a WsSettingsHandler,
+}
+
+impl<'a> BBServices<'a> {
+    /*
+     * bitbake-prserv is daemonizing itself so the pid of the started
+     * process is not the pid of the server. The server writes its own
+     * pid file to /tmp named after the resolved ip address of the host
+     * and the port.
+     */
+    fn prserv_ip(&self) -> String {
+        let addrs: Option<std::vec::IntoIter<SocketAddr>> =
+            format!("localhost:{}", self.settings.prserv_port())
+                .to_socket_addrs()
+                .ok();
+        addrs
+            .and_then(|mut a| a.find(|a| a.is_ipv4()))
+            .map(|a| a.ip().to_string())
+            .unwrap_or(String::from("127.0.0.1"))
+    }
+
+    fn pid_file(&self, service: &BBService) -> PathBuf {
+        match service {
+            BBService::HashServ => self.settings.services_dir().join("hashserv.pid"),
+            BBService::PRServ => PathBuf::from("/tmp").join(format!(
+                "PRServer_{}_{}.pid",
+                self.prserv_ip(),
+                self.settings.prserv_port()
+            )),
+        }
+    }
+
+    fn log_file(&self, service: &BBService) -> PathBuf {
+        match service {
+            BBService::HashServ => self.settings.services_dir().join("hashserv.log"),
+            BBService::PRServ => self.settings.services_dir().join("prserv.log"),
+        }
+    }
+
+    fn pid(&self, service: &BBService) -> Option<u32> {
+        match std::fs::read_to_string(self.pid_file(service)) {
+            Ok(content) => content.trim().parse::<u32>().ok(),
+            Err(_err) => None,
+        }
+    }
+
+    fn env(&self) -> Result<HashMap<String, String>, BError> {
+        self.cli
+            .source_init_env(&self.bb_data.init_env_file(), &self.bb_data.build_dir())
+    }
+
+    fn start_cmd(&self, service: &BBService) -> Vec<String> {
+        let mut cmd_line: Vec<String> = vec![
+            "cd".to_string(),
+            self.settings.services_dir().to_string_lossy().to_string(),
+            "&&".to_string(),
+        ];
+        match service {
+            BBService::HashServ => {
+                cmd_line.append(&mut vec![
+                    "nohup".to_string(),
+                    "bitbake-hashserv".to_string(),
+                    "--bind".to_string(),
+                    self.settings.hashserv_bind(),
+                    "--database".to_string(),
+                    self.settings.hashserv_db().to_string_lossy().to_string(),
+                ]);
+                if !self.settings.cache_hashserve().is_empty() {
+                    cmd_line.append(&mut vec![
+                        "--upstream".to_string(),
+                        self.settings.cache_hashserve().to_string(),
+                    ]);
+                }
+                cmd_line.append(&mut vec![
+                    ">".to_string(),
+                    self.log_file(service).to_string_lossy().to_string(),
+                    "2>&1".to_string(),
+                    "&".to_string(),
+                    "echo".to_string(),
+                    "$!".to_string(),
+                    ">".to_string(),
+                    self.pid_file(service).to_string_lossy().to_string(),
+                ]);
+            }
+            BBService::PRServ => {
+                cmd_line.append(&mut vec![
+                    "bitbake-prserv".to_string(),
+                    "--start".to_string(),
+                    "--host".to_string(),
+                    "localhost".to_string(),
+                    "--port".to_string(),
+                    self.settings.prserv_port().to_string(),
+                    "--file".to_string(),
+                    self.settings.prserv_db().to_string_lossy().to_string(),
+                    "--log".to_string(),
+                    self.log_file(service).to_string_lossy().to_string(),
+                ]);
+            }
+        }
+        cmd_line
+    }
+
+    fn stop_cmd(&self, service: &BBService) -> Result<Vec<String>, BError> {
+        match service {
+            BBService::HashServ => match self.pid(service) {
+                Some(pid) => Ok(vec!["kill".to_string(), pid.to_string()]),
+                None => Err(BError::ValueError(format!(
+                    "No pid found for {} in '{}'",
+                    service.name(),
+                    self.pid_file(service).display()
+                ))),
+            },
+            BBService::PRServ => Ok(vec![
+                "cd".to_string(),
+                self.settings.services_dir().to_string_lossy().to_string(),
+                "&&".to_string(),
+                "bitbake-prserv".to_string(),
+                "--stop".to_string(),
+                "--host".to_string(),
+                "localhost".to_string(),
+                "--port".to_string(),
+                self.settings.prserv_port().to_string(),
+            ]),
+        }
+    }
+
+    pub fn enabled(&self) -> Vec<BBService> {
+        let mut services: Vec<BBService> = vec![];
+        if self.settings.services_hashserv() {
+            services.push(BBService::HashServ);
+        }
+        if self.settings.services_prserv() {
+            services.push(BBService::PRServ);
+        }
+        services
+    }
+
+    pub fn is_running(&self, service: &BBService) -> bool {
+        match self.pid(service) {
+            Some(pid) => PathBuf::from(format!("/proc/{}", pid)).exists(),
+            None => false,
+        }
+    }
+
+    /*
+     * Start all the services enabled in the workspace settings that are not
+     * already running. Returns the services that were started so the caller
+     * can stop them again once done.
+     */
+    pub fn start(&self) -> Result<Vec<BBService>, BError> {
+        let mut started: Vec<BBService> = vec![];
+        let services: Vec<BBService> = self
+            .enabled()
+            .into_iter()
+            .filter(|s| !self.is_running(s))
+            .collect();
+
+        if services.is_empty() {
+            return Ok(started);
+        }
+
+        std::fs::create_dir_all(self.settings.services_dir())?;
+        let env: HashMap<String, String> = self.env()?;
+        for service in services {
+            self.cli.info(format!("Starting {}", service.name()));
+            self.cli.check_call(&self.start_cmd(&service), &env, true)?;
+            started.push(service);
+        }
+        Ok(started)
+    }
+
+    pub fn stop(&self, services: &Vec<BBService>) -> Result<(), BError> {
+        let services: Vec<&BBService> = services.iter().filter(|s| self.is_running(s)).collect();
+
+        if services.is_empty() {
+            return Ok(());
+        }
+
+        let env: HashMap<String, String> = self.env()?;
+        for service in services {
+            self.cli.info(format!("Stopping {}", service.name()));
+            self.cli.check_call(&self.stop_cmd(service)?, &env, true)?;
+            if self.pid_file(service).exists() {
+                std::fs::remove_file(self.pid_file(service))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn status(&self) -> Result<(), BError> {
+        let services: Vec<BBService> = self.enabled();
+
+        if services.is_empty() {
+            self.cli
+                .info("No services enabled in the workspace settings".to_string());
+            return Ok(());
+        }
+
+        for service in services {
+            let address: String = match service {
+                BBService::HashServ => self.settings.hashserv_bind(),
+                BBService::PRServ => self.settings.prserv_host(),
+            };
+            match self.pid(&service) {
+                Some(pid) if self.is_running(&service) => {
+                    self.cli.stdout(format!(
+                        "{}: running (pid {}) on '{}'",
+                        service.name(),
+                        pid,
+                        address
+                    ));
+                }
+                _ => {
+                    self.cli.stdout(format!("{}: stopped", service.name()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn new(cli: &'a Cli, build_data: &'a WsBuildData) -> Self {
+        BBServices {
+            cli,
+            bb_data: build_data.bitbake(),
+            settings: build_data.settings(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    use crate::cli::{BLogger, CallParams, Cli, MockSystem};
+    use crate::data::WsBuildData;
+    use crate::executers::{BBService, BBServices};
+    use crate::helper::Helper;
+
+    #[test]
+    fn test_bitbake_services_start() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let json_settings: &str = r#"
+        {
+            "version": "6",
+            "cache": {
+                "hashserve": "ws://hashserv.example.com:8686"
+            },
+            "services": {
+                "hashserv": "true",
+                "prserv": "true"
+            }
+        }"#;
+        let json_build_config: &str = r#"
+        {
+            "version": "6",
+            "name": "default",
+            "arch": "test-arch",
+            "bb": {}
+        }"#;
+        let data: WsBuildData =
+            Helper::setup_build_data(&work_dir, Some(json_build_config), Some(json_settings));
+        let services_dir: String = work_dir.join(".cache/services").display().to_string();
+        let mut mocked_system: MockSystem = MockSystem::new();
+        mocked_system
+            .expect_init_env_file()
+            .returning(|_x, _y| Ok(HashMap::new()));
+        mocked_system
+            .expect_check_call()
+            .with(mockall::predicate::eq(CallParams {
+                cmd_line: vec![
+                    "cd".to_string(),
+                    services_dir.clone(),
+                    "&&".to_string(),
+                    "nohup".to_string(),
+                    "bitbake-hashserv".to_string(),
+                    "--bind".to_string(),
+                    format!("unix://{}/hashserve.sock", services_dir),
+                    "--database".to_string(),
+                    work_dir.join(".cache/hashserv.db").display().to_string(),
+                    "--upstream".to_string(),
+                    "ws://hashserv.example.com:8686".to_string(),
+                    ">".to_string(),
+                    format!("{}/hashserv.log", services_dir),
+                    "2>&1".to_string(),
+                    "&".to_string(),
+                    "echo".to_string(),
+                    "$!".to_string(),
+                    ">".to_string(),
+                    format!("{}/hashserv.pid", services_dir),
+                ],
+                env: HashMap::new(),
+                shell: true,
+            }))
+            .once()
+            .returning(|_x| Ok(()));
+        mocked_system
+            .expect_check_call()
+            .with(mockall::predicate::eq(CallParams {
+                cmd_line: vec![
+                    "cd".to_string(),
+                    services_dir.clone(),
+                    "&&".to_string(),
+                    "bitbake-prserv".to_string(),
+                    "--start".to_string(),
+                    "--host".to_string(),
+                    "localhost".to_string(),
+                    "--port".to_string(),
+                    "8585".to_string(),
+                    "--file".to_string(),
+                    work_dir.join(".cache/prserv.sqlite3").display().to_string(),
+                    "--log".to_string(),
+                    format!("{}/prserv.log", services_dir),
+                ],
+                env: HashMap::new(),
+                shell: true,
+            }))
+            .once()
+            .returning(|_x| Ok(()));
+        let cli: Cli = Cli::new(
+            Box::new(BLogger::new()),
+            Box::new(mocked_system),
+            clap::Command::new("bakery"),
+            Some(vec!["bakery"]),
+        );
+        let services: BBServices = BBServices::new(&cli, &data);
+        let started: Vec<BBService> = services.start().expect("Failed to start services");
+        assert_eq!(started, vec![BBService::HashServ, BBService::PRServ]);
+        assert!(work_dir.join(".cache/services").is_dir());
+        assert!(data.bitbake().local_conf().contains(&format!(
+            "BB_HASHSERVE ?= \"unix://{}/hashserve.sock\"\n",
+            services_dir
+        )));
+        assert!(data
+            .bitbake()
+            .local_conf()
+            .contains("PRSERV_HOST ?= \"localhost:8585\"\n"));
+    }
+
+    #[test]
+    fn test_bitbake_services_disabled() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let data: WsBuildData = Helper::setup_build_data(&work_dir, None, None);
+        let mocked_system: MockSystem = MockSystem::new();
+        let cli: Cli = Cli::new(
+            Box::new(BLogger::new()),
+            Box::new(mocked_system),
+            clap::Command::new("bakery"),
+            Some(vec!["bakery"]),
+        );
+        let services: BBServices = BBServices::new(&cli, &data);
+        assert!(services.enabled().is_empty());
+        assert!(services
+            .start()
+            .expect("Failed to start services")
+            .is_empty());
+        services.stop(&vec![]).expect("Failed to stop services");
+    }
+
+    #[test]
+    fn test_bitbake_services_pid_file() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: PathBuf = temp_dir.into_path();
+        let data: WsBuildData = Helper::setup_build_data(&work_dir, None, None);
+        let cli: Cli = Cli::new(
+            Box::new(BLogger::new()),
+            Box::new(MockSystem::new()),
+            clap::Command::new("bakery"),
+            Some(vec!["bakery"]),
+        );
+        let services: BBServices = BBServices::new(&cli, &data);
+        assert_eq!(
+            services.pid_file(&BBService::PRServ),
+            PathBuf::from("/tmp/PRServer_127.0.0.1_8585.pid")
+        );
+        assert_eq!(
+            services.pid_file(&BBService::HashServ),
+            work_dir.join(".cache/services/hashserv.pid")
+        );
+        assert!(services.stop_cmd(&BBService::HashServ).is_err());
+    }
+}
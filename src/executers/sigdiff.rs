@@ -0,0 +1,351 @@
+use crate::cli::Cli;
+use crate::data::WsBitbakeData;
+use crate::error::BError;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/*
+ * Compare the last two signatures of a recipe task to find out why the
+ * task was executed again instead of being restored from the sstate
+ * cache. The signatures are written by bitbake as sigdata files in the
+ * stamps dir under TMPDIR and as siginfo files next to the sstate objects
+ * in the sstate cache.
+ */
+pub struct BBSigDiff<'a> {
+    cli: &'a Cli,
+    bb_data: &'a WsBitbakeData,
+}
+
+impl<'a> BBSigDiff<'a> {
+    fn env(&self) -> Result<HashMap<String, String>, BError> {
+        self.cli
+            .source_init_env(&self.bb_data.init_env_file(), &self.bb_data.build_dir())
+    }
+
+    fn collect_files(
+        dir: &PathBuf,
+        depth: usize,
+        matches: &dyn Fn(&PathBuf) -> bool,
+        files: &mut Vec<PathBuf>,
+    ) -> Result<(), BError> {
+        if !dir.is_dir() {
+            return Ok(());
+        }
+
+        for entry in std::fs::read_dir(dir)? {
+            let path: PathBuf = entry?.path();
+            if path.is_dir() {
+                if depth > 0 {
+                    Self::collect_files(&path, depth - 1, matches, files)?;
+                }
+            } else if matches(&path) {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    /*
+     * The sigdata files in the stamps dir are stored in a dir named after
+     * the recipe <TMPDIR>/stamps/<arch>/<recipe>/<version>.<task>.sigdata.<hash>
+     */
+    fn is_stamp_sigdata(path: &PathBuf, recipe: &str, task: &str) -> bool {
+        let name: String = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let parent: String = match path.parent().and_then(|p| p.file_name()) {
+            Some(parent) => parent.to_string_lossy().to_string(),
+            None => String::new(),
+        };
+        parent == recipe && name.contains(&format!(".{}.sigdata.", task))
+    }
+
+    /*
+     * The siginfo files in the sstate cache are using the sstate object name
+     * sstate:<recipe>:<...>:<hash>_<task without do_>.<ext>.siginfo
+     */
+    fn is_sstate_siginfo(path: &PathBuf, recipe: &str, task: &str) -> bool {
+        let name: String = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let sstate_task: &str = task.strip_prefix("do_").unwrap_or(task);
+        name.starts_with(&format!("sstate:{}:", recipe))
+            && name.contains(&format!("_{}.", sstate_task))
+            && name.ends_with(".siginfo")
+    }
+
+    /*
+     * The hash of the task signature is part of the file name for both the
+     * sigdata files and the siginfo files. The same signature can be found
+     * in both the stamps dir and the sstate cache.
+     */
+    fn signature_hash(path: &PathBuf) -> String {
+        let name: String = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        if let Some((_prefix, hash)) = name.split_once(".sigdata.") {
+            return hash.to_string();
+        }
+        match name.rsplit_once(':') {
+            Some((_prefix, object)) => object.split('_').next().unwrap_or_default().to_string(),
+            None => name,
+        }
+    }
+
+    /*
+     * Sort the signatures with the oldest signature first and only keep
+     * the newest file for each signature hash.
+     */
+    fn unique_signatures(mut signatures: Vec<(SystemTime, PathBuf)>) -> Vec<PathBuf> {
+        signatures.sort();
+        let mut hashes: Vec<String> = vec![];
+        let mut unique: Vec<PathBuf> = vec![];
+        for (_modified, path) in signatures.into_iter().rev() {
+            let hash: String = Self::signature_hash(&path);
+            if !hashes.contains(&hash) {
+                hashes.push(hash);
+                unique.push(path);
+            }
+        }
+        unique.reverse();
+        unique
+    }
+
+    /*
+     * Split the argument into recipe and task. The task is optional and
+     * can be specified with or without the do_ prefix.
+     */
+    pub fn recipe_task(target: &str) -> (String, String) {
+        match target.split_once(':') {
+            Some((recipe, task)) if !task.is_empty() => {
+                let task: String = match task.starts_with("do_") {
+                    true => task.to_string(),
+                    false => format!("do_{}", task),
+                };
+                (recipe.to_string(), task)
+            }
+            _ => (
+                target.trim_end_matches(':').to_string(),
+                String::from("do_build"),
+            ),
+        }
+    }
+
+    /*
+     * Find all the signatures for the recipe task sorted with the
+     * oldest signature first. The stamps are only searched in the dirs
+     * for the recipe <TMPDIR>/stamps/<arch>/<recipe> and the sstate
+     * objects are only searched for in the hash dirs of the sstate cache
+     * <SSTATE_DIR>/<xx>/<yy>/sstate:<recipe>:* the same as bitbake.
+     */
+    pub fn signatures(&self, recipe: &str, task: &str) -> Result<Vec<PathBuf>, BError> {
+        let mut files: Vec<PathBuf> = vec![];
+        let stamps_dir: PathBuf = self.bb_data.tmp_dir().join("stamps");
+        if stamps_dir.is_dir() {
+            for entry in std::fs::read_dir(&stamps_dir)? {
+                Self::collect_files(
+                    &entry?.path().join(recipe),
+                    0,
+                    &|p| Self::is_stamp_sigdata(p, recipe, task),
+                    &mut files,
+                )?;
+            }
+        }
+        Self::collect_files(
+            &self.bb_data.sstate_dir(),
+            2,
+            &|p| Self::is_sstate_siginfo(p, recipe, task),
+            &mut files,
+        )?;
+
+        let mut signatures: Vec<(SystemTime, PathBuf)> = vec![];
+        for path in files {
+            let modified: SystemTime = std::fs::metadata(&path)?.modified()?;
+            signatures.push((modified, path));
+        }
+        Ok(Self::unique_signatures(signatures))
+    }
+
+    /*
+     * Pick out the lines from the bitbake-diffsigs output describing what
+     * has changed and group them by changed variables, changed dependencies
+     * and anything else that has changed.
+     */
+    pub fn summary(output: &str) -> Vec<String> {
+        let mut variables: Vec<String> = vec![];
+        let mut dependencies: Vec<String> = vec![];
+        let mut other: Vec<String> = vec![];
+
+        for line in output.lines() {
+            let line: &str = line.trim();
+            let lower: String = line.to_lowercase();
+            if line.is_empty() || line.starts_with("NOTE:") {
+                continue;
+            }
+
+            if lower.contains("depend") {
+                dependencies.push(line.to_string());
+            } else if lower.starts_with("variable ") {
+                variables.push(line.to_string());
+            } else if lower.contains(" changed ")
+                || lower.contains(" added")
+                || lower.contains(" removed")
+            {
+                other.push(line.to_string());
+            }
+        }
+
+        let mut summary: Vec<String> = vec![];
+        for (title, lines) in [
+            ("Changed variables:", variables),
+            ("Changed dependencies:", dependencies),
+            ("Other changes:", other),
+        ] {
+            if lines.is_empty() {
+                continue;
+            }
+            summary.push(title.to_string());
+            summary.extend(lines.into_iter().map(|l| format!("  {}", l)));
+        }
+
+        if summary.is_empty() {
+            summary.push(String::from("No differences found"));
+        }
+        summary
+    }
+
+    /*
+     * Run bitbake-diffsigs on the last two signatures of the recipe task
+     * and return the output.
+     */
+    pub fn diff(&self, recipe: &str, task: &str) -> Result<(Vec<PathBuf>, String), BError> {
+        let signatures: Vec<PathBuf> = self.signatures(recipe, task)?;
+        if signatures.len() < 2 {
+            return Err(BError::CliError(format!(
+                "Found {} signature(s) for '{}:{}' at least two are needed to compare",
+                signatures.len(),
+                recipe,
+                task
+            )));
+        }
+
+        let compare: Vec<PathBuf> = signatures[signatures.len() - 2..].to_vec();
+        let cmd_line: Vec<String> = vec![
+            "cd".to_string(),
+            self.bb_data.build_dir().to_string_lossy().to_string(),
+            "&&".to_string(),
+            "bitbake-diffsigs".to_string(),
+            compare[0].to_string_lossy().to_string(),
+            compare[1].to_string_lossy().to_string(),
+        ];
+        let output: String = self.cli.check_output(&cmd_line, &self.env()?, true)?;
+        Ok((compare, output))
+    }
+
+    pub fn new(cli: &'a Cli, bb_data: &'a WsBitbakeData) -> Self {
+        BBSigDiff { cli, bb_data }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+
+    use crate::executers::BBSigDiff;
+
+    #[test]
+    fn test_sigdiff_recipe_task() {
+        assert_eq!(
+            BBSigDiff::recipe_task("busybox"),
+            (String::from("busybox"), String::from("do_build"))
+        );
+        assert_eq!(
+            BBSigDiff::recipe_task("busybox:compile"),
+            (String::from("busybox"), String::from("do_compile"))
+        );
+        assert_eq!(
+            BBSigDiff::recipe_task("busybox:do_install"),
+            (String::from("busybox"), String::from("do_install"))
+        );
+    }
+
+    #[test]
+    fn test_sigdiff_signature_files() {
+        assert!(BBSigDiff::is_stamp_sigdata(
+            &PathBuf::from(
+                "/tmp/stamps/core2-64-poky-linux/busybox/1.36.1-r0.do_compile.sigdata.abc123"
+            ),
+            "busybox",
+            "do_compile"
+        ));
+        assert!(!BBSigDiff::is_stamp_sigdata(
+            &PathBuf::from(
+                "/tmp/stamps/core2-64-poky-linux/busybox/1.36.1-r0.do_install.sigdata.abc123"
+            ),
+            "busybox",
+            "do_compile"
+        ));
+        assert!(BBSigDiff::is_sstate_siginfo(
+            &PathBuf::from("/sstate/ab/cd/sstate:busybox:core2-64-poky-linux:1.36.1:r0:core2-64:11:abcd_populate_sysroot.tar.zst.siginfo"),
+            "busybox",
+            "do_populate_sysroot"
+        ));
+        assert!(!BBSigDiff::is_sstate_siginfo(
+            &PathBuf::from("/sstate/ab/cd/sstate:busybox:core2-64-poky-linux:1.36.1:r0:core2-64:11:abcd_populate_sysroot.tar.zst"),
+            "busybox",
+            "do_populate_sysroot"
+        ));
+    }
+
+    #[test]
+    fn test_sigdiff_unique_signatures() {
+        let stamp: PathBuf = PathBuf::from(
+            "/tmp/stamps/core2-64-poky-linux/busybox/1.36.1-r0.do_populate_sysroot.sigdata.abcd",
+        );
+        let sstate: PathBuf = PathBuf::from("/sstate/ab/cd/sstate:busybox:core2-64-poky-linux:1.36.1:r0:core2-64:11:abcd_populate_sysroot.tar.zst.siginfo");
+        let older: PathBuf = PathBuf::from("/sstate/12/34/sstate:busybox:core2-64-poky-linux:1.36.1:r0:core2-64:11:1234_populate_sysroot.tar.zst.siginfo");
+        assert_eq!(BBSigDiff::signature_hash(&stamp), "abcd");
+        assert_eq!(BBSigDiff::signature_hash(&sstate), "abcd");
+        let now: SystemTime = SystemTime::now();
+        assert_eq!(
+            BBSigDiff::unique_signatures(vec![
+                (now, stamp.clone()),
+                (now - Duration::from_secs(10), sstate),
+                (now - Duration::from_secs(20), older.clone()),
+            ]),
+            vec![older, stamp]
+        );
+    }
+
+    #[test]
+    fn test_sigdiff_summary() {
+        let output: &str = r#"NOTE: Starting bitbake server...
+basehash changed from 1234 to 5678
+Variable EXTRA_OEMAKE value changed from '-j 8' to '-j 16'
+List of dependencies for variable do_compile changed from '{'CC'}' to '{'CC', 'EXTRA_OEMAKE'}'
+Hash for task dependency zlib:do_populate_sysroot changed from aaaa to bbbb
+"#;
+        assert_eq!(
+            BBSigDiff::summary(output),
+            vec![
+                "Changed variables:",
+                "  Variable EXTRA_OEMAKE value changed from '-j 8' to '-j 16'",
+                "Changed dependencies:",
+                "  List of dependencies for variable do_compile changed from '{'CC'}' to '{'CC', 'EXTRA_OEMAKE'}'",
+                "  Hash for task dependency zlib:do_populate_sysroot changed from aaaa to bbbb",
+                "Other changes:",
+                "  basehash changed from 1234 to 5678",
+            ]
+        );
+        assert_eq!(BBSigDiff::summary(""), vec!["No differences found"]);
+    }
+}
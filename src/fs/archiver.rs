@@ -1,15 +1,71 @@
+use chrono::{Datelike, Timelike};
+use std::collections::HashSet;
 use std::fs::File;
+use std::io::{Read, Write};
+use std::os::unix::fs::MetadataExt;
 use std::path::{Path, PathBuf};
 use zip::{write::FileOptions, ZipWriter};
 
 use crate::error::BError;
 
+/*
+ * Encoder compressing everything written to it. The compressed stream is
+ * only complete after calling finish which is also returning any error
+ * from writing the end of the stream, something that is lost when the
+ * encoder is only dropped.
+ */
+pub enum Compressor {
+    None(File),
+    Gz(flate2::write::GzEncoder<File>),
+    Bz2(bzip2::write::BzEncoder<File>),
+    Xz(xz2::write::XzEncoder<File>),
+    Zst(zstd::Encoder<'static, File>),
+}
+
+impl Write for Compressor {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Compressor::None(w) => w.write(buf),
+            Compressor::Gz(w) => w.write(buf),
+            Compressor::Bz2(w) => w.write(buf),
+            Compressor::Xz(w) => w.write(buf),
+            Compressor::Zst(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Compressor::None(w) => w.flush(),
+            Compressor::Gz(w) => w.flush(),
+            Compressor::Bz2(w) => w.flush(),
+            Compressor::Xz(w) => w.flush(),
+            Compressor::Zst(w) => w.flush(),
+        }
+    }
+}
+
+impl Compressor {
+    pub fn finish(self) -> Result<(), BError> {
+        let file: File = match self {
+            Compressor::None(w) => w,
+            Compressor::Gz(w) => w.finish()?,
+            Compressor::Bz2(w) => w.finish()?,
+            Compressor::Xz(w) => w.finish()?,
+            Compressor::Zst(w) => w.finish()?,
+        };
+        file.sync_all()?;
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct Archiver {
     path: PathBuf,
     name: String,
     extension: String,
     compression: String,
+    level: Option<u32>,
+    source_date_epoch: Option<u64>,
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -18,6 +74,121 @@ enum Mode {
     Write,
 }
 
+/*
+ * Writer for the cpio "newc" format. Each entry is a header of 6 + 13 * 8
+ * ascii characters followed by the name, both padded to 4 bytes, and then
+ * the content of the file also padded to 4 bytes. The archive ends with
+ * an entry named TRAILER!!!.
+ */
+struct CpioWriter<W: Write> {
+    writer: W,
+    ino: u32,
+    dirs: HashSet<PathBuf>,
+    source_date_epoch: Option<u32>,
+}
+
+impl<W: Write> CpioWriter<W> {
+    fn pad(&mut self, len: usize) -> Result<(), BError> {
+        let padding: usize = (4 - len % 4) % 4;
+        self.writer.write_all(&vec![0u8; padding])?;
+        Ok(())
+    }
+
+    fn header(&mut self, name: &str, mode: u32, mtime: u32, size: u32) -> Result<(), BError> {
+        self.ino += 1;
+        let nlink: u32 = match mode & 0o170000 {
+            0o040000 => 2,
+            _ => 1,
+        };
+        let header: String = format!(
+            "070701{:08x}{:08x}{:08x}{:08x}{:08x}{:08x}{:08x}{:08x}{:08x}{:08x}{:08x}{:08x}{:08x}",
+            self.ino,
+            mode,
+            0,
+            0,
+            nlink,
+            mtime,
+            size,
+            0,
+            0,
+            0,
+            0,
+            name.len() + 1,
+            0
+        );
+        self.writer.write_all(header.as_bytes())?;
+        self.writer.write_all(name.as_bytes())?;
+        self.writer.write_all(&[0u8])?;
+        self.pad(header.len() + name.len() + 1)
+    }
+
+    fn append_dir(&mut self, dir: &Path, mtime: u32) -> Result<(), BError> {
+        if dir.as_os_str().is_empty() || self.dirs.contains(dir) {
+            return Ok(());
+        }
+        if let Some(parent) = dir.parent() {
+            self.append_dir(parent, mtime)?;
+        }
+        self.header(&dir.to_string_lossy(), 0o040755, mtime, 0)?;
+        self.dirs.insert(dir.to_path_buf());
+        Ok(())
+    }
+
+    fn append_file(&mut self, name: &Path, path: &PathBuf) -> Result<(), BError> {
+        let metadata: std::fs::Metadata = std::fs::symlink_metadata(path)?;
+        if metadata.file_type().is_symlink() {
+            let target: String = std::fs::read_link(path)?.to_string_lossy().to_string();
+            let mtime: u32 = self
+                .source_date_epoch
+                .unwrap_or(metadata.mtime().max(0) as u32);
+            if let Some(parent) = name.parent() {
+                self.append_dir(parent, mtime)?;
+            }
+            self.header(
+                &name.to_string_lossy(),
+                0o120777,
+                mtime,
+                target.len() as u32,
+            )?;
+            self.writer.write_all(target.as_bytes())?;
+            return self.pad(target.len());
+        }
+
+        let size: u32 = u32::try_from(metadata.len()).map_err(|_err| {
+            BError::ArchiverError(format!(
+                "File '{}' is to large for a cpio archive!",
+                path.display()
+            ))
+        })?;
+        let (mode, mtime): (u32, u32) = match self.source_date_epoch {
+            Some(epoch) => (0o100000 | Archiver::normalized_mode(metadata.mode()), epoch),
+            None => (metadata.mode(), metadata.mtime().max(0) as u32),
+        };
+        if let Some(parent) = name.parent() {
+            self.append_dir(parent, mtime)?;
+        }
+        self.header(&name.to_string_lossy(), mode, mtime, size)?;
+        let mut file: File = File::open(path)?;
+        std::io::copy(&mut file, &mut self.writer)?;
+        self.pad(size as usize)
+    }
+
+    fn finish(mut self) -> Result<W, BError> {
+        self.header("TRAILER!!!", 0, 0, 0)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn new(writer: W, source_date_epoch: Option<u64>) -> Self {
+        CpioWriter {
+            writer,
+            ino: 0,
+            dirs: HashSet::new(),
+            source_date_epoch: source_date_epoch.map(|e| e.min(u32::MAX as u64) as u32),
+        }
+    }
+}
+
 impl Archiver {
     pub fn new(path: &PathBuf) -> Result<Self, BError> {
         let archive_name = path.file_name().unwrap_or_default().to_string_lossy();
@@ -49,12 +220,27 @@ impl Archiver {
                 "tar" if i + 1 < suffixes.len() => {
                     archive_type = "tar".to_string();
                     match suffixes[i + 1] {
-                        "gz" => {
-                            compression = "gz".to_string();
+                        "gz" | "bz2" | "xz" | "zst" => {
+                            compression = suffixes[i + 1].to_string();
                             break;
                         }
-                        "bz2" => {
-                            compression = "bz2".to_string();
+                        _ => {
+                            return Err(BError::ArchiverError(format!(
+                                "Unsupported compression '{}'!",
+                                suffixes[i + 1]
+                            )));
+                        }
+                    }
+                }
+                "tar" | "cpio" if i + 1 == suffixes.len() => {
+                    archive_type = suffixes[i].to_string();
+                    break;
+                }
+                "cpio" if i + 1 < suffixes.len() => {
+                    archive_type = "cpio".to_string();
+                    match suffixes[i + 1] {
+                        "gz" => {
+                            compression = "gz".to_string();
                             break;
                         }
                         _ => {
@@ -76,17 +262,13 @@ impl Archiver {
             )));
         }
 
-        if archive_type == "tar" && compression.is_empty() {
-            return Err(BError::ArchiverError(
-                "Archive must have a compression!".to_string(),
-            ));
-        }
-
         Ok(Archiver {
             path: path.clone(),
             name,
             extension: archive_type,
             compression,
+            level: None,
+            source_date_epoch: None,
         })
     }
 
@@ -106,6 +288,144 @@ impl Archiver {
         &self.compression
     }
 
+    #[cfg(test)]
+    pub fn level(&self) -> Option<u32> {
+        self.level
+    }
+
+    /*
+     * The valid compression levels depends on the compression, gz, bz2
+     * and xz are using 0-9 where bz2 is starting at 1 and zst is using 1-22.
+     */
+    pub fn level_range(compression: &str) -> Option<(u32, u32)> {
+        match compression {
+            "gz" | "xz" => Some((0, 9)),
+            "bz2" => Some((1, 9)),
+            "zst" => Some((1, 22)),
+            _ => None,
+        }
+    }
+
+    /*
+     * Set the compression level used when creating the archive. A zip
+     * archive is using the same levels as gz.
+     */
+    pub fn set_level(&mut self, level: u32) -> Result<(), BError> {
+        let range: Option<(u32, u32)> = match self.extension() {
+            "zip" => Self::level_range("gz"),
+            _ => Self::level_range(self.compression()),
+        };
+        let (min, max): (u32, u32) = match range {
+            Some(range) => range,
+            None => {
+                return Err(BError::ArchiverError(format!(
+                    "Compression level is not supported for '{}'!",
+                    self.name
+                )));
+            }
+        };
+
+        if level < min || level > max {
+            return Err(BError::ArchiverError(format!(
+                "Invalid compression level {} for '{}' expected {}-{}!",
+                level, self.name, min, max
+            )));
+        }
+
+        self.level = Some(level);
+        Ok(())
+    }
+
+    /*
+     * Make the archive reproducible. Rebuilding an archive from the same
+     * files will then result in exactly the same archive. The entries are
+     * sorted by path, all entries are using the source date epoch as
+     * mtime, the owner is set to root and the permissions are set to 0644
+     * or 0755 for files that are executable.
+     */
+    pub fn set_reproducible(&mut self, source_date_epoch: u64) {
+        self.source_date_epoch = Some(source_date_epoch);
+    }
+
+    fn normalized_mode(mode: u32) -> u32 {
+        match mode & 0o111 {
+            0 => 0o644,
+            _ => 0o755,
+        }
+    }
+
+    fn zip_time(source_date_epoch: u64) -> zip::DateTime {
+        /*
+         * A zip archive can only store dates from 1980 so any earlier
+         * date is using the earliest date possible
+         */
+        let time: chrono::NaiveDateTime =
+            match chrono::DateTime::from_timestamp(source_date_epoch as i64, 0) {
+                Some(time) => time.naive_utc(),
+                None => return zip::DateTime::default(),
+            };
+        zip::DateTime::from_date_and_time(
+            time.year() as u16,
+            time.month() as u8,
+            time.day() as u8,
+            time.hour() as u8,
+            time.minute() as u8,
+            time.second() as u8,
+        )
+        .unwrap_or_default()
+    }
+
+    /*
+     * Create an encoder compressing everything written to the file. The
+     * encoder is also used for compressing files that are not archives
+     * like disk images.
+     */
+    pub fn compressor(
+        file: File,
+        compression: &str,
+        level: Option<u32>,
+    ) -> Result<Compressor, BError> {
+        match compression {
+            "" => Ok(Compressor::None(file)),
+            "gz" => {
+                let level: flate2::Compression = match level {
+                    Some(level) => flate2::Compression::new(level),
+                    None => flate2::Compression::default(),
+                };
+                /*
+                 * The gzip header is not including any file name or timestamp
+                 * so the compressed archive is only depending on the content
+                 */
+                Ok(Compressor::Gz(
+                    flate2::GzBuilder::new().mtime(0).write(file, level),
+                ))
+            }
+            "bz2" => {
+                let level: bzip2::Compression = match level {
+                    Some(level) => bzip2::Compression::new(level),
+                    None => bzip2::Compression::default(),
+                };
+                Ok(Compressor::Bz2(bzip2::write::BzEncoder::new(file, level)))
+            }
+            "xz" => Ok(Compressor::Xz(xz2::write::XzEncoder::new(
+                file,
+                level.unwrap_or(6),
+            ))),
+            "zst" => {
+                let level: i32 = level.unwrap_or(0) as i32;
+                Ok(Compressor::Zst(zstd::Encoder::new(file, level)?))
+            }
+            _ => Err(BError::ArchiverError(format!(
+                "Unsupported compression '{}'!",
+                compression
+            ))),
+        }
+    }
+
+    fn encoder(&self, archive_file: File) -> Result<Compressor, BError> {
+        Self::compressor(archive_file, self.compression(), self.level)
+    }
+
     pub fn add_files(&self, files: &Vec<PathBuf>, work_dir: &Path) -> Result<(), BError> {
         let mut mode: Mode = Mode::Write;
 
@@ -123,37 +443,73 @@ impl Archiver {
         }
 
         let archive_file: File = File::create(&self.path)?;
+        let mut entries: Vec<(PathBuf, &PathBuf)> = vec![];
+        for path in files {
+            let striped_path: PathBuf = path.strip_prefix(work_dir.as_os_str())?.to_path_buf();
+            entries.push((striped_path, path));
+        }
+        if self.source_date_epoch.is_some() {
+            entries.sort();
+        }
 
         if self.extension() == "tar" {
             if mode == Mode::Append {}
 
-            let enc: Box<dyn std::io::Write>;
-            let mut tar: tar::Builder<Box<dyn std::io::Write>>;
-            if self.compression() == "gz" {
-                enc = Box::new(flate2::write::GzEncoder::new(
-                    archive_file,
-                    flate2::Compression::default(),
-                ));
-            } else if self.compression() == "bz2" {
-                enc = Box::new(bzip2::write::BzEncoder::new(
-                    archive_file,
-                    bzip2::Compression::default(),
-                ));
-            } else {
-                return Err(BError::ArchiverError(format!(
-                    "Unsupported compression '{}'!",
-                    self.compression
-                )));
-            }
+            let enc: Compressor = self.encoder(archive_file)?;
+            let mut tar: tar::Builder<Compressor> = tar::Builder::new(enc);
+            for (striped_path, path) in entries {
+                /*
+                 * A link is stored as a link in the archive and is not
+                 * replaced by the file it is pointing to
+                 */
+                let metadata: std::fs::Metadata = std::fs::symlink_metadata(path)?;
+                if metadata.file_type().is_symlink() {
+                    let mut header: tar::Header = tar::Header::new_gnu();
+                    header.set_entry_type(tar::EntryType::Symlink);
+                    header.set_size(0);
+                    header.set_mode(0o777);
+                    header.set_mtime(
+                        self.source_date_epoch
+                            .unwrap_or(metadata.mtime().max(0) as u64),
+                    );
+                    if self.source_date_epoch.is_some() {
+                        header.set_username("root")?;
+                        header.set_groupname("root")?;
+                    }
+                    tar.append_link(&mut header, striped_path, std::fs::read_link(path)?)?;
+                    continue;
+                }
 
-            tar = tar::Builder::new(enc);
-            for path in files {
-                let striped_path: PathBuf = path.strip_prefix(work_dir.as_os_str())?.to_path_buf();
                 let mut file: File = File::open(path)?;
-                tar.append_file(striped_path, &mut file)?;
+                match self.source_date_epoch {
+                    Some(mtime) => {
+                        let metadata: std::fs::Metadata = file.metadata()?;
+                        let mut header: tar::Header = tar::Header::new_gnu();
+                        header.set_entry_type(tar::EntryType::Regular);
+                        header.set_size(metadata.len());
+                        header.set_mode(Self::normalized_mode(metadata.mode()));
+                        header.set_mtime(mtime);
+                        header.set_uid(0);
+                        header.set_gid(0);
+                        header.set_username("root")?;
+                        header.set_groupname("root")?;
+                        tar.append_data(&mut header, striped_path, &mut file)?;
+                    }
+                    None => {
+                        tar.append_file(striped_path, &mut file)?;
+                    }
+                }
+            }
+
+            tar.into_inner()?.finish()?;
+        } else if self.extension() == "cpio" {
+            let enc: Compressor = self.encoder(archive_file)?;
+            let mut cpio: CpioWriter<Compressor> = CpioWriter::new(enc, self.source_date_epoch);
+            for (striped_path, path) in entries {
+                cpio.append_file(&striped_path, path)?;
             }
 
-            tar.finish()?;
+            cpio.finish()?.finish()?;
         } else if self.extension() == "zip" {
             if mode == Mode::Append {}
 
@@ -162,11 +518,22 @@ impl Archiver {
             let mut options: FileOptions =
                 zip::write::FileOptions::default().unix_permissions(0o755);
             options = options.large_file(true);
+            if let Some(level) = self.level {
+                options = options.compression_level(Some(level as i32));
+            }
+            if let Some(mtime) = self.source_date_epoch {
+                options = options.last_modified_time(Self::zip_time(mtime));
+            }
 
-            for path in files {
-                //println!("{}", path.display());
-                //println!("{}", work_dir.display());
-                let striped_path: PathBuf = path.strip_prefix(work_dir.as_os_str())?.to_path_buf();
+            for (striped_path, path) in entries {
+                if std::fs::symlink_metadata(path)?.file_type().is_symlink() {
+                    zip.add_symlink(
+                        striped_path.to_string_lossy(),
+                        std::fs::read_link(path)?.to_string_lossy(),
+                        options,
+                    )?;
+                    continue;
+                }
 
                 let mut file: File = File::open(path)?;
 
@@ -180,15 +547,280 @@ impl Archiver {
 
         Ok(())
     }
+
+    fn decoder(&self, archive_file: File) -> Result<Box<dyn std::io::Read>, BError> {
+        let dec: Box<dyn std::io::Read>;
+        match self.compression() {
+            "" => {
+                dec = Box::new(archive_file);
+            }
+            "gz" => {
+                dec = Box::new(flate2::read::GzDecoder::new(archive_file));
+            }
+            "bz2" => {
+                dec = Box::new(bzip2::read::BzDecoder::new(archive_file));
+            }
+            "xz" => {
+                dec = Box::new(xz2::read::XzDecoder::new(archive_file));
+            }
+            "zst" => {
+                dec = Box::new(zstd::Decoder::new(archive_file)?);
+            }
+            _ => {
+                return Err(BError::ArchiverError(format!(
+                    "Unsupported compression '{}'!",
+                    self.compression
+                )));
+            }
+        }
+        Ok(dec)
+    }
+
+    /*
+     * Only entries with a relative path without any '..' can be extracted
+     * so that nothing is written outside of the dest dir. Any leading './'
+     * is removed.
+     */
+    fn entry_path(path: &Path) -> Option<PathBuf> {
+        let mut entry: PathBuf = PathBuf::new();
+        for component in path.components() {
+            match component {
+                std::path::Component::Normal(c) => entry.push(c),
+                std::path::Component::CurDir => {}
+                _ => return None,
+            }
+        }
+        match entry.as_os_str().is_empty() {
+            true => None,
+            false => Some(entry),
+        }
+    }
+
+    /*
+     * Create the dirs leading up to the path in the dest dir. None of the
+     * dirs can be a link since an archive with a link to a dir outside of
+     * the dest dir followed by a file in the linked dir could otherwise
+     * be used to write files anywhere.
+     */
+    fn extract_path(dest: &Path, path: &Path) -> Result<PathBuf, BError> {
+        let mut dir: PathBuf = dest.to_path_buf();
+        if let Some(parent) = path.parent() {
+            for component in parent.components() {
+                dir.push(component);
+                if let Ok(metadata) = std::fs::symlink_metadata(&dir) {
+                    if metadata.file_type().is_symlink() {
+                        return Err(BError::ArchiverError(format!(
+                            "Refusing to extract '{}' through the link '{}'!",
+                            path.display(),
+                            dir.display()
+                        )));
+                    }
+                }
+            }
+            std::fs::create_dir_all(&dir)?;
+        }
+        Ok(dest.join(path))
+    }
+
+    fn extract_data(
+        dest: &Path,
+        path: &Path,
+        mode: u32,
+        reader: &mut dyn std::io::Read,
+    ) -> Result<PathBuf, BError> {
+        let out: PathBuf = Self::extract_path(dest, path)?;
+        if std::fs::symlink_metadata(&out).is_ok() {
+            std::fs::remove_file(&out)?;
+        }
+
+        if mode & 0o170000 == 0o120000 {
+            let mut target: String = String::new();
+            reader.read_to_string(&mut target)?;
+            std::os::unix::fs::symlink(target, &out)?;
+            return Ok(out);
+        }
+
+        let mut file: File = File::create(&out)?;
+        std::io::copy(reader, &mut file)?;
+        if mode & 0o7777 != 0 {
+            std::fs::set_permissions(
+                &out,
+                std::os::unix::fs::PermissionsExt::from_mode(mode & 0o7777),
+            )?;
+        }
+        Ok(out)
+    }
+
+    /*
+     * Read a cpio "newc" archive. Hardlinked files are stored with the
+     * content in the last entry having the same inode so the earlier
+     * entries are linked once the content has been extracted.
+     */
+    fn extract_cpio(
+        mut reader: Box<dyn std::io::Read>,
+        dest: &Path,
+        filter: &dyn Fn(&Path) -> Option<PathBuf>,
+    ) -> Result<Vec<PathBuf>, BError> {
+        let mut extracted: Vec<PathBuf> = vec![];
+        let mut links: std::collections::HashMap<u32, Vec<PathBuf>> =
+            std::collections::HashMap::new();
+        loop {
+            let mut header: [u8; 110] = [0; 110];
+            reader.read_exact(&mut header)?;
+            if &header[0..6] != b"070701" && &header[0..6] != b"070702" {
+                return Err(BError::ArchiverError(String::from(
+                    "Invalid cpio archive, only the newc format is supported!",
+                )));
+            }
+            let mut fields: Vec<u32> = vec![];
+            for i in 0..13 {
+                let start: usize = 6 + i * 8;
+                let hex: &str = std::str::from_utf8(&header[start..start + 8])?;
+                fields.push(u32::from_str_radix(hex, 16).map_err(|err| {
+                    BError::ArchiverError(format!("Invalid cpio header, {}", err))
+                })?);
+            }
+            let (ino, mode, nlink, size, name_size) = (
+                fields[0],
+                fields[1],
+                fields[4],
+                fields[6] as usize,
+                fields[11] as usize,
+            );
+            let mut name: Vec<u8> = vec![0; name_size + (4 - (110 + name_size) % 4) % 4];
+            reader.read_exact(&mut name)?;
+            let name: String =
+                String::from_utf8_lossy(&name[..name_size.saturating_sub(1)]).to_string();
+            if name == "TRAILER!!!" {
+                break;
+            }
+
+            let mut data: std::io::Take<&mut Box<dyn std::io::Read>> =
+                (&mut reader).take(size as u64);
+            let file_type: u32 = mode & 0o170000;
+            let out: Option<PathBuf> = match file_type {
+                0o100000 | 0o120000 => Self::entry_path(Path::new(&name)).and_then(|p| filter(&p)),
+                _ => None,
+            };
+            if let Some(path) = out {
+                if file_type == 0o100000 && nlink > 1 && size == 0 {
+                    links.entry(ino).or_default().push(path);
+                } else {
+                    let out: PathBuf = Self::extract_data(dest, &path, mode, &mut data)?;
+                    for link in links.remove(&ino).unwrap_or_default() {
+                        let link: PathBuf = Self::extract_path(dest, &link)?;
+                        std::fs::hard_link(&out, &link)?;
+                        extracted.push(link);
+                    }
+                    extracted.push(out);
+                }
+            }
+            std::io::copy(&mut data, &mut std::io::sink())?;
+            let mut padding: Vec<u8> = vec![0; (4 - size % 4) % 4];
+            reader.read_exact(&mut padding)?;
+        }
+        Ok(extracted)
+    }
+
+    /*
+     * Extract the files and links in the archive to the dest dir. The
+     * filter is called with the path of every entry in the archive and
+     * returns the path to extract the entry to relative to the dest dir
+     * or None if the entry should be skipped. Directories are created
+     * when needed and are not extracted on their own.
+     */
+    pub fn extract(
+        &self,
+        dest: &Path,
+        filter: &dyn Fn(&Path) -> Option<PathBuf>,
+    ) -> Result<Vec<PathBuf>, BError> {
+        let archive_file: File = File::open(&self.path)?;
+        let mut extracted: Vec<PathBuf> = vec![];
+        std::fs::create_dir_all(dest)?;
+
+        if self.extension() == "tar" {
+            let mut tar: tar::Archive<Box<dyn std::io::Read>> =
+                tar::Archive::new(self.decoder(archive_file)?);
+            for entry in tar.entries()? {
+                let mut entry: tar::Entry<Box<dyn std::io::Read>> = entry?;
+                let path: PathBuf = match Self::entry_path(&entry.path()?) {
+                    Some(path) => path,
+                    None => continue,
+                };
+                let out: PathBuf = match filter(&path) {
+                    Some(out) => out,
+                    None => continue,
+                };
+                match entry.header().entry_type() {
+                    tar::EntryType::Regular | tar::EntryType::Continuous => {
+                        let mode: u32 = entry.header().mode()? | 0o100000;
+                        extracted.push(Self::extract_data(dest, &out, mode, &mut entry)?);
+                    }
+                    tar::EntryType::Symlink => {
+                        let target: PathBuf = entry.link_name()?.unwrap_or_default().to_path_buf();
+                        let mut reader: &[u8] = target.as_os_str().as_encoded_bytes();
+                        extracted.push(Self::extract_data(dest, &out, 0o120777, &mut reader)?);
+                    }
+                    tar::EntryType::Link => {
+                        /*
+                         * A hardlink can only be created if the file it is
+                         * linked to has been extracted, if not it is skipped
+                         */
+                        let target: Option<PathBuf> = entry
+                            .link_name()?
+                            .and_then(|t| Self::entry_path(&t))
+                            .and_then(|t| filter(&t))
+                            .map(|t| Self::extract_path(dest, &t))
+                            .transpose()?;
+                        if let Some(target) = target.filter(|t| t.is_file()) {
+                            let link: PathBuf = Self::extract_path(dest, &out)?;
+                            if std::fs::symlink_metadata(&link).is_ok() {
+                                std::fs::remove_file(&link)?;
+                            }
+                            std::fs::hard_link(&target, &link)?;
+                            extracted.push(link);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        } else if self.extension() == "cpio" {
+            extracted = Self::extract_cpio(self.decoder(archive_file)?, dest, filter)?;
+        } else if self.extension() == "zip" {
+            let mut zip: zip::ZipArchive<File> = zip::ZipArchive::new(archive_file)?;
+            for i in 0..zip.len() {
+                let mut file: zip::read::ZipFile<'_> = zip.by_index(i)?;
+                if file.is_dir() {
+                    continue;
+                }
+                let out: PathBuf = match file
+                    .enclosed_name()
+                    .and_then(Self::entry_path)
+                    .and_then(|p| filter(&p))
+                {
+                    Some(out) => out,
+                    None => continue,
+                };
+                let mode: u32 = file.unix_mode().unwrap_or(0o100644);
+                extracted.push(Self::extract_data(dest, &out, mode, &mut file)?);
+            }
+        }
+
+        Ok(extracted)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::fs::File;
+    use std::io::{Read, Write};
+    use std::os::unix::fs::PermissionsExt;
     use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
     use tempdir::TempDir;
 
     use crate::error::BError;
-    use crate::fs::Archiver;
+    use crate::fs::{Archiver, Compressor};
     use crate::helper::Helper;
 
     #[test]
@@ -251,10 +883,10 @@ mod tests {
         assert_eq!(archiver.compression(), "bz2");
     }
 
-    /*
     #[test]
     fn test_archiver_tar_xz() {
-        let temp_dir: TempDir = TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
         let path: &Path = temp_dir.path();
         let archiver_path: PathBuf = path.join("test-archiver.tar.xz");
         let archiver: Archiver = Archiver::new(&archiver_path).expect("Failed to setup archiver!");
@@ -262,7 +894,100 @@ mod tests {
         assert_eq!(archiver.extension(), "tar");
         assert_eq!(archiver.compression(), "xz");
     }
-    */
+
+    #[test]
+    fn test_archiver_tar_zst() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let path: &Path = temp_dir.path();
+        let archiver_path: PathBuf = path.join("test-archiver.tar.zst");
+        let archiver: Archiver = Archiver::new(&archiver_path).expect("Failed to setup archiver!");
+        assert_eq!(archiver.name(), "test-archiver.tar.zst");
+        assert_eq!(archiver.extension(), "tar");
+        assert_eq!(archiver.compression(), "zst");
+    }
+
+    #[test]
+    fn test_archiver_cpio() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let path: &Path = temp_dir.path();
+        let archiver: Archiver =
+            Archiver::new(&path.join("test-archiver.cpio")).expect("Failed to setup archiver!");
+        assert_eq!(archiver.extension(), "cpio");
+        assert_eq!(archiver.compression(), "");
+        let archiver: Archiver =
+            Archiver::new(&path.join("test-archiver.cpio.gz")).expect("Failed to setup archiver!");
+        assert_eq!(archiver.extension(), "cpio");
+        assert_eq!(archiver.compression(), "gz");
+        let error: BError = Archiver::new(&path.join("test-archiver.cpio.bz2"))
+            .expect_err("We are expecting an error but got an Archiver");
+        assert_eq!(
+            error.to_string(),
+            "Unsupported compression 'bz2'!".to_string()
+        );
+    }
+
+    #[test]
+    fn test_archiver_level() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let path: &Path = temp_dir.path();
+        let mut archiver: Archiver =
+            Archiver::new(&path.join("test-archiver.tar.zst")).expect("Failed to setup archiver!");
+        archiver.set_level(19).expect("Failed to set level");
+        assert_eq!(archiver.level(), Some(19));
+        let error: BError = archiver
+            .set_level(23)
+            .expect_err("We are expecting an error for the level");
+        assert_eq!(
+            error.to_string(),
+            "Invalid compression level 23 for 'test-archiver.tar.zst' expected 1-22!".to_string()
+        );
+        let mut archiver: Archiver =
+            Archiver::new(&path.join("test-archiver.tar.bz2")).expect("Failed to setup archiver!");
+        assert!(archiver.set_level(0).is_err());
+        let mut archiver: Archiver =
+            Archiver::new(&path.join("test-archiver.tar")).expect("Failed to setup archiver!");
+        let error: BError = archiver
+            .set_level(1)
+            .expect_err("We are expecting an error for the level");
+        assert_eq!(
+            error.to_string(),
+            "Compression level is not supported for 'test-archiver.tar'!".to_string()
+        );
+    }
+
+    #[test]
+    fn test_archiver_compressor() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let path: &Path = temp_dir.path();
+        let data: Vec<u8> = "bakery".repeat(1024).into_bytes();
+        for compression in ["", "gz", "bz2", "xz", "zst"] {
+            let file: PathBuf = path.join(format!("data.{}", compression));
+            let mut enc: Compressor = Archiver::compressor(
+                File::create(&file).expect("Failed to create file"),
+                compression,
+                None,
+            )
+            .expect("Failed to setup compressor!");
+            enc.write_all(&data).expect("Failed to write data");
+            enc.finish().expect("Failed to finish compressor");
+            let compressed: File = File::open(&file).expect("Failed to open file");
+            let mut dec: Box<dyn Read> = match compression {
+                "gz" => Box::new(flate2::read::GzDecoder::new(compressed)),
+                "bz2" => Box::new(bzip2::read::BzDecoder::new(compressed)),
+                "xz" => Box::new(xz2::read::XzDecoder::new(compressed)),
+                "zst" => Box::new(zstd::Decoder::new(compressed).unwrap()),
+                _ => Box::new(compressed),
+            };
+            let mut content: Vec<u8> = vec![];
+            dec.read_to_end(&mut content)
+                .expect("Failed to decompress data");
+            assert_eq!(content, data);
+        }
+    }
 
     #[test]
     fn test_archiver_error_unsupported_archive() {
@@ -293,17 +1018,15 @@ mod tests {
     }
 
     #[test]
-    fn test_archiver_error_no_compression() {
+    fn test_archiver_tar() {
         let temp_dir: TempDir =
             TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
         let path: &Path = temp_dir.path();
         let archiver_path: PathBuf = path.join("test-archiver.tar");
-        let error: BError = Archiver::new(&archiver_path)
-            .expect_err("We are expecting an error but got an Archiver");
-        assert_eq!(
-            error.to_string(),
-            "Unsupported archive 'test-archiver.tar'!".to_string()
-        );
+        let archiver: Archiver = Archiver::new(&archiver_path).expect("Failed to setup archiver!");
+        assert_eq!(archiver.name(), "test-archiver.tar");
+        assert_eq!(archiver.extension(), "tar");
+        assert_eq!(archiver.compression(), "");
     }
 
     #[test]
@@ -398,4 +1121,307 @@ mod tests {
         // we are iterating over the content and is collecting the file names
         Helper::verify_archived_files(&files, &archived_files, work_dir);
     }
+
+    fn helper_test_archiver_files(archive_name: &str, level: Option<u32>) {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: &Path = temp_dir.path();
+        let archiver_path: PathBuf = work_dir.join(archive_name);
+        let files: Vec<PathBuf> = vec![
+            PathBuf::from(work_dir.join("dir1/file1.txt")),
+            PathBuf::from(work_dir.join("file2.txt")),
+            PathBuf::from(work_dir.join("dir2/sub/file3.txt")),
+        ];
+
+        Helper::create_test_files(&files);
+
+        let mut archiver: Archiver =
+            Archiver::new(&archiver_path).expect("Failed to setup archiver!");
+        if let Some(level) = level {
+            archiver.set_level(level).expect("Failed to set level");
+        }
+        archiver
+            .add_files(&files, work_dir)
+            .expect("Failed too create archive");
+        assert!(archiver_path.exists());
+
+        let archived_files: Vec<PathBuf> =
+            Helper::list_files_in_archive(&archiver, &work_dir.join(PathBuf::from("unpack/")))
+                .expect("Failed to list files in archive");
+        Helper::verify_archived_files(&files, &archived_files, work_dir);
+    }
+
+    #[test]
+    fn test_archiver_file_tar_xz() {
+        helper_test_archiver_files("test-archiver.tar.xz", Some(1));
+    }
+
+    #[test]
+    fn test_archiver_file_tar_zst() {
+        helper_test_archiver_files("test-archiver.tar.zst", Some(19));
+    }
+
+    #[test]
+    fn test_archiver_file_tar() {
+        helper_test_archiver_files("test-archiver.tar", None);
+    }
+
+    #[test]
+    fn test_archiver_file_cpio() {
+        helper_test_archiver_files("test-archiver.cpio", None);
+    }
+
+    #[test]
+    fn test_archiver_file_cpio_gz() {
+        helper_test_archiver_files("test-archiver.cpio.gz", Some(9));
+    }
+
+    #[test]
+    fn test_archiver_file_zip_level() {
+        helper_test_archiver_files("test-archiver.zip", Some(0));
+    }
+
+    fn helper_test_archiver_reproducible(archive_name: &str) {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: &Path = temp_dir.path();
+        let files: Vec<PathBuf> = vec![
+            PathBuf::from(work_dir.join("file2.txt")),
+            PathBuf::from(work_dir.join("dir1/file1.txt")),
+        ];
+        Helper::create_test_files(&files);
+
+        let mut checksums: Vec<Vec<u8>> = vec![];
+        for (i, order) in [vec![0, 1], vec![1, 0]].iter().enumerate() {
+            // Change the order, mtime and permissions of the files between the archives
+            let modified: SystemTime =
+                SystemTime::UNIX_EPOCH + Duration::from_secs(1000 * i as u64);
+            for f in files.iter() {
+                File::options()
+                    .write(true)
+                    .open(f)
+                    .unwrap()
+                    .set_modified(modified)
+                    .unwrap();
+                std::fs::set_permissions(
+                    f,
+                    std::fs::Permissions::from_mode(0o600 + i as u32 * 0o40),
+                )
+                .unwrap();
+            }
+            let ordered: Vec<PathBuf> = order.iter().map(|i| files[*i].clone()).collect();
+            let archiver_path: PathBuf = work_dir.join(format!("{}/{}", i, archive_name));
+            let mut archiver: Archiver =
+                Archiver::new(&archiver_path).expect("Failed to setup archiver!");
+            archiver.set_reproducible(1700000000);
+            archiver
+                .add_files(&ordered, work_dir)
+                .expect("Failed too create archive");
+            checksums.push(std::fs::read(&archiver_path).unwrap());
+        }
+        assert_eq!(checksums[0], checksums[1]);
+    }
+
+    #[test]
+    fn test_archiver_reproducible_tar_gz() {
+        helper_test_archiver_reproducible("test-archiver.tar.gz");
+    }
+
+    #[test]
+    fn test_archiver_reproducible_zip() {
+        helper_test_archiver_reproducible("test-archiver.zip");
+    }
+
+    #[test]
+    fn test_archiver_reproducible_cpio_gz() {
+        helper_test_archiver_reproducible("test-archiver.cpio.gz");
+    }
+
+    #[test]
+    fn test_archiver_reproducible_tar_header() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: &Path = temp_dir.path();
+        let files: Vec<PathBuf> = vec![PathBuf::from(work_dir.join("file1.txt"))];
+        Helper::create_test_files(&files);
+        let archiver_path: PathBuf = work_dir.join("test-archiver.tar");
+        let mut archiver: Archiver =
+            Archiver::new(&archiver_path).expect("Failed to setup archiver!");
+        archiver.set_reproducible(1700000000);
+        archiver
+            .add_files(&files, work_dir)
+            .expect("Failed too create archive");
+        let mut tar: tar::Archive<File> = tar::Archive::new(File::open(&archiver_path).unwrap());
+        let entry: tar::Entry<File> = tar.entries().unwrap().next().unwrap().unwrap();
+        assert_eq!(entry.header().mtime().unwrap(), 1700000000);
+        assert_eq!(entry.header().mode().unwrap(), 0o644);
+        assert_eq!(entry.header().uid().unwrap(), 0);
+        assert_eq!(entry.header().username().unwrap(), Some("root"));
+    }
+
+    #[test]
+    fn test_archiver_symlink() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: &Path = temp_dir.path();
+        let files: Vec<PathBuf> = vec![PathBuf::from(work_dir.join("image-1.0.wic"))];
+        Helper::create_test_files(&files);
+        std::os::unix::fs::symlink("image-1.0.wic", work_dir.join("image.wic")).unwrap();
+        let archiver_path: PathBuf = work_dir.join("archive/test-archiver.tar");
+        let archiver: Archiver = Archiver::new(&archiver_path).expect("Failed to setup archiver!");
+        archiver
+            .add_files(
+                &vec![work_dir.join("image-1.0.wic"), work_dir.join("image.wic")],
+                work_dir,
+            )
+            .expect("Failed too create archive");
+        let mut tar: tar::Archive<File> = tar::Archive::new(File::open(&archiver_path).unwrap());
+        let links: Vec<(PathBuf, Option<PathBuf>)> = tar
+            .entries()
+            .unwrap()
+            .map(|e| {
+                let e: tar::Entry<File> = e.unwrap();
+                (
+                    e.path().unwrap().to_path_buf(),
+                    e.link_name().unwrap().map(|l| l.to_path_buf()),
+                )
+            })
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                (PathBuf::from("image-1.0.wic"), None),
+                (
+                    PathBuf::from("image.wic"),
+                    Some(PathBuf::from("image-1.0.wic"))
+                ),
+            ]
+        );
+    }
+
+    fn helper_test_archiver_extract(archive_name: &str) {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: &Path = temp_dir.path();
+        let files: Vec<PathBuf> = vec![
+            work_dir.join("rootfs/boot/zImage"),
+            work_dir.join("rootfs/boot/dtbs/board.dtb"),
+            work_dir.join("rootfs/usr/bin/app"),
+        ];
+        Helper::create_test_files(&files);
+        std::fs::set_permissions(
+            work_dir.join("rootfs/usr/bin/app"),
+            std::fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+        std::os::unix::fs::symlink("zImage", work_dir.join("rootfs/boot/kernel")).unwrap();
+        let mut archived: Vec<PathBuf> = files.clone();
+        archived.push(work_dir.join("rootfs/boot/kernel"));
+        let archiver: Archiver =
+            Archiver::new(&work_dir.join(archive_name)).expect("Failed to setup archiver!");
+        archiver
+            .add_files(&archived, work_dir)
+            .expect("Failed too create archive");
+
+        let dest: PathBuf = work_dir.join("extract");
+        let mut extracted: Vec<PathBuf> = archiver
+            .extract(&dest, &|p: &Path| {
+                p.strip_prefix("rootfs/boot").ok().map(|p| p.to_path_buf())
+            })
+            .expect("Failed to extract archive");
+        extracted.sort();
+        assert_eq!(
+            extracted,
+            vec![
+                dest.join("dtbs/board.dtb"),
+                dest.join("kernel"),
+                dest.join("zImage"),
+            ]
+        );
+        assert_eq!(
+            std::fs::read(dest.join("dtbs/board.dtb")).unwrap(),
+            std::fs::read(work_dir.join("rootfs/boot/dtbs/board.dtb")).unwrap()
+        );
+        assert_eq!(
+            std::fs::read_link(dest.join("kernel")).unwrap(),
+            PathBuf::from("zImage")
+        );
+
+        let extracted: Vec<PathBuf> = archiver
+            .extract(&dest, &|p: &Path| {
+                p.strip_prefix("rootfs/usr").ok().map(|p| p.to_path_buf())
+            })
+            .expect("Failed to extract archive");
+        assert_eq!(extracted, vec![dest.join("bin/app")]);
+        assert_eq!(
+            std::fs::metadata(dest.join("bin/app"))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777,
+            0o755
+        );
+    }
+
+    #[test]
+    fn test_archiver_extract_tar_gz() {
+        helper_test_archiver_extract("test-archiver.tar.gz");
+    }
+
+    #[test]
+    fn test_archiver_extract_tar_zst() {
+        helper_test_archiver_extract("test-archiver.tar.zst");
+    }
+
+    #[test]
+    fn test_archiver_extract_zip() {
+        helper_test_archiver_extract("test-archiver.zip");
+    }
+
+    #[test]
+    fn test_archiver_extract_cpio_gz() {
+        helper_test_archiver_extract("test-archiver.cpio.gz");
+    }
+
+    #[test]
+    fn test_archiver_extract_symlink_dir() {
+        for archive_name in [
+            "test-archiver.tar.gz",
+            "test-archiver.zip",
+            "test-archiver.cpio",
+        ] {
+            let temp_dir: TempDir =
+                TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+            let work_dir: &Path = temp_dir.path();
+            let escape_dir: PathBuf = work_dir.join("escape");
+            Helper::create_test_files(&vec![escape_dir.join("file.txt")]);
+            std::fs::create_dir_all(work_dir.join("rootfs")).unwrap();
+            std::os::unix::fs::symlink(&escape_dir, work_dir.join("rootfs/lib")).unwrap();
+            let archiver: Archiver =
+                Archiver::new(&work_dir.join(archive_name)).expect("Failed to setup archiver!");
+            archiver
+                .add_files(
+                    &vec![
+                        work_dir.join("rootfs/lib"),
+                        work_dir.join("rootfs/lib/file.txt"),
+                    ],
+                    work_dir,
+                )
+                .expect("Failed too create archive");
+            std::fs::remove_file(escape_dir.join("file.txt")).unwrap();
+
+            let dest: PathBuf = work_dir.join("extract");
+            let error: BError = archiver
+                .extract(&dest, &|p: &Path| Some(p.to_path_buf()))
+                .expect_err("We are expecting an error for the link");
+            assert_eq!(
+                error.to_string(),
+                format!(
+                    "Refusing to extract 'rootfs/lib/file.txt' through the link '{}'!",
+                    dest.join("rootfs/lib").display()
+                )
+            );
+            assert!(!escape_dir.join("file.txt").exists());
+        }
+    }
 }
@@ -0,0 +1,240 @@
+use indexmap::IndexMap;
+use regex::Regex;
+use std::path::PathBuf;
+
+use crate::error::BError;
+
+/*
+ * Parse the output captured from a bitbake task and pick out what is
+ * needed to quickly find what went wrong in a failing build. The full
+ * output can easily be tens of thousands of lines so the goal is to
+ * get a compact summary of the failed tasks and where the logs for
+ * the failed tasks can be found.
+ */
+pub struct BBLog {
+    failed_tasks: Vec<String>,
+    failure_logs: Vec<PathBuf>,
+    warnings: Vec<String>,
+    errors: Vec<String>,
+    warnings_summary: Option<u32>,
+}
+
+impl BBLog {
+    fn summary_count(regexp: &Regex, line: &str) -> Option<u32> {
+        match regexp.captures(line) {
+            Some(caps) => caps[1].parse::<u32>().ok(),
+            None => None,
+        }
+    }
+
+    pub fn from_file(path: &PathBuf) -> Result<Self, BError> {
+        let content: Vec<u8> = std::fs::read(path)?;
+        Ok(Self::new(&String::from_utf8_lossy(&content)))
+    }
+
+    pub fn new(content: &str) -> Self {
+        let task_failed: Regex =
+            Regex::new(r"^ERROR: Task \((.+)\) failed with exit code").unwrap();
+        let failure_log: Regex = Regex::new(r"Logfile of failure stored in: (\S+)").unwrap();
+        let warnings_summary: Regex =
+            Regex::new(r"^Summary: There (?:was|were) (\d+) WARNING message").unwrap();
+        let mut failed_tasks: Vec<String> = Vec::new();
+        let mut failure_logs: Vec<PathBuf> = Vec::new();
+        let mut warnings: Vec<String> = Vec::new();
+        let mut errors: Vec<String> = Vec::new();
+        let mut summary: Option<u32> = None;
+
+        for line in content.lines() {
+            let line: &str = line.trim_end();
+            if let Some(caps) = task_failed.captures(line) {
+                let task: String = caps[1].to_string();
+                if !failed_tasks.contains(&task) {
+                    failed_tasks.push(task);
+                }
+            }
+
+            if let Some(caps) = failure_log.captures(line) {
+                let log: PathBuf = PathBuf::from(&caps[1]);
+                if !failure_logs.contains(&log) {
+                    failure_logs.push(log);
+                }
+            }
+
+            if let Some(count) = Self::summary_count(&warnings_summary, line) {
+                summary = Some(count);
+            }
+
+            if let Some(warning) = line.strip_prefix("WARNING: ") {
+                warnings.push(warning.to_string());
+            }
+
+            if let Some(error) = line.strip_prefix("ERROR: ") {
+                errors.push(error.to_string());
+            }
+        }
+
+        BBLog {
+            failed_tasks,
+            failure_logs,
+            warnings,
+            errors,
+            warnings_summary: summary,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn failed_tasks(&self) -> &Vec<String> {
+        &self.failed_tasks
+    }
+
+    #[cfg(test)]
+    pub fn failure_logs(&self) -> &Vec<PathBuf> {
+        &self.failure_logs
+    }
+
+    #[cfg(test)]
+    pub fn warnings(&self) -> &Vec<String> {
+        &self.warnings
+    }
+
+    /*
+     * Group the warnings by the recipe that is reporting the warning. The
+     * warnings from bitbake that are not related to a specific recipe are
+     * grouped under 'bitbake'.
+     */
+    pub fn warnings_by_recipe(&self) -> IndexMap<String, Vec<String>> {
+        let recipe_warning: Regex = Regex::new(r"^(\S+) do_\w+: ").unwrap();
+        let mut grouped: IndexMap<String, Vec<String>> = IndexMap::new();
+
+        for warning in self.warnings.iter() {
+            let recipe: String = match recipe_warning.captures(warning) {
+                Some(caps) => caps[1].to_string(),
+                None => String::from("bitbake"),
+            };
+            grouped.entry(recipe).or_default().push(warning.clone());
+        }
+
+        grouped
+    }
+
+    #[cfg(test)]
+    pub fn errors(&self) -> &Vec<String> {
+        &self.errors
+    }
+
+    /*
+     * Bitbake is printing a summary with the number of warnings at the
+     * end of the build. If it is available we use it since it is what
+     * bitbake is reporting otherwise we count the warnings in the output.
+     */
+    pub fn warnings_count(&self) -> u32 {
+        match self.warnings_summary {
+            Some(count) => count,
+            None => self.warnings.len() as u32,
+        }
+    }
+
+    pub fn summary(&self) -> Vec<String> {
+        let mut summary: Vec<String> = vec![format!(
+            "{} warning(s), {} error(s)",
+            self.warnings_count(),
+            self.errors.len()
+        )];
+
+        for task in self.failed_tasks.iter() {
+            summary.push(format!("Failed task {}", task));
+        }
+
+        for log in self.failure_logs.iter() {
+            summary.push(format!("Failure log {}", log.display()));
+        }
+
+        for (recipe, warnings) in self.warnings_by_recipe() {
+            summary.push(format!("{} warning(s) from {}", warnings.len(), recipe));
+        }
+
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+    use std::path::PathBuf;
+
+    use crate::fs::BBLog;
+
+    #[test]
+    fn test_bblog_failed_build() {
+        let content: &str = r#"
+Loading cache...done.
+NOTE: Executing Tasks
+WARNING: test-recipe-1.0-r0 do_package_qa: QA Issue: test-recipe: Files/directories were installed but not shipped in any package [installed-vs-shipped]
+ERROR: test-recipe-1.0-r0 do_compile: oe_runmake failed
+ERROR: test-recipe-1.0-r0 do_compile: Execution of '/builds/default/tmp/work/test-recipe/1.0-r0/temp/run.do_compile.1234' failed with exit code 1
+ERROR: Logfile of failure stored in: /builds/default/tmp/work/test-recipe/1.0-r0/temp/log.do_compile.1234
+ERROR: Task (/layers/meta-test/recipes-test/test-recipe/test-recipe_1.0.bb:do_compile) failed with exit code '1'
+NOTE: Tasks Summary: Attempted 2100 tasks of which 2098 didn't need to be rerun and 1 failed.
+
+Summary: 1 task failed:
+  /layers/meta-test/recipes-test/test-recipe/test-recipe_1.0.bb:do_compile
+Summary: There was 1 WARNING message.
+Summary: There were 4 ERROR messages, returning a non-zero exit code.
+"#;
+        let log: BBLog = BBLog::new(content);
+        assert_eq!(
+            log.failed_tasks(),
+            &vec![String::from(
+                "/layers/meta-test/recipes-test/test-recipe/test-recipe_1.0.bb:do_compile"
+            )]
+        );
+        assert_eq!(
+            log.failure_logs(),
+            &vec![PathBuf::from(
+                "/builds/default/tmp/work/test-recipe/1.0-r0/temp/log.do_compile.1234"
+            )]
+        );
+        assert_eq!(log.warnings_count(), 1);
+        assert_eq!(log.errors().len(), 4);
+        assert_eq!(
+            log.summary(),
+            vec![
+                String::from("1 warning(s), 4 error(s)"),
+                String::from("Failed task /layers/meta-test/recipes-test/test-recipe/test-recipe_1.0.bb:do_compile"),
+                String::from("Failure log /builds/default/tmp/work/test-recipe/1.0-r0/temp/log.do_compile.1234"),
+                String::from("1 warning(s) from test-recipe-1.0-r0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bblog_warnings() {
+        let content: &str = r#"
+WARNING: recipe-a-1.0-r0 do_package_qa: QA Issue: issue one
+WARNING: recipe-b-2.0-r0 do_package_qa: QA Issue: issue two
+WARNING: recipe-a-1.0-r0 do_install: issue three
+WARNING: Host distribution "test" has not been validated
+NOTE: Tasks Summary: Attempted 100 tasks of which 98 didn't need to be rerun and all succeeded.
+"#;
+        let log: BBLog = BBLog::new(content);
+        assert!(log.failed_tasks().is_empty());
+        assert!(log.failure_logs().is_empty());
+        assert_eq!(log.warnings_count(), 4);
+        assert_eq!(
+            log.warnings().get(1).unwrap(),
+            "recipe-b-2.0-r0 do_package_qa: QA Issue: issue two"
+        );
+        let grouped: IndexMap<String, Vec<String>> = log.warnings_by_recipe();
+        assert_eq!(
+            grouped.keys().collect::<Vec<&String>>(),
+            vec!["recipe-a-1.0-r0", "recipe-b-2.0-r0", "bitbake"]
+        );
+        assert_eq!(
+            grouped.get("recipe-a-1.0-r0").unwrap(),
+            &vec![
+                String::from("recipe-a-1.0-r0 do_package_qa: QA Issue: issue one"),
+                String::from("recipe-a-1.0-r0 do_install: issue three"),
+            ]
+        );
+    }
+}
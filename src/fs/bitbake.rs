@@ -1,3 +1,4 @@
+use indexmap::IndexMap;
 use std::io::Read;
 use std::io::Write;
 use std::path::PathBuf;
@@ -6,6 +7,7 @@ use std::result;
 use crate::cli::Cli;
 use crate::data::WsBitbakeData;
 use crate::error::BError;
+use crate::fs::Diff;
 
 static BBLAYERS_PATTERNS: [&str; 4] = ["BBLAYERS ?=", "BBLAYERS?=", "BBLAYERS=", "BBLAYERS ="];
 
@@ -17,6 +19,9 @@ pub struct BitbakeConf {
     bblayers_conf_content: String,
     force: bool,
     bb_variables: Vec<String>,
+    extra_confs: IndexMap<PathBuf, String>,
+    local_conf_user: Option<PathBuf>,
+    tmp_dir_marker: Option<(PathBuf, String)>,
 }
 
 impl BitbakeConf {
@@ -47,10 +52,9 @@ impl BitbakeConf {
         String::from(content)
     }
 
-    fn write_bb_build_conf(&self, path: &PathBuf, content: &str) -> Result<(), BError> {
+    fn format_bb_build_conf(&self, content: &str) -> String {
         let mut found_bblayers: bool = false;
         let mut result_lines: Vec<String> = Vec::new();
-        let mut file: std::fs::File = std::fs::File::create(path)?;
 
         /*
          * Clean the content from newlines and tabs
@@ -83,23 +87,51 @@ impl BitbakeConf {
 
         cleaned = result_lines.join("\n");
         cleaned.push_str("\n");
-        file.write_all(cleaned.as_bytes())?;
-        Ok(())
+        cleaned
     }
 
-    fn cmp_files(&self, path1: &PathBuf, path2: &PathBuf) -> Result<bool, BError> {
-        let mut file1: std::fs::File = std::fs::File::open(path1)?;
-        let mut file2: std::fs::File = std::fs::File::open(path2)?;
-        let mut content1: String = String::new();
-        let mut content2: String = String::new();
-        file1.read_to_string(&mut content1)?;
-        file2.read_to_string(&mut content2)?;
+    fn write_bb_build_conf(&self, path: &PathBuf, content: &str) -> Result<(), BError> {
+        let mut file: std::fs::File = std::fs::File::create(path)?;
+        file.write_all(content.as_bytes())?;
+        Ok(())
+    }
 
-        if content1 != content2 {
-            return Ok(false);
-        }
+    fn read_file(&self, path: &PathBuf) -> Result<String, BError> {
+        let mut file: std::fs::File = std::fs::File::open(path)?;
+        let mut content: String = String::new();
+        file.read_to_string(&mut content)?;
+        Ok(content)
+    }
 
-        Ok(true)
+    /*
+     * Before a conf file is regenerated the previous version is kept
+     * as a backup next to it and the changes are presented so nothing
+     * is lost without the user knowing about it.
+     */
+    fn backup_bb_conf_file(
+        &self,
+        cli: &Cli,
+        conf_path: &PathBuf,
+        diff: &Diff,
+    ) -> Result<(), BError> {
+        let file_name: String = conf_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let backup_path: PathBuf = conf_path.with_file_name(format!("{}.bak", file_name));
+        std::fs::copy(conf_path, &backup_path)?;
+        cli.info(format!(
+            "Changes in {}, previous version saved to {}",
+            file_name,
+            backup_path.display()
+        ));
+        cli.stdout(diff.unified(
+            &backup_path.to_string_lossy(),
+            &conf_path.to_string_lossy(),
+            3,
+        ));
+        Ok(())
     }
 
     fn create_bb_conf_file(
@@ -127,24 +159,34 @@ impl BitbakeConf {
             None => {}
         }
 
-        if force {
-            cli.info(format!("Autogenerate {}", conf_path.display()));
-            self.write_bb_build_conf(&conf_path, &conf_str)?;
-        } else {
-            if conf_path.exists() {
-                let tmp_conf_path: PathBuf = conf_path.parent().unwrap().join("tmp.conf");
-                self.write_bb_build_conf(&tmp_conf_path, &self.add_ws_to_bblayers_conf(&conf_str))?;
-                let identical: bool = self.cmp_files(&tmp_conf_path, conf_path)?;
-                if identical {
-                    cli.info(format!("{} exists skipping", file_name));
-                } else {
+        let new_conf: String = self.format_bb_build_conf(&conf_str);
+        if conf_path.exists() {
+            let current_conf: String = self.read_file(conf_path)?;
+            /*
+             * devtool is adding the workspace layer to the bblayers.conf
+             * so a conf that only differs by the workspace layer is not
+             * considered changed. Any diff presented is always between
+             * the current conf and exactly what is written.
+             */
+            let unchanged: bool = current_conf == new_conf
+                || current_conf
+                    == self.format_bb_build_conf(&self.add_ws_to_bblayers_conf(&conf_str));
+            if unchanged {
+                if force {
                     cli.info(format!("Autogenerate {}", conf_path.display()));
-                    self.write_bb_build_conf(conf_path, &conf_str)?;
+                    self.write_bb_build_conf(conf_path, &new_conf)?;
+                } else {
+                    cli.info(format!("{} exists skipping", file_name));
                 }
             } else {
                 cli.info(format!("Autogenerate {}", conf_path.display()));
-                self.write_bb_build_conf(conf_path, &conf_str)?;
+                let diff: Diff = Diff::new(&current_conf, &new_conf);
+                self.backup_bb_conf_file(cli, conf_path, &diff)?;
+                self.write_bb_build_conf(conf_path, &new_conf)?;
             }
+        } else {
+            cli.info(format!("Autogenerate {}", conf_path.display()));
+            self.write_bb_build_conf(conf_path, &new_conf)?;
         }
         Ok(())
     }
@@ -160,23 +202,80 @@ impl BitbakeConf {
     }
 
     pub fn create_local_conf(&self, cli: &Cli) -> Result<(), BError> {
+        let mut bb_variables: Vec<String> = self.bb_variables.clone();
+        /*
+         * The user conf is owned by the developer and is never touched by
+         * bakery. It is included last so any local tweaks will override
+         * what is defined in the build config. Include is used instead of
+         * require since the file is optional.
+         */
+        match &self.local_conf_user {
+            Some(path) => {
+                bb_variables.push(format!("include {}", path.display()));
+            }
+            None => {}
+        }
         self.create_bb_conf_file(
             cli,
             &self.local_conf_path,
             &self.local_conf_content,
-            Some(&self.bb_variables),
+            Some(&bb_variables),
             self.force,
         )
     }
 
+    pub fn set_local_conf_user(&mut self, path: Option<PathBuf>) {
+        self.local_conf_user = path;
+    }
+
+    /*
+     * Additional conf files like site.conf, auto.conf and the multiconfig
+     * conf files. They are only generated when defined in the build config.
+     */
+    pub fn create_extra_confs(&self, cli: &Cli) -> Result<(), BError> {
+        for (conf_path, content) in self.extra_confs.iter() {
+            match conf_path.parent() {
+                Some(parent) => std::fs::create_dir_all(parent)?,
+                None => {}
+            }
+            self.create_bb_conf_file(cli, conf_path, content, None, self.force)?;
+        }
+        Ok(())
+    }
+
+    pub fn add_extra_conf(&mut self, conf_path: &PathBuf, content: String) {
+        self.extra_confs.insert(conf_path.clone(), content);
+    }
+
+    /*
+     * A TMPDIR outside of the build dir is marked as created by bakery for
+     * the build so that the clean command knows it is safe to remove. An
+     * already existing dir without a marker is never marked since it could
+     * be shared with other builds.
+     */
+    pub fn create_tmp_dir_marker(&self) -> Result<(), BError> {
+        if let Some((marker, build_dir)) = &self.tmp_dir_marker {
+            if let Some(tmp_dir) = marker.parent() {
+                if tmp_dir.exists() {
+                    return Ok(());
+                }
+                std::fs::create_dir_all(tmp_dir)?;
+            }
+            std::fs::write(marker, format!("{}\n", build_dir))?;
+        }
+        Ok(())
+    }
+
     pub fn create_bitbake_configs(&self, cli: &Cli) -> Result<(), BError> {
         std::fs::create_dir_all(&self.build_conf_dir)?;
         self.create_local_conf(cli)?;
-        self.create_bblayers_conf(cli)
+        self.create_bblayers_conf(cli)?;
+        self.create_extra_confs(cli)?;
+        self.create_tmp_dir_marker()
     }
 
     pub fn new(bitbake: &WsBitbakeData, bb_variables: &Vec<String>, force: bool) -> Self {
-        Self::construct(
+        let mut conf: BitbakeConf = Self::construct(
             &bitbake.build_config_dir(),
             &bitbake.local_conf_path(),
             &bitbake.bblayers_conf_path(),
@@ -184,7 +283,32 @@ impl BitbakeConf {
             bitbake.bblayers_conf(),
             force,
             bb_variables.clone(),
-        )
+        );
+
+        let site_conf: String = bitbake.site_conf();
+        if !site_conf.is_empty() {
+            conf.add_extra_conf(&bitbake.site_conf_path(), site_conf);
+        }
+
+        let auto_conf: String = bitbake.auto_conf();
+        if !auto_conf.is_empty() {
+            conf.add_extra_conf(&bitbake.auto_conf_path(), auto_conf);
+        }
+
+        for (name, content) in bitbake.multiconfig() {
+            conf.add_extra_conf(&bitbake.multiconfig_conf_path(&name), content);
+        }
+
+        conf.set_local_conf_user(bitbake.local_conf_user_path());
+
+        if bitbake.tmp_dir_outside_build_dir() {
+            conf.tmp_dir_marker = Some((
+                bitbake.tmp_dir_marker_path(),
+                bitbake.build_dir().to_string_lossy().to_string(),
+            ));
+        }
+
+        conf
     }
 
     pub fn construct(
@@ -204,6 +328,9 @@ impl BitbakeConf {
             bblayers_conf_content,
             force,
             bb_variables,
+            extra_confs: IndexMap::new(),
+            local_conf_user: None,
+            tmp_dir_marker: None,
         }
     }
 }
@@ -633,4 +760,183 @@ mod tests {
         validate_bblayers_conf.push_str(&bblayers_conf_content);
         assert_eq!(validate_bblayers_conf, contents);
     }
+
+    #[test]
+    fn test_bitbake_create_extra_confs() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let path: &Path = temp_dir.path();
+        let bitbake_conf_path: PathBuf = path.join("conf");
+        let local_conf_path: PathBuf = bitbake_conf_path.join("local.conf");
+        let bblayers_conf_path: PathBuf = bitbake_conf_path.join("bblayers.conf");
+        let mc_conf_path: PathBuf = bitbake_conf_path.join("multiconfig/firmware.conf");
+        let mc_conf_content: String = String::from("MACHINE = \"test-mcu\"\n");
+        let mut mocked_logger: MockLogger = MockLogger::new();
+        mocked_logger
+            .expect_info()
+            .with(mockall::predicate::eq(format!(
+                "Autogenerate {}",
+                mc_conf_path.display()
+            )))
+            .once()
+            .returning(|_x| ());
+        let cli: Cli = Cli::new(
+            Box::new(mocked_logger),
+            Box::new(BSystem::new()),
+            clap::Command::new("bakery"),
+            None,
+        );
+        let mut conf: BitbakeConf = BitbakeConf::construct(
+            &bitbake_conf_path,
+            &local_conf_path,
+            &bblayers_conf_path,
+            String::new(),
+            String::new(),
+            true,
+            vec![],
+        );
+        conf.add_extra_conf(&mc_conf_path, mc_conf_content.clone());
+        conf.create_extra_confs(&cli)
+            .expect("Failed to create extra conf files");
+        let mut file: File = File::open(&mc_conf_path).expect("Failed to open firmware.conf file!");
+        let mut contents: String = String::new();
+        file.read_to_string(&mut contents)
+            .expect("Failed to read firmware.conf file!");
+        let mut validate_mc_conf: String = String::from("# AUTO GENERATED\n");
+        validate_mc_conf.push_str(&mc_conf_content);
+        assert_eq!(validate_mc_conf, contents);
+    }
+
+    #[test]
+    fn test_bitbake_conf_local_conf_changed_backup() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let path: &Path = temp_dir.path();
+        let bitbake_conf_path: PathBuf = path.join("conf");
+        let local_conf_path: PathBuf = bitbake_conf_path.join("local.conf");
+        let local_conf_backup_path: PathBuf = bitbake_conf_path.join("local.conf.bak");
+        let bblayers_conf_path: PathBuf = bitbake_conf_path.join("bblayers.conf");
+        let old_local_conf_content: String = String::from("LOCAL_CONF_TEST_LINE ?= \"1\"\n");
+        let new_local_conf_content: String = String::from("LOCAL_CONF_TEST_LINE ?= \"2\"\n");
+        let mut mocked_logger: MockLogger = MockLogger::new();
+        mocked_logger
+            .expect_info()
+            .with(mockall::predicate::eq(format!(
+                "Autogenerate {}",
+                local_conf_path.display()
+            )))
+            .times(2)
+            .returning(|_x| ());
+        mocked_logger
+            .expect_info()
+            .with(mockall::predicate::eq(format!(
+                "Changes in local.conf, previous version saved to {}",
+                local_conf_backup_path.display()
+            )))
+            .once()
+            .returning(|_x| ());
+        mocked_logger
+            .expect_stdout()
+            .with(mockall::predicate::eq(format!(
+                "--- {}\n+++ {}\n@@ -1,2 +1,2 @@\n # AUTO GENERATED\n-LOCAL_CONF_TEST_LINE ?= \"1\"\n+LOCAL_CONF_TEST_LINE ?= \"2\"\n",
+                local_conf_backup_path.display(),
+                local_conf_path.display()
+            )))
+            .once()
+            .returning(|_x| ());
+        let cli: Cli = Cli::new(
+            Box::new(mocked_logger),
+            Box::new(BSystem::new()),
+            clap::Command::new("bakery"),
+            None,
+        );
+        std::fs::create_dir_all(&bitbake_conf_path).expect("Failed to create bitbake conf dir");
+        let conf: BitbakeConf = BitbakeConf::construct(
+            &bitbake_conf_path,
+            &local_conf_path,
+            &bblayers_conf_path,
+            old_local_conf_content.clone(),
+            String::new(),
+            false,
+            vec![],
+        );
+        conf.create_local_conf(&cli)
+            .expect("Failed to create local.conf");
+        let conf: BitbakeConf = BitbakeConf::construct(
+            &bitbake_conf_path,
+            &local_conf_path,
+            &bblayers_conf_path,
+            new_local_conf_content.clone(),
+            String::new(),
+            false,
+            vec![],
+        );
+        conf.create_local_conf(&cli)
+            .expect("Failed to create local.conf");
+        let mut file: File =
+            File::open(&local_conf_backup_path).expect("Failed to open local.conf.bak file!");
+        let mut contents: String = String::new();
+        file.read_to_string(&mut contents)
+            .expect("Failed to read local.conf.bak file!");
+        let mut validate_local_conf: String = String::from("# AUTO GENERATED\n");
+        validate_local_conf.push_str(&old_local_conf_content);
+        assert_eq!(validate_local_conf, contents);
+        let mut file: File = File::open(&local_conf_path).expect("Failed to open local.conf file!");
+        let mut contents: String = String::new();
+        file.read_to_string(&mut contents)
+            .expect("Failed to read local.conf file!");
+        let mut validate_local_conf: String = String::from("# AUTO GENERATED\n");
+        validate_local_conf.push_str(&new_local_conf_content);
+        assert_eq!(validate_local_conf, contents);
+    }
+
+    #[test]
+    fn test_bitbake_conf_local_conf_user() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let path: &Path = temp_dir.path();
+        let bitbake_conf_path: PathBuf = path.join("conf");
+        let local_conf_path: PathBuf = bitbake_conf_path.join("local.conf");
+        let local_conf_user_path: PathBuf = bitbake_conf_path.join("local.conf.user");
+        let bblayers_conf_path: PathBuf = bitbake_conf_path.join("bblayers.conf");
+        let local_conf_content: String = String::from("LOCAL_CONF_TEST_LINE ?= \"1\"\n");
+        let mut mocked_logger: MockLogger = MockLogger::new();
+        mocked_logger
+            .expect_info()
+            .with(mockall::predicate::eq(format!(
+                "Autogenerate {}",
+                local_conf_path.display()
+            )))
+            .once()
+            .returning(|_x| ());
+        let cli: Cli = Cli::new(
+            Box::new(mocked_logger),
+            Box::new(BSystem::new()),
+            clap::Command::new("bakery"),
+            None,
+        );
+        let mut conf: BitbakeConf = BitbakeConf::construct(
+            &bitbake_conf_path,
+            &local_conf_path,
+            &bblayers_conf_path,
+            local_conf_content.clone(),
+            String::new(),
+            true,
+            vec![String::from("BB_VARIABLE ?= \"1\"")],
+        );
+        conf.set_local_conf_user(Some(local_conf_user_path.clone()));
+        std::fs::create_dir_all(&bitbake_conf_path).expect("Failed to create bitbake conf dir");
+        conf.create_local_conf(&cli)
+            .expect("Failed to create local.conf");
+        let mut file: File = File::open(&local_conf_path).expect("Failed to open local.conf file!");
+        let mut contents: String = String::new();
+        file.read_to_string(&mut contents)
+            .expect("Failed to read local.conf file!");
+        let mut validate_local_conf: String = String::from("# AUTO GENERATED\n");
+        validate_local_conf.push_str(&local_conf_content);
+        validate_local_conf.push_str("BB_VARIABLE ?= \"1\"\n");
+        validate_local_conf.push_str(&format!("include {}\n", local_conf_user_path.display()));
+        assert_eq!(validate_local_conf, contents);
+        assert!(!local_conf_user_path.exists());
+    }
 }
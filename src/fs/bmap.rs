@@ -0,0 +1,251 @@
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use crate::error::BError;
+
+/*
+ * Block map of a disk image in the format used by bmaptool. Only the
+ * blocks containing data are mapped so flashing the image with bmaptool
+ * is only writing the mapped blocks instead of the whole image. A block
+ * is considered unmapped if it only contains zeros. Every range of mapped
+ * blocks has a sha256 checksum that bmaptool is verifying when flashing.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BlockMap {
+    image_size: u64,
+    block_size: u64,
+    ranges: Vec<(u64, u64, String)>,
+}
+
+impl BlockMap {
+    pub const BLOCK_SIZE: u64 = 4096;
+
+    /*
+     * Read the image once block by block. Every block is passed on to the
+     * callback together with a flag telling if it is a zero block so the
+     * image can be copied or compressed at the same time as it is mapped.
+     */
+    pub fn scan<F>(image: &PathBuf, mut block: F) -> Result<Self, BError>
+    where
+        F: FnMut(&[u8], bool) -> Result<(), BError>,
+    {
+        let mut file: File = File::open(image)?;
+        let mut bmap: BlockMap = BlockMap {
+            image_size: file.metadata()?.len(),
+            block_size: Self::BLOCK_SIZE,
+            ranges: vec![],
+        };
+        let mut buffer: Vec<u8> = vec![0; (Self::BLOCK_SIZE * 256) as usize];
+        let mut range: Option<(u64, Sha256)> = None;
+        let mut index: u64 = 0;
+
+        loop {
+            /*
+             * Fill the buffer before it is split into blocks since a read
+             * can return less than requested before the end of the file
+             */
+            let mut len: usize = 0;
+            while len < buffer.len() {
+                match file.read(&mut buffer[len..])? {
+                    0 => break,
+                    n => len += n,
+                }
+            }
+            if len == 0 {
+                break;
+            }
+
+            for data in buffer[..len].chunks(Self::BLOCK_SIZE as usize) {
+                let zero: bool = data.iter().all(|b| *b == 0);
+                block(data, zero)?;
+                match (zero, range.as_mut()) {
+                    (false, Some((_start, hasher))) => hasher.update(data),
+                    (false, None) => {
+                        let mut hasher: Sha256 = Sha256::new();
+                        hasher.update(data);
+                        range = Some((index, hasher));
+                    }
+                    (true, _) => {
+                        if let Some((start, hasher)) = range.take() {
+                            bmap.push(start, index - 1, hasher);
+                        }
+                    }
+                }
+                index += 1;
+            }
+
+            if len < buffer.len() {
+                break;
+            }
+        }
+
+        if let Some((start, hasher)) = range.take() {
+            bmap.push(start, index - 1, hasher);
+        }
+        Ok(bmap)
+    }
+
+    fn push(&mut self, start: u64, end: u64, hasher: Sha256) {
+        self.ranges
+            .push((start, end, format!("{:x}", hasher.finalize())));
+    }
+
+    pub fn image_size(&self) -> u64 {
+        self.image_size
+    }
+
+    pub fn blocks_count(&self) -> u64 {
+        self.image_size.div_ceil(self.block_size)
+    }
+
+    pub fn mapped_blocks_count(&self) -> u64 {
+        self.ranges
+            .iter()
+            .map(|(start, end, _c)| end - start + 1)
+            .sum()
+    }
+
+    #[cfg(test)]
+    pub fn ranges(&self) -> &Vec<(u64, u64, String)> {
+        &self.ranges
+    }
+
+    /*
+     * The bmap file is including a checksum of itself which is calculated
+     * with the checksum set to all zeros.
+     */
+    pub fn to_xml(&self) -> String {
+        let mut ranges: String = String::new();
+        for (start, end, checksum) in self.ranges.iter() {
+            let range: String = match start == end {
+                true => start.to_string(),
+                false => format!("{}-{}", start, end),
+            };
+            ranges.push_str(&format!(
+                "        <Range chksum=\"{}\"> {} </Range>\n",
+                checksum, range
+            ));
+        }
+
+        let zero_checksum: String = "0".repeat(64);
+        let xml: String = format!(
+            r#"<?xml version="1.0" ?>
+<!-- This file contains the block map for an image file. Only the blocks
+     listed in the block map contain data and have to be copied to the
+     target device. -->
+<bmap version="2.0">
+    <!-- Image size in bytes -->
+    <ImageSize> {} </ImageSize>
+
+    <!-- Size of a block in bytes -->
+    <BlockSize> {} </BlockSize>
+
+    <!-- Count of blocks in the image file -->
+    <BlocksCount> {} </BlocksCount>
+
+    <!-- Count of mapped blocks -->
+    <MappedBlocksCount> {} </MappedBlocksCount>
+
+    <!-- Type of checksum used in this file -->
+    <ChecksumType> sha256 </ChecksumType>
+
+    <!-- The checksum of this bmap file. When it is calculated, the value of
+         the checksum has be zero (all ASCII "0" symbols). -->
+    <BmapFileChecksum> {} </BmapFileChecksum>
+
+    <!-- The block map which consists of elements which may either be a
+         range of blocks or a single block. The 'chksum' attribute is the
+         checksum of this blocks range. -->
+    <BlockMap>
+{}    </BlockMap>
+</bmap>
+"#,
+            self.image_size,
+            self.block_size,
+            self.blocks_count(),
+            self.mapped_blocks_count(),
+            zero_checksum,
+            ranges
+        );
+        let checksum: String = format!("{:x}", Sha256::digest(xml.as_bytes()));
+        xml.replacen(&zero_checksum, &checksum, 1)
+    }
+
+    pub fn write(&self, path: &PathBuf) -> Result<(), BError> {
+        if let Some(parent_dir) = path.parent() {
+            std::fs::create_dir_all(parent_dir)?;
+        }
+        let mut file: File = File::create(path)?;
+        file.write_all(self.to_xml().as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha256};
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    use crate::fs::BlockMap;
+
+    #[test]
+    fn test_bmap() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let image: PathBuf = temp_dir.path().join("test.wic");
+        let block: usize = BlockMap::BLOCK_SIZE as usize;
+        let mut data: Vec<u8> = vec![0; block * 5 + 100];
+        data[10] = 1;
+        data[block * 3] = 2;
+        data[block * 4 + 1] = 3;
+        data[block * 5 + 99] = 4;
+        std::fs::write(&image, &data).unwrap();
+
+        let mut blocks: Vec<bool> = vec![];
+        let bmap: BlockMap = BlockMap::scan(&image, |_data: &[u8], zero: bool| {
+            blocks.push(zero);
+            Ok(())
+        })
+        .expect("Failed to create bmap");
+        assert_eq!(blocks, vec![false, true, true, false, false, false]);
+        assert_eq!(bmap.image_size(), data.len() as u64);
+        assert_eq!(bmap.blocks_count(), 6);
+        assert_eq!(bmap.mapped_blocks_count(), 4);
+        assert_eq!(
+            bmap.ranges(),
+            &vec![
+                (0, 0, format!("{:x}", Sha256::digest(&data[..block]))),
+                (3, 5, format!("{:x}", Sha256::digest(&data[block * 3..]))),
+            ]
+        );
+
+        let xml: String = bmap.to_xml();
+        assert!(xml.contains("<BlocksCount> 6 </BlocksCount>"));
+        assert!(xml.contains("<MappedBlocksCount> 4 </MappedBlocksCount>"));
+        assert!(xml.contains(&format!(
+            "<Range chksum=\"{}\"> 0 </Range>",
+            bmap.ranges()[0].2
+        )));
+        assert!(xml.contains(&format!(
+            "<Range chksum=\"{}\"> 3-5 </Range>",
+            bmap.ranges()[1].2
+        )));
+        let checksum: &str = xml
+            .split("<BmapFileChecksum> ")
+            .nth(1)
+            .unwrap()
+            .split(' ')
+            .next()
+            .unwrap();
+        assert_eq!(
+            checksum,
+            format!(
+                "{:x}",
+                Sha256::digest(xml.replace(checksum, &"0".repeat(64)).as_bytes())
+            )
+        );
+    }
+}
@@ -0,0 +1,383 @@
+use indexmap::IndexMap;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::cli::Cli;
+use crate::error::BError;
+
+/*
+ * Snapshot of an image from the buildhistory. The buildhistory is keeping
+ * the packages installed in an image with the size of each package in
+ * installed-package-sizes.txt, the package files in installed-packages.txt
+ * which is where the version is picked from and the total size of the image
+ * in image-info.txt.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BHImage {
+    pub packages: IndexMap<String, String>,
+    pub sizes: IndexMap<String, u64>,
+    pub image_size: Option<u64>,
+}
+
+impl BHImage {
+    /*
+     * The package files are named <name>_<version>_<arch>.ipk/.deb or
+     * <name>-<version>-<release>.<arch>.rpm
+     */
+    fn package_version(file: &str) -> Option<(String, String)> {
+        let file: &str = file.rsplit('/').next().unwrap_or(file);
+        if let Some(rpm) = file.strip_suffix(".rpm") {
+            let (nvr, _arch) = rpm.rsplit_once('.')?;
+            // The name can contain '-' so the version and release are split from the end
+            let parts: Vec<&str> = nvr.rsplitn(3, '-').collect();
+            if parts.len() != 3 {
+                return None;
+            }
+            let (release, version, name) = (parts[0], parts[1], parts[2]);
+            return Some((name.to_string(), format!("{}-{}", version, release)));
+        }
+
+        let base: &str = file
+            .strip_suffix(".ipk")
+            .or_else(|| file.strip_suffix(".deb"))?;
+        // The arch can contain '_' so the name and version are split from the start
+        let parts: Vec<&str> = base.splitn(3, '_').collect();
+        if parts.len() != 3 {
+            return None;
+        }
+        Some((parts[0].to_string(), parts[1].to_string()))
+    }
+
+    pub fn new(sizes: &str, packages: &str, image_info: &str) -> Self {
+        let mut image: BHImage = BHImage::default();
+
+        for line in sizes.lines() {
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            if columns.len() != 3 {
+                continue;
+            }
+            if let Ok(size) = columns[0].parse::<u64>() {
+                image.sizes.insert(columns[2].to_string(), size);
+                image.packages.insert(columns[2].to_string(), String::new());
+            }
+        }
+
+        for line in packages.lines() {
+            if let Some((name, version)) = Self::package_version(line.trim()) {
+                image.packages.insert(name, version);
+            }
+        }
+
+        for line in image_info.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim() == "IMAGESIZE" {
+                    image.image_size = value.trim().parse::<u64>().ok();
+                }
+            }
+        }
+
+        image
+    }
+}
+
+/*
+ * The difference between two snapshots of an image. All the sizes are
+ * in KiB as reported by the buildhistory.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BHImageDiff {
+    pub image: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub versions: Vec<(String, String, String)>,
+    pub sizes: Vec<(String, u64, u64)>,
+    pub image_size: (Option<u64>, Option<u64>),
+}
+
+impl BHImageDiff {
+    fn delta(old: u64, new: u64) -> i64 {
+        new as i64 - old as i64
+    }
+
+    pub fn new(image: &str, old: &BHImage, new: &BHImage) -> Self {
+        let mut diff: BHImageDiff = BHImageDiff {
+            image: image.to_string(),
+            image_size: (old.image_size, new.image_size),
+            ..Default::default()
+        };
+
+        let mut names: Vec<&String> = old.packages.keys().chain(new.packages.keys()).collect();
+        names.sort();
+        names.dedup();
+
+        for name in names {
+            match (old.packages.get(name), new.packages.get(name)) {
+                (None, Some(_v)) => diff.added.push(name.clone()),
+                (Some(_v), None) => diff.removed.push(name.clone()),
+                (Some(old_version), Some(new_version)) if old_version != new_version => {
+                    diff.versions
+                        .push((name.clone(), old_version.clone(), new_version.clone()));
+                }
+                _ => {}
+            }
+
+            match (old.sizes.get(name), new.sizes.get(name)) {
+                (Some(old_size), Some(new_size)) if old_size != new_size => {
+                    diff.sizes.push((name.clone(), *old_size, *new_size));
+                }
+                _ => {}
+            }
+        }
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.versions.is_empty()
+            && self.sizes.is_empty()
+            && self.image_size.0 == self.image_size.1
+    }
+
+    pub fn to_json(&self) -> Value {
+        let image_size: Value = match self.image_size {
+            (Some(old), Some(new)) => json!({
+                "old": old,
+                "new": new,
+                "delta": Self::delta(old, new),
+            }),
+            (old, new) => json!({ "old": old, "new": new }),
+        };
+        json!({
+            "image": self.image,
+            "added": self.added,
+            "removed": self.removed,
+            "versions": self.versions.iter().map(|(name, old, new)| json!({
+                "name": name,
+                "old": old,
+                "new": new,
+            })).collect::<Vec<Value>>(),
+            "sizes": self.sizes.iter().map(|(name, old, new)| json!({
+                "name": name,
+                "old": old,
+                "new": new,
+                "delta": Self::delta(*old, *new),
+            })).collect::<Vec<Value>>(),
+            "image_size": image_size,
+        })
+    }
+
+    pub fn summary(&self) -> Vec<String> {
+        let mut summary: Vec<String> = vec![format!("Image {}", self.image)];
+
+        if self.is_empty() {
+            summary.push(String::from("  No changes"));
+            return summary;
+        }
+
+        if let (Some(old), Some(new)) = self.image_size {
+            if old != new {
+                summary.push(format!(
+                    "  Image size changed from {} KiB to {} KiB ({:+} KiB)",
+                    old,
+                    new,
+                    Self::delta(old, new)
+                ));
+            }
+        }
+
+        for name in self.added.iter() {
+            summary.push(format!("  Added {}", name));
+        }
+
+        for name in self.removed.iter() {
+            summary.push(format!("  Removed {}", name));
+        }
+
+        for (name, old, new) in self.versions.iter() {
+            summary.push(format!("  {} changed from {} to {}", name, old, new));
+        }
+
+        for (name, old, new) in self.sizes.iter() {
+            summary.push(format!(
+                "  {} size changed from {} KiB to {} KiB ({:+} KiB)",
+                name,
+                old,
+                new,
+                Self::delta(*old, *new)
+            ));
+        }
+
+        summary
+    }
+}
+
+/*
+ * The buildhistory is a git repository where each build is committed. The
+ * content of a revision is read using git so the working tree of the
+ * buildhistory can be left as it is.
+ */
+pub struct BuildHistory<'a> {
+    cli: &'a Cli,
+    dir: PathBuf,
+}
+
+impl<'a> BuildHistory<'a> {
+    fn git(&self, args: Vec<String>) -> Result<String, BError> {
+        let mut cmd_line: Vec<String> = vec![
+            "git".to_string(),
+            "-C".to_string(),
+            self.dir.to_string_lossy().to_string(),
+        ];
+        cmd_line.extend(args);
+        self.cli.check_output(&cmd_line, &HashMap::new(), true)
+    }
+
+    fn show(&self, rev: &str, path: &str) -> Result<String, BError> {
+        self.git(vec!["show".to_string(), format!("{}:{}", rev, path)])
+    }
+
+    /*
+     * List the images in a revision. Every image is stored in a dir under
+     * images/<machine>/<libc>/<image> containing an image-info.txt
+     */
+    pub fn images(&self, rev: &str) -> Result<Vec<String>, BError> {
+        let output: String = self.git(vec![
+            "ls-tree".to_string(),
+            "-r".to_string(),
+            "--name-only".to_string(),
+            rev.to_string(),
+            "images".to_string(),
+        ])?;
+        Ok(output
+            .lines()
+            .filter_map(|l| l.trim().strip_suffix("/image-info.txt"))
+            .map(|l| l.to_string())
+            .collect())
+    }
+
+    pub fn image(&self, rev: &str, image_dir: &str) -> Result<BHImage, BError> {
+        Ok(BHImage::new(
+            &self.show(rev, &format!("{}/installed-package-sizes.txt", image_dir))?,
+            &self.show(rev, &format!("{}/installed-packages.txt", image_dir))?,
+            &self.show(rev, &format!("{}/image-info.txt", image_dir))?,
+        ))
+    }
+
+    /*
+     * Compare all the images found in either of the revisions. An image
+     * that is only available in one of the revisions is compared against
+     * an empty image.
+     */
+    pub fn diff(&self, rev1: &str, rev2: &str) -> Result<Vec<BHImageDiff>, BError> {
+        let old_images: Vec<String> = self.images(rev1)?;
+        let new_images: Vec<String> = self.images(rev2)?;
+        let mut images: Vec<&String> = old_images.iter().chain(new_images.iter()).collect();
+        images.sort();
+        images.dedup();
+
+        let mut diffs: Vec<BHImageDiff> = vec![];
+        for image_dir in images {
+            let old: BHImage = match old_images.contains(image_dir) {
+                true => self.image(rev1, image_dir)?,
+                false => BHImage::default(),
+            };
+            let new: BHImage = match new_images.contains(image_dir) {
+                true => self.image(rev2, image_dir)?,
+                false => BHImage::default(),
+            };
+            let name: &str = image_dir.rsplit('/').next().unwrap_or(image_dir);
+            diffs.push(BHImageDiff::new(name, &old, &new));
+        }
+        Ok(diffs)
+    }
+
+    pub fn new(cli: &'a Cli, dir: &PathBuf) -> Self {
+        BuildHistory {
+            cli,
+            dir: dir.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::fs::buildhistory::BHImage;
+    use crate::fs::BHImageDiff;
+
+    #[test]
+    fn test_buildhistory_package_version() {
+        assert_eq!(
+            BHImage::package_version("base-files_3.0.14-r0_raspberrypi4_64.ipk"),
+            Some((String::from("base-files"), String::from("3.0.14-r0")))
+        );
+        assert_eq!(
+            BHImage::package_version("busybox_1.36.1-r0_core2-64.ipk"),
+            Some((String::from("busybox"), String::from("1.36.1-r0")))
+        );
+        assert_eq!(
+            BHImage::package_version("busybox-1.36.1-r0.core2_64.rpm"),
+            Some((String::from("busybox"), String::from("1.36.1-r0")))
+        );
+        assert_eq!(BHImage::package_version("busybox.ipk"), None);
+    }
+
+    #[test]
+    fn test_buildhistory_image() {
+        let sizes: &str = "1024\tKiB busybox\n12\tKiB base-files\n";
+        let packages: &str =
+            "busybox_1.36.1-r0_core2-64.ipk\nbase-files_3.0.14-r0_qemux86_64.ipk\n";
+        let image_info: &str = "DISTRO = poky\nIMAGESIZE = 4096\n";
+        let image: BHImage = BHImage::new(sizes, packages, image_info);
+        assert_eq!(image.packages.get("busybox").unwrap(), "1.36.1-r0");
+        assert_eq!(image.sizes.get("busybox").unwrap(), &1024);
+        assert_eq!(image.image_size, Some(4096));
+        let rpm: BHImage = BHImage::new("", "busybox-1.36.1-r0.core2_64.rpm\n", "");
+        assert_eq!(rpm.packages.get("busybox").unwrap(), "1.36.1-r0");
+    }
+
+    #[test]
+    fn test_buildhistory_image_diff() {
+        let old: BHImage = BHImage::new(
+            "1024\tKiB busybox\n12\tKiB base-files\n100\tKiB dropbear\n",
+            "busybox_1.36.0-r0_core2-64.ipk\nbase-files_3.0.14-r0_qemux86_64.ipk\ndropbear_2022.83-r0_core2-64.ipk\n",
+            "IMAGESIZE = 4096\n",
+        );
+        let new: BHImage = BHImage::new(
+            "1100\tKiB busybox\n12\tKiB base-files\n200\tKiB openssh\n",
+            "busybox_1.36.1-r0_core2-64.ipk\nbase-files_3.0.14-r0_qemux86_64.ipk\nopenssh_9.6p1-r0_core2-64.ipk\n",
+            "IMAGESIZE = 4272\n",
+        );
+        let diff: BHImageDiff = BHImageDiff::new("core-image-minimal", &old, &new);
+        assert_eq!(diff.added, vec!["openssh"]);
+        assert_eq!(diff.removed, vec!["dropbear"]);
+        assert_eq!(
+            diff.versions,
+            vec![(
+                String::from("busybox"),
+                String::from("1.36.0-r0"),
+                String::from("1.36.1-r0")
+            )]
+        );
+        assert_eq!(diff.sizes, vec![(String::from("busybox"), 1024, 1100)]);
+        assert_eq!(
+            diff.summary(),
+            vec![
+                "Image core-image-minimal",
+                "  Image size changed from 4096 KiB to 4272 KiB (+176 KiB)",
+                "  Added openssh",
+                "  Removed dropbear",
+                "  busybox changed from 1.36.0-r0 to 1.36.1-r0",
+                "  busybox size changed from 1024 KiB to 1100 KiB (+76 KiB)",
+            ]
+        );
+        let json: Value = diff.to_json();
+        assert_eq!(json["image_size"]["delta"], 176);
+        assert_eq!(json["sizes"][0]["delta"], 76);
+        assert!(BHImageDiff::new("core-image-minimal", &old, &old).is_empty());
+    }
+}
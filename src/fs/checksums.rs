@@ -0,0 +1,240 @@
+use indexmap::IndexMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::BError;
+use crate::fs::Manifest;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    Added(u64),
+    Removed(u64),
+    Changed(u64, u64),
+}
+
+/*
+ * The sha256 checksums of a set of artifacts. The checksums recorded
+ * when collecting the artifacts are written in the same format as
+ * sha256sum so they can also be verified using 'sha256sum -c'.
+ */
+pub struct Checksums {
+    root: PathBuf,
+    entries: IndexMap<PathBuf, String>,
+}
+
+impl Checksums {
+    fn list_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), BError> {
+        let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<Result<Vec<PathBuf>, std::io::Error>>()?;
+        entries.sort();
+        for path in entries {
+            /*
+             * Links to directories like the 'latest' link are not followed
+             * since they would list the same artifacts twice.
+             */
+            if path.is_dir() {
+                if !path.is_symlink() {
+                    Self::list_files(&path, files)?;
+                }
+            } else if path.is_file() {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    fn size(path: &Path) -> u64 {
+        std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+    }
+
+    /*
+     * Calculate the checksums for the files. The files are stored
+     * relative to the root if they are located under it.
+     */
+    pub fn from_files(root: &Path, files: &Vec<PathBuf>) -> Result<Self, BError> {
+        let mut entries: IndexMap<PathBuf, String> = IndexMap::new();
+        for file in files.iter() {
+            if !file.is_file() {
+                continue;
+            }
+            let key: PathBuf = file.strip_prefix(root).unwrap_or(file).to_path_buf();
+            entries.insert(key, Manifest::checksum(&file.to_path_buf())?);
+        }
+        Ok(Checksums {
+            root: root.to_path_buf(),
+            entries,
+        })
+    }
+
+    pub fn scan(root: &Path) -> Result<Self, BError> {
+        if !root.is_dir() {
+            return Err(BError::IOError(format!(
+                "The artifacts directory '{}' does not exists",
+                root.display()
+            )));
+        }
+        let mut files: Vec<PathBuf> = vec![];
+        Self::list_files(root, &mut files)?;
+        Self::from_files(root, &files)
+    }
+
+    pub fn read(root: &Path, file: &Path) -> Result<Self, BError> {
+        let content: String = std::fs::read_to_string(file)?;
+        let mut entries: IndexMap<PathBuf, String> = IndexMap::new();
+        for line in content.lines() {
+            match line.split_once("  ") {
+                Some((checksum, path)) => {
+                    entries.insert(PathBuf::from(path), checksum.to_string());
+                }
+                None => {
+                    return Err(BError::ValueError(format!(
+                        "Invalid checksum '{}' in '{}'",
+                        line,
+                        file.display()
+                    )));
+                }
+            }
+        }
+        Ok(Checksums {
+            root: root.to_path_buf(),
+            entries,
+        })
+    }
+
+    pub fn write(&self, file: &Path) -> Result<(), BError> {
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content: String = self
+            .entries
+            .iter()
+            .map(|(path, checksum)| format!("{}  {}\n", checksum, path.display()))
+            .collect();
+        std::fs::write(file, content)?;
+        Ok(())
+    }
+
+    pub fn entries(&self) -> &IndexMap<PathBuf, String> {
+        &self.entries
+    }
+
+    /*
+     * Returns the artifacts that no longer match the checksums
+     * together with the reason, either missing or changed.
+     */
+    pub fn verify(&self) -> Result<Vec<(PathBuf, String)>, BError> {
+        let mut failed: Vec<(PathBuf, String)> = vec![];
+        for (path, checksum) in self.entries.iter() {
+            let file: PathBuf = self.root.join(path);
+            if !file.is_file() {
+                failed.push((path.clone(), String::from("missing")));
+            } else if &Manifest::checksum(&file)? != checksum {
+                failed.push((path.clone(), String::from("changed")));
+            }
+        }
+        Ok(failed)
+    }
+
+    /*
+     * Compare the artifacts with a newer set of artifacts. The sizes are
+     * read from the files so both sets of artifacts must still exist.
+     */
+    pub fn diff(&self, new: &Checksums) -> Vec<(PathBuf, Change)> {
+        let mut changes: Vec<(PathBuf, Change)> = vec![];
+        for (path, checksum) in self.entries.iter() {
+            let old_size: u64 = Self::size(&self.root.join(path));
+            match new.entries.get(path) {
+                Some(new_checksum) => {
+                    if new_checksum != checksum {
+                        let new_size: u64 = Self::size(&new.root.join(path));
+                        changes.push((path.clone(), Change::Changed(old_size, new_size)));
+                    }
+                }
+                None => {
+                    changes.push((path.clone(), Change::Removed(old_size)));
+                }
+            }
+        }
+        for path in new.entries.keys() {
+            if !self.entries.contains_key(path) {
+                let new_size: u64 = Self::size(&new.root.join(path));
+                changes.push((path.clone(), Change::Added(new_size)));
+            }
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use tempdir::TempDir;
+
+    use crate::fs::{Change, Checksums};
+
+    fn helper_write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path: PathBuf = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).expect("Failed to create dir");
+        std::fs::write(&path, content).expect("Failed to write file");
+        path
+    }
+
+    #[test]
+    fn test_checksums_verify() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: &Path = temp_dir.path();
+        let artifacts_dir: PathBuf = work_dir.join("artifacts");
+        let files: Vec<PathBuf> = vec![
+            helper_write(&artifacts_dir, "file1.txt", "file1"),
+            helper_write(&artifacts_dir, "dir/file2.txt", "file2"),
+            helper_write(&artifacts_dir, "file3.txt", "file3"),
+        ];
+        let record: PathBuf = work_dir.join("collected/task.sha256");
+        Checksums::from_files(&PathBuf::new(), &files)
+            .expect("Failed to calculate checksums")
+            .write(&record)
+            .expect("Failed to write checksums");
+        let checksums: Checksums =
+            Checksums::read(&PathBuf::new(), &record).expect("Failed to read checksums");
+        assert_eq!(checksums.entries().len(), 3);
+        assert!(checksums.verify().expect("Failed to verify").is_empty());
+        helper_write(&artifacts_dir, "file1.txt", "modified");
+        std::fs::remove_file(artifacts_dir.join("file3.txt")).expect("Failed to remove file");
+        assert_eq!(
+            checksums.verify().expect("Failed to verify"),
+            vec![
+                (artifacts_dir.join("file1.txt"), String::from("changed")),
+                (artifacts_dir.join("file3.txt"), String::from("missing")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_checksums_diff() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let work_dir: &Path = temp_dir.path();
+        let old_dir: PathBuf = work_dir.join("1.0.0-1");
+        let new_dir: PathBuf = work_dir.join("1.0.0-2");
+        helper_write(&old_dir, "same.txt", "same");
+        helper_write(&old_dir, "images/image.wic", "image");
+        helper_write(&old_dir, "removed.txt", "removed");
+        helper_write(&new_dir, "same.txt", "same");
+        helper_write(&new_dir, "images/image.wic", "image v2");
+        helper_write(&new_dir, "added.txt", "added");
+        std::os::unix::fs::symlink(&new_dir, work_dir.join("latest"))
+            .expect("Failed to create link");
+        let old: Checksums = Checksums::scan(&old_dir).expect("Failed to scan");
+        let new: Checksums = Checksums::scan(&work_dir.join("latest")).expect("Failed to scan");
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                (PathBuf::from("images/image.wic"), Change::Changed(5, 8)),
+                (PathBuf::from("removed.txt"), Change::Removed(7)),
+                (PathBuf::from("added.txt"), Change::Added(5)),
+            ]
+        );
+        assert!(Checksums::scan(&work_dir.join("missing")).is_err());
+    }
+}
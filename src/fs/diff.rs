@@ -0,0 +1,218 @@
+#[derive(Clone, Debug, PartialEq)]
+enum DiffOp {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/*
+ * Minimal line based diff used to present what changed when bakery is
+ * regenerating a file. The files are small (bitbake conf files) so a
+ * plain LCS table is good enough and we don't need any extra crate.
+ */
+pub struct Diff {
+    old: Vec<String>,
+    new: Vec<String>,
+}
+
+impl Diff {
+    fn ops(&self) -> Vec<DiffOp> {
+        let n: usize = self.old.len();
+        let m: usize = self.new.len();
+        let mut lcs: Vec<Vec<usize>> = vec![vec![0; m + 1]; n + 1];
+
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                if self.old[i] == self.new[j] {
+                    lcs[i][j] = lcs[i + 1][j + 1] + 1;
+                } else {
+                    lcs[i][j] = std::cmp::max(lcs[i + 1][j], lcs[i][j + 1]);
+                }
+            }
+        }
+
+        let mut ops: Vec<DiffOp> = Vec::new();
+        let mut i: usize = 0;
+        let mut j: usize = 0;
+        while i < n && j < m {
+            if self.old[i] == self.new[j] {
+                ops.push(DiffOp::Equal(i, j));
+                i += 1;
+                j += 1;
+            } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+                ops.push(DiffOp::Delete(i));
+                i += 1;
+            } else {
+                ops.push(DiffOp::Insert(j));
+                j += 1;
+            }
+        }
+        while i < n {
+            ops.push(DiffOp::Delete(i));
+            i += 1;
+        }
+        while j < m {
+            ops.push(DiffOp::Insert(j));
+            j += 1;
+        }
+
+        ops
+    }
+
+    fn hunk_range(start: usize, len: usize) -> String {
+        if len == 0 {
+            return format!("{},0", start);
+        }
+
+        if len == 1 {
+            return format!("{}", start + 1);
+        }
+
+        format!("{},{}", start + 1, len)
+    }
+
+    pub fn is_equal(&self) -> bool {
+        self.old == self.new
+    }
+
+    pub fn unified(&self, old_name: &str, new_name: &str, context: usize) -> String {
+        let ops: Vec<DiffOp> = self.ops();
+        let mut diff: String = String::new();
+
+        if self.is_equal() {
+            return diff;
+        }
+
+        diff.push_str(&format!("--- {}\n", old_name));
+        diff.push_str(&format!("+++ {}\n", new_name));
+
+        /*
+         * Group the changes into hunks. Changes that are closer to each other
+         * then two times the context will end up in the same hunk.
+         */
+        let changes: Vec<usize> = ops
+            .iter()
+            .enumerate()
+            .filter(|(_i, op)| !matches!(op, DiffOp::Equal(_, _)))
+            .map(|(i, _op)| i)
+            .collect();
+        let mut hunks: Vec<(usize, usize)> = Vec::new();
+        for index in changes {
+            let start: usize = index.saturating_sub(context);
+            let end: usize = std::cmp::min(index + context + 1, ops.len());
+            match hunks.last_mut() {
+                Some(last) if start <= last.1 => {
+                    last.1 = end;
+                }
+                _ => hunks.push((start, end)),
+            }
+        }
+
+        for (start, end) in hunks {
+            let mut lines: String = String::new();
+            let mut old_start: Option<usize> = None;
+            let mut new_start: Option<usize> = None;
+            let mut old_len: usize = 0;
+            let mut new_len: usize = 0;
+
+            /*
+             * Position in the old and new file where the hunk begins used
+             * when the hunk is only adding or removing lines
+             */
+            let mut old_pos: usize = 0;
+            let mut new_pos: usize = 0;
+            for op in ops[..start].iter() {
+                match op {
+                    DiffOp::Equal(_, _) => {
+                        old_pos += 1;
+                        new_pos += 1;
+                    }
+                    DiffOp::Delete(_) => old_pos += 1,
+                    DiffOp::Insert(_) => new_pos += 1,
+                }
+            }
+
+            for op in ops[start..end].iter() {
+                match op {
+                    DiffOp::Equal(i, j) => {
+                        old_start.get_or_insert(*i);
+                        new_start.get_or_insert(*j);
+                        old_len += 1;
+                        new_len += 1;
+                        lines.push_str(&format!(" {}\n", self.old[*i]));
+                    }
+                    DiffOp::Delete(i) => {
+                        old_start.get_or_insert(*i);
+                        old_len += 1;
+                        lines.push_str(&format!("-{}\n", self.old[*i]));
+                    }
+                    DiffOp::Insert(j) => {
+                        new_start.get_or_insert(*j);
+                        new_len += 1;
+                        lines.push_str(&format!("+{}\n", self.new[*j]));
+                    }
+                }
+            }
+
+            diff.push_str(&format!(
+                "@@ -{} +{} @@\n",
+                Self::hunk_range(old_start.unwrap_or(old_pos), old_len),
+                Self::hunk_range(new_start.unwrap_or(new_pos), new_len)
+            ));
+            diff.push_str(&lines);
+        }
+
+        diff
+    }
+
+    pub fn new(old: &str, new: &str) -> Self {
+        Diff {
+            old: old.lines().map(|l| l.to_string()).collect(),
+            new: new.lines().map(|l| l.to_string()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fs::Diff;
+
+    #[test]
+    fn test_diff_equal() {
+        let diff: Diff = Diff::new("A\nB\nC\n", "A\nB\nC\n");
+        assert!(diff.is_equal());
+        assert!(diff.unified("a", "b", 3).is_empty());
+    }
+
+    #[test]
+    fn test_diff_unified() {
+        let old: &str = "LINE_1\nLINE_2\nLINE_3\nLINE_4\nLINE_5\nLINE_6\nLINE_7\nLINE_8\nLINE_9\n";
+        let new: &str = "LINE_1\nLINE_2\nLINE_3\nLINE_4\nLINE_CHANGED\nLINE_6\nLINE_7\nLINE_8\nLINE_9\nLINE_10\n";
+        let diff: Diff = Diff::new(old, new);
+        assert!(!diff.is_equal());
+        let mut verify: String = String::new();
+        verify.push_str("--- local.conf.bak\n");
+        verify.push_str("+++ local.conf\n");
+        verify.push_str("@@ -2,8 +2,9 @@\n");
+        verify.push_str(" LINE_2\n");
+        verify.push_str(" LINE_3\n");
+        verify.push_str(" LINE_4\n");
+        verify.push_str("-LINE_5\n");
+        verify.push_str("+LINE_CHANGED\n");
+        verify.push_str(" LINE_6\n");
+        verify.push_str(" LINE_7\n");
+        verify.push_str(" LINE_8\n");
+        verify.push_str(" LINE_9\n");
+        verify.push_str("+LINE_10\n");
+        assert_eq!(diff.unified("local.conf.bak", "local.conf", 3), verify);
+    }
+
+    #[test]
+    fn test_diff_unified_add_only() {
+        let diff: Diff = Diff::new("", "LINE_1\n");
+        assert_eq!(
+            diff.unified("a", "b", 3),
+            "--- a\n+++ b\n@@ -0,0 +1 @@\n+LINE_1\n"
+        );
+    }
+}
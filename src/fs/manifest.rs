@@ -1,9 +1,17 @@
+use serde_json::Value;
+use sha2::{Digest, Sha256};
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
 
 use crate::error::BError;
 
+/*
+ * The manifest content is always specified as json in the build config
+ * and is converted to the format given by the extension of the manifest
+ * file when it is written. Supported formats are json, yaml, toml and env
+ * where env is a file with KEY=VALUE lines that can be sourced by a shell.
+ */
 pub struct Manifest {
     path: PathBuf,
     name: String,
@@ -28,7 +36,12 @@ impl Manifest {
             ))?
             .to_string();
 
-        if suffix != "json" {
+        if suffix != "json"
+            && suffix != "yaml"
+            && suffix != "yml"
+            && suffix != "toml"
+            && suffix != "env"
+        {
             return Err(BError::ParseManifestError(format!(
                 "Unsupported manifest extension '{}'!",
                 suffix
@@ -54,15 +67,123 @@ impl Manifest {
         &self.extension
     }
 
+    /*
+     * Calculate the sha256 checksum of a file. The checksum is used
+     * to record the collected artifacts in a manifest.
+     */
+    pub fn checksum(path: &PathBuf) -> Result<String, BError> {
+        let mut file: File = File::open(path)?;
+        let mut hasher: Sha256 = Sha256::new();
+        std::io::copy(&mut file, &mut hasher)?;
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    fn env_key(key: &str) -> String {
+        key.chars()
+            .map(|c| match c.is_ascii_alphanumeric() {
+                true => c.to_ascii_uppercase(),
+                false => '_',
+            })
+            .collect()
+    }
+
+    /*
+     * Flatten the json into KEY=VALUE lines. Nested keys are joined
+     * with '_' and arrays are using the index as key. The values are
+     * single quoted so the file can be sourced by a shell. A shell
+     * variable cannot start with a digit so a top level key starting
+     * with a digit, like the index of a top level array, is prefixed
+     * with '_'.
+     */
+    fn env(prefix: &str, value: &Value, lines: &mut Vec<String>) {
+        let key = |k: &str| -> String {
+            let k: String = Self::env_key(k);
+            match prefix.is_empty() {
+                true if k.starts_with(|c: char| c.is_ascii_digit()) => format!("_{}", k),
+                true => k,
+                false => format!("{}_{}", prefix, k),
+            }
+        };
+
+        match value {
+            Value::Object(map) => {
+                for (k, v) in map.iter() {
+                    Self::env(&key(k), v, lines);
+                }
+            }
+            Value::Array(array) => {
+                for (i, v) in array.iter().enumerate() {
+                    Self::env(&key(&i.to_string()), v, lines);
+                }
+            }
+            Value::Null => lines.push(format!("{}=''", prefix)),
+            Value::String(s) => {
+                lines.push(format!("{}='{}'", prefix, s.replace('\'', "'\\''")));
+            }
+            _ => lines.push(format!("{}='{}'", prefix, value)),
+        }
+    }
+
+    pub fn format(&self, json_str: &str) -> Result<String, BError> {
+        /*
+         * A json manifest is written as it is to keep the format
+         * from the build config
+         */
+        if self.extension == "json" {
+            return Ok(json_str.to_string());
+        }
+
+        let value: Value = serde_json::from_str(json_str)?;
+
+        /*
+         * Both toml and env are a list of keys so a single value
+         * like a string or null cannot be converted
+         */
+        if (self.extension == "toml" || self.extension == "env")
+            && !(value.is_object() || value.is_array())
+        {
+            return Err(BError::ParseManifestError(format!(
+                "Failed to convert manifest '{}' to {}, the content has to be an object or an array",
+                self.name, self.extension
+            )));
+        }
+
+        let content: String;
+        match self.extension.as_str() {
+            "yaml" | "yml" => {
+                content = serde_norway::to_string(&value).map_err(|err| {
+                    BError::ParseManifestError(format!(
+                        "Failed to convert manifest '{}' to yaml, {}",
+                        self.name, err
+                    ))
+                })?;
+            }
+            "toml" => {
+                content = toml::to_string(&value).map_err(|err| {
+                    BError::ParseManifestError(format!(
+                        "Failed to convert manifest '{}' to toml, {}",
+                        self.name, err
+                    ))
+                })?;
+            }
+            _ => {
+                let mut lines: Vec<String> = vec![];
+                Self::env("", &value, &mut lines);
+                content = lines.iter().map(|l| format!("{}\n", l)).collect::<String>();
+            }
+        }
+        Ok(content)
+    }
+
     pub fn write(&self, json_str: &str) -> Result<(), BError> {
+        let content: String = self.format(json_str)?;
+
         if let Some(parent_dir) = self.path.parent() {
             std::fs::create_dir_all(parent_dir)?;
         }
 
         let mut file: File = File::create(&self.path)?;
-
-        // Write the JSON string to the file.
-        file.write_all(json_str.as_bytes())?;
+        file.write_all(content.as_bytes())?;
 
         Ok(())
     }
@@ -128,4 +249,56 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_manifest_formats() {
+        let json_test_str: &str =
+            r#"{"name":"test","build":{"id":42,"release":true},"images":["a.wic","it's.wic"]}"#;
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let path: &Path = temp_dir.path();
+        let yaml: Manifest =
+            Manifest::new(&path.join("manifest.yaml")).expect("Failed to setup manifest!");
+        assert_eq!(
+            yaml.format(json_test_str).unwrap(),
+            "build:\n  id: 42\n  release: true\nimages:\n- a.wic\n- it's.wic\nname: test\n"
+        );
+        let toml: Manifest =
+            Manifest::new(&path.join("manifest.toml")).expect("Failed to setup manifest!");
+        assert_eq!(
+            toml.format(json_test_str).unwrap(),
+            "images = [\"a.wic\", \"it's.wic\"]\nname = \"test\"\n\n[build]\nid = 42\nrelease = true\n"
+        );
+        let env: Manifest =
+            Manifest::new(&path.join("manifest.env")).expect("Failed to setup manifest!");
+        env.write(json_test_str)
+            .expect("Failed to write manifest file!");
+        assert_eq!(
+            std::fs::read_to_string(env.path()).unwrap(),
+            "BUILD_ID='42'\nBUILD_RELEASE='true'\nIMAGES_0='a.wic'\nIMAGES_1='it'\\''s.wic'\nNAME='test'\n"
+        );
+        assert_eq!(
+            env.format(r#"{"1st":"a","list":[["b"]]}"#).unwrap(),
+            "_1ST='a'\nLIST_0_0='b'\n"
+        );
+        assert_eq!(env.format(r#"["a","b"]"#).unwrap(), "_0='a'\n_1='b'\n");
+        for content in [r#""test""#, "42", "null"] {
+            for manifest in [&env, &toml] {
+                let result: Result<String, BError> = manifest.format(content);
+                match result {
+                    Err(BError::ParseManifestError(msg)) => {
+                        assert_eq!(
+                            msg,
+                            format!(
+                                "Failed to convert manifest '{}' to {}, the content has to be an object or an array",
+                                manifest.name(),
+                                manifest.extension()
+                            )
+                        );
+                    }
+                    _ => panic!("Expected a ParseManifestError for '{}'", content),
+                }
+            }
+        }
+    }
 }
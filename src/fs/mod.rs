@@ -1,9 +1,21 @@
 pub mod archiver;
+pub mod bblog;
 pub mod bitbake;
+pub mod bmap;
+pub mod buildhistory;
+pub mod checksums;
 pub mod config;
+pub mod diff;
 pub mod manifest;
+pub mod retention;
 
-pub use archiver::Archiver;
+pub use archiver::{Archiver, Compressor};
+pub use bblog::BBLog;
 pub use bitbake::BitbakeConf;
+pub use bmap::BlockMap;
+pub use buildhistory::{BHImageDiff, BuildHistory};
+pub use checksums::{Change, Checksums};
 pub use config::ConfigFileReader;
+pub use diff::Diff;
 pub use manifest::Manifest;
+pub use retention::Retention;
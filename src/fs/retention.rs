@@ -0,0 +1,178 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::error::BError;
+
+pub const LATEST: &str = "latest";
+
+/*
+ * Retention policy for versioned artifact directories. All the
+ * directories next to each other, for example all the releases of
+ * a build config, are sorted by modification time and the ones
+ * outside of the policy are removed. A directory is removed if it is
+ * not one of the 'keep' newest or if it is older than 'max age' days.
+ * A value of zero disables that part of the policy.
+ */
+pub struct Retention {
+    keep: usize,
+    max_age: u64,
+}
+
+impl Retention {
+    pub fn new(keep: usize, max_age: u64) -> Self {
+        Retention { keep, max_age }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.keep != 0 || self.max_age != 0
+    }
+
+    fn modified(path: &Path) -> Result<SystemTime, BError> {
+        let metadata: std::fs::Metadata = std::fs::metadata(path)?;
+        Ok(metadata.modified()?)
+    }
+
+    /*
+     * Returns the directories in 'dir' that are outside of the policy.
+     * The directories listed in 'protected' and the directory that the
+     * 'latest' link is pointing to are never returned.
+     */
+    pub fn expired(&self, dir: &Path, protected: &Vec<PathBuf>) -> Result<Vec<PathBuf>, BError> {
+        if !self.enabled() || !dir.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut keep: Vec<PathBuf> = protected
+            .iter()
+            .filter_map(|p| p.canonicalize().ok())
+            .collect();
+        if let Ok(latest) = dir.join(LATEST).canonicalize() {
+            keep.push(latest);
+        }
+
+        let mut dirs: Vec<(SystemTime, PathBuf)> = vec![];
+        for entry in std::fs::read_dir(dir)? {
+            let path: PathBuf = entry?.path();
+            if path.is_symlink() || !path.is_dir() {
+                continue;
+            }
+            dirs.push((Self::modified(&path)?, path));
+        }
+        dirs.sort_by_key(|d| std::cmp::Reverse(d.0));
+
+        let now: SystemTime = SystemTime::now();
+        let max_age: Duration = Duration::from_secs(self.max_age * 24 * 60 * 60);
+        let mut expired: Vec<PathBuf> = vec![];
+        for (index, (modified, path)) in dirs.into_iter().enumerate() {
+            if keep.contains(&path.canonicalize()?) {
+                continue;
+            }
+
+            let too_many: bool = self.keep != 0 && index >= self.keep;
+            let too_old: bool = self.max_age != 0
+                && now.duration_since(modified).unwrap_or(Duration::ZERO) > max_age;
+            if too_many || too_old {
+                expired.push(path);
+            }
+        }
+
+        Ok(expired)
+    }
+
+    pub fn prune(&self, dir: &Path, protected: &Vec<PathBuf>) -> Result<Vec<PathBuf>, BError> {
+        let expired: Vec<PathBuf> = self.expired(dir, protected)?;
+        for path in expired.iter() {
+            std::fs::remove_dir_all(path)?;
+        }
+        Ok(expired)
+    }
+
+    /*
+     * Point the 'latest' link next to 'dir' to it. The link is relative
+     * so it is still valid if the workspace is moved or mounted at a
+     * different location inside docker.
+     */
+    pub fn link_latest(dir: &Path) -> Result<PathBuf, BError> {
+        let parent: &Path = dir.parent().ok_or_else(|| {
+            BError::IOError(format!("No parent directory for '{}'", dir.display()))
+        })?;
+        let name: &std::ffi::OsStr = dir.file_name().ok_or_else(|| {
+            BError::IOError(format!("Invalid artifacts directory '{}'", dir.display()))
+        })?;
+        let link: PathBuf = parent.join(LATEST);
+
+        if link.is_symlink() {
+            std::fs::remove_file(&link)?;
+        } else if link.exists() {
+            return Err(BError::IOError(format!(
+                "Cannot create link '{}', the path already exists",
+                link.display()
+            )));
+        }
+
+        std::os::unix::fs::symlink(name, &link)?;
+        Ok(link)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+    use tempdir::TempDir;
+
+    use crate::fs::Retention;
+
+    fn helper_create_dir(dir: &Path, name: &str, age: u64) -> PathBuf {
+        let path: PathBuf = dir.join(name);
+        std::fs::create_dir_all(&path).expect("Failed to create dir");
+        let modified: SystemTime = SystemTime::now() - Duration::from_secs(age * 24 * 60 * 60);
+        File::open(&path)
+            .expect("Failed to open dir")
+            .set_modified(modified)
+            .expect("Failed to set modified time");
+        path
+    }
+
+    #[test]
+    fn test_retention_keep() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let dir: &Path = temp_dir.path();
+        helper_create_dir(dir, "1.0.0-1", 4);
+        helper_create_dir(dir, "1.0.0-2", 3);
+        let current: PathBuf = helper_create_dir(dir, "1.0.0-3", 2);
+        helper_create_dir(dir, "1.0.0-4", 1);
+        Retention::link_latest(&current).expect("Failed to link latest");
+        let pruned: Vec<PathBuf> = Retention::new(2, 0)
+            .prune(dir, &vec![])
+            .expect("Failed to prune");
+        assert_eq!(pruned, vec![dir.join("1.0.0-2"), dir.join("1.0.0-1")]);
+        assert!(dir.join("1.0.0-3").exists());
+        assert!(dir.join("1.0.0-4").exists());
+        assert_eq!(
+            std::fs::read_link(dir.join("latest")).expect("Failed to read link"),
+            PathBuf::from("1.0.0-3")
+        );
+    }
+
+    #[test]
+    fn test_retention_max_age() {
+        let temp_dir: TempDir =
+            TempDir::new("bakery-test-dir").expect("Failed to create temp directory");
+        let dir: &Path = temp_dir.path();
+        helper_create_dir(dir, "old", 40);
+        let protected: PathBuf = helper_create_dir(dir, "protected", 50);
+        helper_create_dir(dir, "new", 10);
+        let retention: Retention = Retention::new(0, 30);
+        let expired: Vec<PathBuf> = retention
+            .expired(dir, &vec![protected])
+            .expect("Failed to list expired");
+        assert_eq!(expired, vec![dir.join("old")]);
+        assert!(Retention::new(0, 0)
+            .expired(dir, &vec![])
+            .expect("Failed to list expired")
+            .is_empty());
+    }
+}
@@ -117,6 +117,37 @@ impl Helper {
         Ok(())
     }
 
+    /*
+     * List the regular files in a cpio newc archive by reading the headers
+     * and skipping the content of each entry.
+     */
+    pub fn list_files_in_cpio(mut reader: Box<dyn std::io::Read>) -> Result<Vec<PathBuf>, BError> {
+        let mut files: Vec<PathBuf> = Vec::new();
+        loop {
+            let mut header: [u8; 110] = [0; 110];
+            reader.read_exact(&mut header)?;
+            assert_eq!(&header[0..6], b"070701");
+            let field = |i: usize| -> usize {
+                let start: usize = 6 + i * 8;
+                let hex: &str = std::str::from_utf8(&header[start..start + 8]).unwrap();
+                usize::from_str_radix(hex, 16).unwrap()
+            };
+            let (mode, size, name_size) = (field(1), field(6), field(11));
+            let mut name: Vec<u8> = vec![0; name_size + (4 - (110 + name_size) % 4) % 4];
+            reader.read_exact(&mut name)?;
+            let name: String = String::from_utf8_lossy(&name[..name_size - 1]).to_string();
+            if name == "TRAILER!!!" {
+                break;
+            }
+            let mut content: Vec<u8> = vec![0; size + (4 - size % 4) % 4];
+            reader.read_exact(&mut content)?;
+            if mode & 0o170000 == 0o100000 {
+                files.push(PathBuf::from(name));
+            }
+        }
+        Ok(files)
+    }
+
     pub fn list_files_in_archive(
         archive: &Archiver,
         work_dir: &Path,
@@ -135,13 +166,19 @@ impl Helper {
             )));
         }
 
-        if archive.extension() == "tar" {
+        if archive.extension() == "tar" || archive.extension() == "cpio" {
             let file: File = File::open(archive.path())?;
-            let mut tar: tar::Archive<Box<dyn std::io::Read>>;
+            let reader: Box<dyn std::io::Read>;
             if archive.compression() == "gz" {
-                tar = tar::Archive::new(Box::new(flate2::read::GzDecoder::new(file)));
+                reader = Box::new(flate2::read::GzDecoder::new(file));
             } else if archive.compression() == "bz2" {
-                tar = tar::Archive::new(Box::new(bzip2::read::BzDecoder::new(file)));
+                reader = Box::new(bzip2::read::BzDecoder::new(file));
+            } else if archive.compression() == "xz" {
+                reader = Box::new(xz2::read::XzDecoder::new(file));
+            } else if archive.compression() == "zst" {
+                reader = Box::new(zstd::Decoder::new(file)?);
+            } else if archive.compression().is_empty() {
+                reader = Box::new(file);
             } else {
                 return Err(BError::ArchiverError(format!(
                     "Unsupported compression '{}'!",
@@ -149,6 +186,11 @@ impl Helper {
                 )));
             }
 
+            if archive.extension() == "cpio" {
+                return Helper::list_files_in_cpio(reader);
+            }
+
+            let mut tar: tar::Archive<Box<dyn std::io::Read>> = tar::Archive::new(reader);
             tar.unpack(unpack_dir.to_str().unwrap()).unwrap();
 
             Helper::list_files_in_dir(&unpack_dir, &mut archived_files, &unpack_dir)
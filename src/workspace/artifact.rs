@@ -9,6 +9,7 @@ use std::path::PathBuf;
 pub struct WsArtifactsHandler {
     data: WsArtifactData,
     children: Vec<WsArtifactsHandler>,
+    work_dir: PathBuf,
 }
 
 impl WsArtifactsHandler {
@@ -31,6 +32,7 @@ impl WsArtifactsHandler {
         Ok(WsArtifactsHandler {
             data: artifact_data,
             children,
+            work_dir: build_data.settings().work_dir(),
         })
     }
 
@@ -49,6 +51,10 @@ impl WsArtifactsHandler {
     pub fn children(&self) -> &Vec<WsArtifactsHandler> {
         &self.children
     }
+
+    pub fn work_dir(&self) -> &PathBuf {
+        &self.work_dir
+    }
 }
 
 #[cfg(test)]
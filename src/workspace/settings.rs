@@ -1,6 +1,7 @@
 use crate::configs::Context;
 use crate::constants::BkryConstants;
 use crate::error::BError;
+use crate::fs::Retention;
 use crate::{configs::WsSettings, executers::DockerImage};
 
 use std::path::{Path, PathBuf};
@@ -113,10 +114,75 @@ impl WsSettingsHandler {
         self.append_dir(&self.ws_settings.cache_dir)
     }
 
-    pub fn artifacts_dir(&self) -> PathBuf {
+    pub fn artifacts_root_dir(&self) -> PathBuf {
         self.append_dir(&self.ws_settings.artifacts_dir)
     }
 
+    /*
+     * When an artifacts layout is defined the artifacts are collected
+     * to a sub directory of the artifacts dir, for example one directory
+     * per build config and release.
+     */
+    pub fn artifacts_dir(&self) -> PathBuf {
+        let root: PathBuf = self.artifacts_root_dir();
+        if self.ws_settings.artifacts_layout.is_empty() {
+            return root;
+        }
+        root.join(&self.ws_settings.artifacts_layout)
+    }
+
+    /*
+     * The directory containing all the versions of the artifacts dir
+     * defined by the layout. This is where the 'latest' link is created
+     * and the retention policy is applied. Without a layout the artifacts
+     * are collected straight into the artifacts dir and there is nothing
+     * to keep track of. The layout is only versioned when the last dir is
+     * using a context variable and the dirs above it are per build config,
+     * that is using '$#[BKRY_NAME]'. Otherwise the dirs next to the
+     * artifacts dir could belong to the other build configs and must never
+     * be pruned, for example with '$#[BKRY_PLATFORM_RELEASE]/$#[BKRY_NAME]'.
+     */
+    pub fn artifacts_versions_dir(&self) -> Option<PathBuf> {
+        let name: &str = "$#[BKRY_NAME]";
+        let layout: &Path = Path::new(&self.ws_settings.artifacts_layout_unexpanded);
+        let dirs: Vec<String> = layout
+            .components()
+            .filter(|c| matches!(c, std::path::Component::Normal(_)))
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        match dirs.split_last() {
+            Some((version, parents))
+                if version.contains("$#[")
+                    && !version.contains(name)
+                    && parents.iter().any(|d| d.contains(name)) =>
+            {
+                self.artifacts_dir().parent().map(|p| p.to_path_buf())
+            }
+            _ => None,
+        }
+    }
+
+    pub fn artifacts_latest(&self) -> bool {
+        self.ws_settings.artifacts_latest == "true"
+    }
+
+    pub fn artifacts_checksums(&self) -> bool {
+        self.ws_settings.artifacts_checksums == "true"
+    }
+
+    pub fn artifacts_retention(&self) -> Retention {
+        Retention::new(
+            self.ws_settings
+                .artifacts_keep
+                .parse::<usize>()
+                .unwrap_or(0),
+            self.ws_settings
+                .artifacts_max_age
+                .parse::<u64>()
+                .unwrap_or(0),
+        )
+    }
+
     pub fn layers_dir(&self) -> PathBuf {
         self.append_dir(&self.ws_settings.layers_dir)
     }
@@ -168,6 +234,80 @@ impl WsSettingsHandler {
         }
     }
 
+    pub fn docker_hermetic(&self) -> bool {
+        self.ws_settings.docker_hermetic == "true"
+    }
+
+    pub fn cache_sstate_mirrors(&self) -> &Vec<String> {
+        &self.ws_settings.cache_sstate_mirrors
+    }
+
+    pub fn cache_premirrors(&self) -> &Vec<String> {
+        &self.ws_settings.cache_premirrors
+    }
+
+    pub fn cache_hashserve(&self) -> &str {
+        &self.ws_settings.cache_hashserve
+    }
+
+    pub fn cache_signature_handler(&self) -> &str {
+        &self.ws_settings.cache_signature_handler
+    }
+
+    pub fn cache_arch_layout(&self) -> bool {
+        self.ws_settings.cache_arch_layout == "true"
+    }
+
+    pub fn cache_shared(&self) -> bool {
+        !self.ws_settings.cache_sstate_mirrors.is_empty()
+            || !self.ws_settings.cache_premirrors.is_empty()
+            || !self.ws_settings.cache_hashserve.is_empty()
+            || self.services_hashserv()
+            || self.services_prserv()
+    }
+
+    pub fn services_dir(&self) -> PathBuf {
+        self.cache_dir().join("services")
+    }
+
+    pub fn services_hashserv(&self) -> bool {
+        self.ws_settings.services_hashserv == "true"
+    }
+
+    pub fn services_prserv(&self) -> bool {
+        self.ws_settings.services_prserv == "true"
+    }
+
+    pub fn hashserv_bind(&self) -> String {
+        if self.ws_settings.services_hashserv_bind.is_empty() {
+            return format!(
+                "unix://{}",
+                self.services_dir().join("hashserve.sock").display()
+            );
+        }
+        self.ws_settings.services_hashserv_bind.clone()
+    }
+
+    pub fn hashserv_db(&self) -> PathBuf {
+        self.cache_dir().join("hashserv.db")
+    }
+
+    pub fn prserv_host(&self) -> String {
+        format!("localhost:{}", self.ws_settings.services_prserv_port)
+    }
+
+    pub fn prserv_port(&self) -> &str {
+        &self.ws_settings.services_prserv_port
+    }
+
+    pub fn prserv_db(&self) -> PathBuf {
+        self.cache_dir().join("prserv.sqlite3")
+    }
+
+    pub fn logs_capture(&self) -> bool {
+        self.ws_settings.logs_capture == "true"
+    }
+
     pub fn mode(&self) -> Mode {
         match self.ws_settings.mode.as_str() {
             "default" => {
@@ -407,6 +547,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_settings_docker_hermetic() {
+        let json_test_str = r#"
+        {
+            "version": "6",
+            "docker": {
+                "hermetic": "true"
+            }
+        }"#;
+        let work_dir: PathBuf = PathBuf::from("/workspace");
+        let settings: WsSettingsHandler =
+            WsSettingsHandler::new(work_dir, Helper::setup_ws_settings(json_test_str), None);
+        assert!(settings.docker_hermetic());
+    }
+
     #[test]
     fn test_settings_default_supported_builds() {
         let json_test_str = r#"
@@ -529,4 +684,108 @@ mod tests {
         assert_eq!(settings.scripts_dir(), work_dir.join("scripts_var3"));
         assert_eq!(settings.cache_dir(), work_dir.join("cache_var4"));
     }
+
+    #[test]
+    fn test_settings_artifacts_layout() {
+        let json_test_str = r#"
+        {
+            "version": "6",
+            "artifacts": {
+                "layout": "$#[BKRY_NAME]/$#[BKRY_PLATFORM_RELEASE]",
+                "keep": "5"
+            }
+        }"#;
+        let work_dir: PathBuf = PathBuf::from("/workspace");
+        let mut settings: WsSettingsHandler = WsSettingsHandler::new(
+            work_dir.clone(),
+            Helper::setup_ws_settings(json_test_str),
+            None,
+        );
+        let variables: IndexMap<String, String> = indexmap! {
+            "BKRY_NAME".to_string() => "default".to_string(),
+            "BKRY_PLATFORM_RELEASE".to_string() => "1.2.3-4".to_string()
+        };
+        let ctx: Context = Context::new(&variables);
+        settings.expand_ctx(&ctx).unwrap();
+        assert_eq!(settings.artifacts_root_dir(), work_dir.join("artifacts"));
+        assert_eq!(
+            settings.artifacts_dir(),
+            work_dir.join("artifacts/default/1.2.3-4")
+        );
+        assert_eq!(
+            settings.artifacts_versions_dir(),
+            Some(work_dir.join("artifacts/default"))
+        );
+        assert!(settings.artifacts_latest());
+        assert!(settings.artifacts_retention().enabled());
+    }
+
+    #[test]
+    fn test_settings_default_artifacts_layout() {
+        let json_test_str = r#"
+        {
+            "version": "6"
+        }"#;
+        let work_dir: PathBuf = PathBuf::from("/workspace");
+        let settings: WsSettingsHandler =
+            WsSettingsHandler::new(work_dir, Helper::setup_ws_settings(json_test_str), None);
+        assert_eq!(settings.artifacts_dir(), settings.artifacts_root_dir());
+        assert_eq!(settings.artifacts_versions_dir(), None);
+        assert!(!settings.artifacts_retention().enabled());
+        assert!(!settings.artifacts_checksums());
+    }
+
+    #[test]
+    fn test_settings_single_dir_artifacts_layout() {
+        let json_test_str = r#"
+        {
+            "version": "6",
+            "artifacts": {
+                "layout": "$#[BKRY_NAME]",
+                "keep": "1"
+            }
+        }"#;
+        let work_dir: PathBuf = PathBuf::from("/workspace");
+        let mut settings: WsSettingsHandler = WsSettingsHandler::new(
+            work_dir.clone(),
+            Helper::setup_ws_settings(json_test_str),
+            None,
+        );
+        let variables: IndexMap<String, String> = indexmap! {
+            "BKRY_NAME".to_string() => "default".to_string()
+        };
+        let ctx: Context = Context::new(&variables);
+        settings.expand_ctx(&ctx).unwrap();
+        assert_eq!(settings.artifacts_dir(), work_dir.join("artifacts/default"));
+        assert_eq!(settings.artifacts_versions_dir(), None);
+    }
+
+    #[test]
+    fn test_settings_release_first_artifacts_layout() {
+        let json_test_str = r#"
+        {
+            "version": "6",
+            "artifacts": {
+                "layout": "$#[BKRY_PLATFORM_RELEASE]/$#[BKRY_NAME]",
+                "keep": "1"
+            }
+        }"#;
+        let work_dir: PathBuf = PathBuf::from("/workspace");
+        let mut settings: WsSettingsHandler = WsSettingsHandler::new(
+            work_dir.clone(),
+            Helper::setup_ws_settings(json_test_str),
+            None,
+        );
+        let variables: IndexMap<String, String> = indexmap! {
+            "BKRY_NAME".to_string() => "default".to_string(),
+            "BKRY_PLATFORM_RELEASE".to_string() => "1.2.3-4".to_string()
+        };
+        let ctx: Context = Context::new(&variables);
+        settings.expand_ctx(&ctx).unwrap();
+        assert_eq!(
+            settings.artifacts_dir(),
+            work_dir.join("artifacts/1.2.3-4/default")
+        );
+        assert_eq!(settings.artifacts_versions_dir(), None);
+    }
 }
@@ -1,4 +1,4 @@
-use crate::cli::Cli;
+use crate::cli::{Cli, TraceSpan};
 use crate::collector::{Collected, Collector, CollectorFactory};
 use crate::configs::Context;
 use crate::data::{TType, WsBuildData, WsTaskData};
@@ -6,11 +6,12 @@ use crate::error::BError;
 use crate::executers::{
     BBBuildExecuter, BBCleanExecuter, NonBBBuildExecuter, NonBBCleanExecuter, TaskExecuter,
 };
-use crate::fs::ConfigFileReader;
+use crate::fs::{Checksums, ConfigFileReader};
 use crate::workspace::WsArtifactsHandler;
 
 use serde_json::Value;
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 pub struct WsTaskHandler {
     data: WsTaskData,
@@ -76,7 +77,51 @@ impl WsTaskHandler {
         executer.exec(env_variables, dry_run, interactive)?;
 
         if !dry_run {
-            self.collect(cli, build_data)?;
+            let span: TraceSpan = cli
+                .trace()
+                .begin(&format!("collect {}", self.data.name()), "artifacts");
+            let result: Result<Vec<Collected>, BError> = self.collect(cli, build_data);
+            cli.trace().end(span);
+            result?;
+        }
+
+        Ok(())
+    }
+
+    pub fn fetch(
+        &self,
+        cli: &Cli,
+        build_data: &WsBuildData,
+        bb_variables: &Vec<String>,
+        env_variables: &HashMap<String, String>,
+        interactive: bool,
+        force: bool,
+    ) -> Result<(), BError> {
+        if !force && self.data.disabled() {
+            cli.info(format!("Task '{}' disabled, skipping", self.data.name()));
+            return Ok(());
+        }
+
+        if !force && !self.data.condition() {
+            cli.info(format!(
+                "Task condition for '{}' is not met, skipping",
+                self.data.name()
+            ));
+            return Ok(());
+        }
+
+        match self.data.ttype() {
+            TType::Bitbake => {
+                let executer: BBBuildExecuter =
+                    BBBuildExecuter::fetch(cli, &self.data, build_data.bitbake(), bb_variables);
+                executer.exec(env_variables, false, interactive)?;
+            }
+            TType::NonBitbake => {
+                cli.info(format!(
+                    "Task '{}' is not a bitbake task, nothing to fetch",
+                    self.data.name()
+                ));
+            }
         }
 
         Ok(())
@@ -131,13 +176,29 @@ impl WsTaskHandler {
             ));
             for artifact in self.artifacts.iter() {
                 let collector: Box<dyn Collector> = CollectorFactory::create(artifact, Some(cli))?;
-                let mut c: Vec<Collected> = collector.collect(
+                let mut c: Vec<Collected> = collector.collect_with(
                     self.data.build_dir(),
                     &build_data.settings().artifacts_dir(),
+                    &collected,
                 )?;
                 collected.append(&mut c);
             }
 
+            /*
+             * Record the checksums of the collected artifacts so that they
+             * can be verified later on using 'bakery artifacts verify'. It
+             * is reading all the collected files again which takes time for
+             * large images so it is only done when enabled in the settings.
+             */
+            if build_data.settings().artifacts_checksums() {
+                let files: Vec<PathBuf> = collected.iter().map(|c| c.dest.clone()).collect();
+                Checksums::from_files(&PathBuf::new(), &files)?.write(
+                    &build_data
+                        .collected_dir()
+                        .join(format!("{}.sha256", self.data.name())),
+                )?;
+            }
+
             cli.info(format!(
                 "All artifacts for task '{}' have been collected to '{}'",
                 self.data.name(),
@@ -883,6 +944,10 @@ mod tests {
             "DL_DIR ?= \"{}/.cache/download\"\n",
             work_dir.to_string_lossy().to_string()
         ));
+        local_conf_content.push_str(&format!(
+            "include {}/conf/local.conf.user\n",
+            build_dir.to_string_lossy().to_string()
+        ));
         let mut bblayers_conf_content: String = String::from("");
         bblayers_conf_content.push_str("LCONF_VERSION=\"7\"\n");
         bblayers_conf_content.push_str("BBPATH=\"${TOPDIR}\"\n");
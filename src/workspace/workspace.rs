@@ -4,9 +4,10 @@ use std::fs::DirEntry;
 use std::io::Error;
 use std::path::PathBuf;
 
+use crate::cli::Cli;
 use crate::data::{WsContextData, WsProductData};
 use crate::error::BError;
-use crate::fs::ConfigFileReader;
+use crate::fs::{ConfigFileReader, Retention};
 use crate::workspace::{WsBuildConfigHandler, WsSettingsHandler};
 
 pub struct Workspace {
@@ -199,6 +200,42 @@ impl Workspace {
         self.config().ctx()
     }
 
+    /*
+     * When the artifacts are collected using a layout the 'latest' link
+     * is updated to point to the artifacts from this build and the
+     * retention policy is applied to the artifacts from earlier builds.
+     */
+    pub fn update_artifacts(&self, cli: &Cli) -> Result<(), BError> {
+        let artifacts_dir: PathBuf = self.settings().artifacts_dir();
+        let versions_dir: PathBuf;
+        match self.settings().artifacts_versions_dir() {
+            Some(dir) => {
+                versions_dir = dir;
+            }
+            None => {
+                return Ok(());
+            }
+        }
+
+        if !artifacts_dir.is_dir() {
+            return Ok(());
+        }
+
+        if self.settings().artifacts_latest() {
+            let link: PathBuf = Retention::link_latest(&artifacts_dir)?;
+            cli.debug(format!("Updated link {:?}", link));
+        }
+
+        let pruned: Vec<PathBuf> = self
+            .settings()
+            .artifacts_retention()
+            .prune(&versions_dir, &vec![artifacts_dir])?;
+        for dir in pruned.iter() {
+            cli.info(format!("Removed artifacts {:?}", dir));
+        }
+        Ok(())
+    }
+
     pub fn verify_ws(&mut self) -> Result<(), BError> {
         self.config.build_data().settings().verify_ws()?;
         Ok(())